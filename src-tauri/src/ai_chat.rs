@@ -1,21 +1,29 @@
 use async_openai::{
-    config::OpenAIConfig,
     types::{
         ChatCompletionMessageToolCall, ChatCompletionNamedToolChoice,
         ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestSystemMessageArgs,
         ChatCompletionRequestToolMessageArgs, ChatCompletionRequestToolMessageContent,
         ChatCompletionRequestUserMessageArgs, ChatCompletionToolArgs,
         ChatCompletionToolChoiceOption, ChatCompletionToolType, CreateChatCompletionRequestArgs,
-        FunctionCall, FunctionName, FunctionObject,
+        CreateChatCompletionResponse, FunctionCall, FunctionName, FunctionObject, Stop,
     },
-    Client,
 };
+use futures::future::join_all;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::backend::application::event_bus::EventBus;
-
-use super::api_config::ApiConfig;
+use crate::backend::domain::SessionOverrides;
+
+use super::api_config::{ApiConfig, ApiDialect, PromptMode};
+use super::api_failover::{parse_retry_after, ApiCallError, RetryPolicy};
+use super::change_set::ChangeSetService;
+use super::tool_approval::{ApprovalDecision, ApprovalPolicy, ToolApprovalService};
+use super::tool_loop::{RepeatedCallGuard, ToolCallError, ToolCallOutcome, ToolLoopPolicy};
+use super::tool_profiles::ToolSettingsService;
+use super::chat_history::ServedBy;
+use super::http_client::HttpClientFactory;
+use super::instruct_template::InstructTemplate;
 
 /// 聊天消息角色 (为前端兼容性保留)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +87,16 @@ pub enum StopSequence {
     Multiple(Vec<String>),
 }
 
+impl StopSequence {
+    /// 展开为停止序列列表
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            StopSequence::Single(value) => vec![value.clone()],
+            StopSequence::Multiple(values) => values.clone(),
+        }
+    }
+}
+
 /// 使用统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Usage {
@@ -112,6 +130,10 @@ pub struct ChatCompletionResponse {
     /// 用于保存工具调用的完整上下文
     #[serde(skip_serializing_if = "Option::is_none")]
     pub intermediate_messages: Option<Vec<ChatMessage>>,
+
+    /// 实际生成回复的 API 配置与模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<ServedBy>,
 }
 
 /// 聊天完成请求 (兼容性)
@@ -128,6 +150,22 @@ pub struct ChatCompletionRequest {
     pub stream: Option<bool>,
     pub tools: Option<Vec<ChatTool>>,
     pub tool_choice: Option<ToolChoice>,
+    /// 以下为 OpenAI 标准之外的采样参数（vLLM / llama.cpp / KoboldCpp 等支持）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repetition_penalty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+}
+
+impl ChatCompletionRequest {
+    /// 是否包含 async-openai 无法表达的扩展采样参数
+    fn has_extended_sampling(&self) -> bool {
+        self.top_k.is_some() || self.min_p.is_some() || self.repetition_penalty.is_some()
+    }
 }
 
 /// 工具参数定义
@@ -176,25 +214,20 @@ pub struct ChatTool {
     pub function: ToolFunction,
 }
 
+/// 一轮工具调用的处理结果
+enum ToolRound {
+    /// 响应中没有工具调用
+    None,
+    /// 已执行工具调用，需要将结果发送回 AI
+    Executed,
+    /// 所有调用都是被拦截的重复调用
+    Stalled,
+}
+
 /// AI聊天服务
 pub struct AIChatService;
 
 impl AIChatService {
-    /// 创建带自定义配置的客户端
-    async fn create_client_with_config(
-        api_config: &ApiConfig,
-    ) -> Result<Client<OpenAIConfig>, String> {
-        let base_url = Self::normalize_api_base(&api_config.endpoint);
-
-        // 创建自定义配置
-        let config = OpenAIConfig::new()
-            .with_api_key(&api_config.key)
-            .with_api_base(&base_url);
-
-        let client = Client::with_config(config);
-        Ok(client)
-    }
-
     /// 将前端消息转换为 async-openai 消息格式
     fn convert_messages_to_openai(
         messages: &[ChatMessage],
@@ -300,6 +333,16 @@ impl AIChatService {
             .collect()
     }
 
+    fn convert_stop_to_openai(stop: &StopSequence) -> Option<Stop> {
+        match stop {
+            StopSequence::Single(value) if !value.is_empty() => Some(Stop::String(value.clone())),
+            StopSequence::Multiple(values) if !values.is_empty() => {
+                Some(Stop::StringArray(values.clone()))
+            }
+            _ => None,
+        }
+    }
+
     fn convert_tool_choice_to_openai(
         choice: &ToolChoice,
     ) -> Option<ChatCompletionToolChoiceOption> {
@@ -403,6 +446,7 @@ impl AIChatService {
                     total_tokens: 0,
                 }),
             intermediate_messages: None, // 初始时没有中间消息，在工具调用时会填充
+            served_by: None,
         }
     }
 
@@ -412,236 +456,693 @@ impl AIChatService {
         request: &ChatCompletionRequest,
         app_handle: Option<&tauri::AppHandle>,
     ) -> Result<ChatCompletionResponse, String> {
-        let client = Self::create_client_with_config(api_config).await?;
+        let character_uuid = crate::character_state::CHARACTER_STATE.get_current_character();
+        Self::create_chat_completion_with_failover(
+            std::slice::from_ref(api_config),
+            request,
+            app_handle,
+            &ToolLoopPolicy::default(),
+            character_uuid.as_deref(),
+            &SessionOverrides::default(),
+        )
+        .await
+    }
+
+    /// 沿故障转移链创建聊天完成请求
+    ///
+    /// 每一轮请求（包括工具调用后的续轮）都会对可重试错误进行指数退避重试，
+    /// 当前配置仍失败时切换到链中的下一个配置，并在后续轮次继续使用该配置。
+    /// 第一个配置使用请求中的模型，备用配置使用各自配置的模型。
+    ///
+    /// 工具调用轮数达到上限或模型反复发起相同调用时停止循环，
+    /// 已执行的工具调用与结果仍作为中间消息返回。
+    /// 工具调用作用于 `character_uuid` 指定的角色，并按该会话设置叠加 `overrides` 筛选工具。
+    pub async fn create_chat_completion_with_failover(
+        chain: &[ApiConfig],
+        request: &ChatCompletionRequest,
+        app_handle: Option<&tauri::AppHandle>,
+        tool_loop: &ToolLoopPolicy,
+        character_uuid: Option<&str>,
+        overrides: &SessionOverrides,
+    ) -> Result<ChatCompletionResponse, String> {
+        if chain.is_empty() {
+            return Err("没有可用的API配置".to_string());
+        }
+
+        let policy = RetryPolicy::default();
         let mut messages = request.messages.clone();
-        let max_iterations = 5; // 防止无限循环
         let mut iteration = 0;
+        let mut active_index = 0;
+        let mut repeated_calls = RepeatedCallGuard::new(tool_loop.max_identical_calls);
 
         // 收集中间消息（包括 assistant with tool_calls 和 tool results）
         let mut intermediate_messages: Vec<ChatMessage> = Vec::new();
 
         loop {
-            if iteration >= max_iterations {
-                return Err("工具调用循环次数超过限制".to_string());
-            }
             iteration += 1;
 
-            let openai_messages = Self::convert_messages_to_openai(&messages);
-            let mut request_builder = CreateChatCompletionRequestArgs::default();
-
-            request_builder.model(&request.model);
-            request_builder.messages(openai_messages);
-
-            if let Some(temp) = request.temperature {
-                request_builder.temperature(temp as f32);
-            }
-            if let Some(max_tokens) = request.max_tokens {
-                request_builder.max_tokens(max_tokens);
-            }
-            if let Some(top_p) = request.top_p {
-                request_builder.top_p(top_p as f32);
-            }
-            if let Some(freq_penalty) = request.frequency_penalty {
-                request_builder.frequency_penalty(freq_penalty as f32);
-            }
-            if let Some(pres_penalty) = request.presence_penalty {
-                request_builder.presence_penalty(pres_penalty as f32);
-            }
-            if let Some(tools) = &request.tools {
-                let converted_tools = Self::convert_tools_to_openai(tools);
-                if !converted_tools.is_empty() {
-                    request_builder.tools(converted_tools);
+            let iteration_request = ChatCompletionRequest {
+                messages: messages.clone(),
+                ..request.clone()
+            };
+            let (mut our_response, served_index) =
+                Self::send_with_failover(chain, active_index, &iteration_request, &policy).await?;
+            active_index = served_index;
+
+            let served_config = &chain[served_index];
+            our_response.served_by = Some(ServedBy {
+                profile: served_config.profile.clone(),
+                model: if our_response.model.is_empty() {
+                    served_config.model.clone()
+                } else {
+                    our_response.model.clone()
+                },
+            });
+
+            let round = Self::handle_tool_calls(
+                &our_response,
+                app_handle,
+                character_uuid,
+                overrides,
+                &mut messages,
+                &mut intermediate_messages,
+                &mut repeated_calls,
+            )
+            .await;
+
+            match round {
+                // 继续循环，将工具结果发送回AI
+                ToolRound::Executed if iteration < tool_loop.max_iterations => continue,
+                ToolRound::Executed => {
+                    return Ok(Self::stop_tool_loop(
+                        our_response,
+                        intermediate_messages,
+                        format!(
+                            "工具调用已达到 {} 轮上限，已停止继续调用。以上工具调用结果已保留，可以继续对话。",
+                            tool_loop.max_iterations
+                        ),
+                    ));
                 }
-            }
-            if let Some(tool_choice) = &request.tool_choice {
-                if let Some(openai_choice) = Self::convert_tool_choice_to_openai(tool_choice) {
-                    request_builder.tool_choice(openai_choice);
+                ToolRound::Stalled => {
+                    return Ok(Self::stop_tool_loop(
+                        our_response,
+                        intermediate_messages,
+                        "检测到重复的工具调用，已停止继续调用。以上工具调用结果已保留，可以继续对话。".to_string(),
+                    ));
+                }
+                // 没有工具调用，返回结果
+                ToolRound::None => {
+                    return Ok(Self::finalize_response(our_response, intermediate_messages));
                 }
             }
+        }
+    }
 
-            let openai_request = request_builder
-                .build()
-                .map_err(|e| format!("请求build错误: {}", e))?;
-
-            let response = client.chat().create(openai_request).await;
+    /// 从 `start_index` 开始依次尝试故障转移链中的配置，返回响应及实际使用的配置索引
+    async fn send_with_failover(
+        chain: &[ApiConfig],
+        start_index: usize,
+        request: &ChatCompletionRequest,
+        policy: &RetryPolicy,
+    ) -> Result<(ChatCompletionResponse, usize), String> {
+        let mut failures = Vec::new();
+
+        for (index, api_config) in chain.iter().enumerate().skip(start_index) {
+            let profile_request = if index == 0 {
+                request.clone()
+            } else {
+                ChatCompletionRequest {
+                    model: api_config.model.clone(),
+                    ..request.clone()
+                }
+            };
 
-            let our_response = match response {
-                Ok(resp) => Self::convert_response_from_openai(resp),
-                Err(err) => {
-                    let err_msg = err.to_string();
-                    let lower = err_msg.to_lowercase();
-                    if lower.contains("deserialize") {
+            let mut attempt = 0;
+            loop {
+                match Self::send_once(api_config, &profile_request).await {
+                    Ok(response) => {
+                        if index != start_index {
+                            eprintln!("🔁 已切换到备用API配置 '{}'", api_config.profile);
+                        }
+                        return Ok((response, index));
+                    }
+                    Err(err) if err.retryable && attempt < policy.max_retries => {
+                        let delay = policy.delay_for(attempt, err.retry_after);
                         eprintln!(
-                            "⚠️ async-openai 解析响应失败，尝试回退至 reqwest 捕获原始响应: {}",
-                            err_msg
+                            "⚠️ API配置 '{}' 请求失败（第 {} 次），{}ms 后重试: {}",
+                            api_config.profile,
+                            attempt + 1,
+                            delay.as_millis(),
+                            err
                         );
-                        // 回退到 HTTP，并打印原始响应
-                        Self::send_chat_request_via_http(api_config, request, true).await?
-                    } else {
-                        return Err(format!("API请求失败: {}", err_msg));
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    Err(err) => {
+                        eprintln!("❌ API配置 '{}' 请求失败: {}", api_config.profile, err);
+                        failures.push(format!("[{}] {}", api_config.profile, err));
+                        break;
                     }
                 }
-            };
+            }
+        }
 
-            // 检查是否有工具调用需要执行
-            if let Some(choice) = our_response.choices.first() {
-                if let Some(tool_calls) = &choice.message.tool_calls {
-                    if !tool_calls.is_empty() {
-                        // 执行工具调用
-                        if let Some(app_handle) = app_handle {
-                            // 保存 assistant 消息（包含 tool_calls）到中间消息
-                            intermediate_messages.push(choice.message.clone());
-                            messages.push(choice.message.clone());
-
-                            // 获取当前角色UUID用于事件发送
-                            let character_uuid = crate::character_state::CHARACTER_STATE
-                                .get_current_character()
-                                .unwrap_or_else(|| "unknown".to_string());
-
-                            for tool_call in tool_calls {
-                                if let Some(tool_result) = Self::execute_single_tool_call(
-                                    app_handle,
-                                    &tool_call.function.name,
-                                    &tool_call.function.arguments,
-                                    &messages,
-                                )
-                                .await
-                                {
-                                    // 解析工具执行结果
-                                    let success = tool_result.get("success")
-                                        .and_then(|v| v.as_bool())
-                                        .unwrap_or(false);
-                                    let data = tool_result.get("data").cloned();
-                                    let error = tool_result.get("error")
-                                        .and_then(|v| v.as_str())
-                                        .map(|s| s.to_string());
-                                    let execution_time_ms = tool_result.get("execution_time_ms")
-                                        .and_then(|v| v.as_u64())
-                                        .unwrap_or(0);
-
-                                    // 发送工具执行事件
-                                    if let Err(e) = EventBus::tool_executed(
-                                        app_handle,
-                                        &character_uuid,
-                                        &tool_call.function.name,
-                                        success,
-                                        data.clone(),
-                                        error.clone(),
-                                        execution_time_ms,
-                                    ) {
-                                        eprintln!("发送工具执行事件失败: {}", e);
-                                    }
-
-                                    // 将工具结果添加到消息列表
-                                    let tool_message = ChatMessage {
-                                        role: MessageRole::Tool,
-                                        content: serde_json::to_string(&tool_result)
-                                            .unwrap_or_default(),
-                                        name: None,
-                                        tool_calls: None,
-                                        tool_call_id: Some(tool_call.id.clone()),
-                                    };
-                                    intermediate_messages.push(tool_message.clone());
-                                    messages.push(tool_message);
-                                } else {
-                                    // 工具执行失败
-                                    if let Err(e) = EventBus::tool_executed(
-                                        app_handle,
-                                        &character_uuid,
-                                        &tool_call.function.name,
-                                        false,
-                                        None,
-                                        Some("Tool execution failed".to_string()),
-                                        0,
-                                    ) {
-                                        eprintln!("发送工具执行失败事件失败: {}", e);
-                                    }
-
-                                    let tool_error_message = ChatMessage {
-                                        role: MessageRole::Tool,
-                                        content: serde_json::json!({
-                                            "success": false,
-                                            "error": "Tool execution failed"
-                                        })
-                                        .to_string(),
-                                        name: None,
-                                        tool_calls: None,
-                                        tool_call_id: Some(tool_call.id.clone()),
-                                    };
-                                    intermediate_messages.push(tool_error_message.clone());
-                                    messages.push(tool_error_message);
-                                }
-                            }
+        Err(failures.join("; "))
+    }
 
-                            // 继续循环，将工具结果发送回AI
-                            continue;
-                        }
-                    }
-                }
+    /// 使用单个 API 配置发送一轮请求
+    async fn send_once(
+        api_config: &ApiConfig,
+        request: &ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, ApiCallError> {
+        // 文本补全模式不支持工具调用，直接渲染为单个 prompt
+        if api_config.prompt_mode != PromptMode::Chat {
+            return Self::create_text_completion(api_config, request).await;
+        }
+
+        // 扩展采样参数只能通过原始 HTTP 请求发送；Azure 的部署地址与鉴权方式也由 HTTP 路径处理
+        if request.has_extended_sampling() || api_config.dialect() == ApiDialect::Azure {
+            return Self::send_chat_request_via_http(api_config, request).await;
+        }
+
+        let client = HttpClientFactory::build_for_openai(&api_config.network).map_err(ApiCallError::permanent)?;
+        let openai_messages = Self::convert_messages_to_openai(&request.messages);
+        let mut request_builder = CreateChatCompletionRequestArgs::default();
+
+        request_builder.model(&request.model);
+        request_builder.messages(openai_messages);
+
+        if let Some(temp) = request.temperature {
+            request_builder.temperature(temp as f32);
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            request_builder.max_tokens(max_tokens);
+        }
+        if let Some(top_p) = request.top_p {
+            request_builder.top_p(top_p as f32);
+        }
+        if let Some(freq_penalty) = request.frequency_penalty {
+            request_builder.frequency_penalty(freq_penalty as f32);
+        }
+        if let Some(pres_penalty) = request.presence_penalty {
+            request_builder.presence_penalty(pres_penalty as f32);
+        }
+        if let Some(seed) = request.seed {
+            request_builder.seed(seed);
+        }
+        if let Some(stop) = request.stop.as_ref().and_then(Self::convert_stop_to_openai) {
+            request_builder.stop(stop);
+        }
+        if let Some(tools) = &request.tools {
+            let converted_tools = Self::convert_tools_to_openai(tools);
+            if !converted_tools.is_empty() {
+                request_builder.tools(converted_tools);
+            }
+        }
+        if let Some(tool_choice) = &request.tool_choice {
+            if let Some(openai_choice) = Self::convert_tool_choice_to_openai(tool_choice) {
+                request_builder.tool_choice(openai_choice);
             }
+        }
+
+        let openai_request = request_builder
+            .build()
+            .map_err(|e| ApiCallError::permanent(format!("请求build错误: {}", e)))?;
+
+        // 由 async-openai 构建请求与解析响应，请求本身由应用发送，以便读取状态码与 Retry-After 头
+        let response = client
+            .post(api_config.endpoint_url("chat/completions"))
+            .bearer_auth(&api_config.key)
+            .json(&openai_request)
+            .send()
+            .await
+            .map_err(|err| match err.status() {
+                Some(status) => ApiCallError::from_status(status.as_u16(), format!("API请求失败: {}", err), None),
+                None => ApiCallError {
+                    message: format!("API请求失败: {}", err),
+                    status: None,
+                    retry_after: None,
+                    retryable: err.is_timeout() || err.is_connect(),
+                },
+            })?;
+
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest_openai::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ApiCallError::permanent(format!("读取API响应失败: {}", e)))?;
+
+        if !(200..300).contains(&status) {
+            let status_code = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(ApiCallError::from_status(
+                status,
+                Self::format_api_error(status_code, &body),
+                retry_after,
+            ));
+        }
+
+        // async-openai 的类型较严格，解析失败时用宽松的结构解析同一响应体（不重新发送，避免重复计费与重复执行工具）
+        match serde_json::from_str::<CreateChatCompletionResponse>(&body) {
+            Ok(resp) => Ok(Self::convert_response_from_openai(resp)),
+            Err(err) => serde_json::from_str::<ChatCompletionResponse>(&body).map_err(|_| {
+                Self::log_response_body_debug(&body);
+                ApiCallError::permanent(format!("API响应解析失败: {} - {}", err, body))
+            }),
+        }
+    }
+
+    /// 执行响应中的工具调用，并将 assistant/tool 消息追加到对话
+    ///
+    /// 每个调用都会得到一条 tool 消息；失败时内容为结构化错误，便于模型修正后重试
+    async fn handle_tool_calls(
+        response: &ChatCompletionResponse,
+        app_handle: Option<&tauri::AppHandle>,
+        character_uuid: Option<&str>,
+        overrides: &SessionOverrides,
+        messages: &mut Vec<ChatMessage>,
+        intermediate_messages: &mut Vec<ChatMessage>,
+        repeated_calls: &mut RepeatedCallGuard,
+    ) -> ToolRound {
+        let Some(app_handle) = app_handle else {
+            return ToolRound::None;
+        };
+        let Some(choice) = response.choices.first() else {
+            return ToolRound::None;
+        };
+        let tool_calls = match &choice.message.tool_calls {
+            Some(calls) if !calls.is_empty() => calls,
+            _ => return ToolRound::None,
+        };
+
+        // 保存 assistant 消息（包含 tool_calls）到中间消息
+        intermediate_messages.push(choice.message.clone());
+        messages.push(choice.message.clone());
 
-            // 没有工具调用或工具调用完成，返回结果
-            // 如果有中间消息，添加到响应中
-            let mut final_response = our_response;
-            if !intermediate_messages.is_empty() {
-                final_response.intermediate_messages = Some(intermediate_messages);
+        // 工具调用作用的角色（也用于事件发送）
+        let event_uuid = character_uuid.unwrap_or("unknown");
+
+        // 按调用顺序登记重复检测，再并发执行（修改同一角色的调用由角色锁串行化）
+        let plans: Vec<Result<(), u32>> = tool_calls
+            .iter()
+            .map(|tool_call| repeated_calls.register(&tool_call.function.name, &tool_call.function.arguments))
+            .collect();
+        let executed_any = plans.iter().any(Result::is_ok);
+
+        let outcomes = join_all(tool_calls.iter().zip(&plans).map(|(tool_call, plan)| async move {
+            let tool_name = &tool_call.function.name;
+            match plan {
+                Ok(()) => {
+                    Self::execute_single_tool_call(
+                        app_handle,
+                        character_uuid,
+                        overrides,
+                        tool_name,
+                        &tool_call.function.arguments,
+                        &response.model,
+                    )
+                    .await
+                }
+                Err(times) => ToolCallOutcome::failed(ToolCallError::repeated_call(tool_name, *times)),
             }
-            return Ok(final_response);
+        }))
+        .await;
+
+        for (tool_call, outcome) in tool_calls.iter().zip(outcomes) {
+            let tool_name = &tool_call.function.name;
+
+            // 发送工具执行事件
+            if let Err(e) = EventBus::tool_executed(
+                app_handle,
+                event_uuid,
+                tool_name,
+                outcome.success(),
+                outcome.data.clone(),
+                outcome.error.as_ref().map(|error| error.message.clone()),
+                outcome.execution_time_ms,
+            ) {
+                eprintln!("发送工具执行事件失败: {}", e);
+            }
+
+            // 将工具结果添加到消息列表（保持模型返回的调用顺序）
+            let tool_message = ChatMessage {
+                role: MessageRole::Tool,
+                content: outcome.to_content(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: Some(tool_call.id.clone()),
+            };
+            intermediate_messages.push(tool_message.clone());
+            messages.push(tool_message);
+        }
+
+        if executed_any {
+            ToolRound::Executed
+        } else {
+            ToolRound::Stalled
+        }
+    }
+
+    /// 将中间消息附加到最终响应
+    fn finalize_response(
+        mut response: ChatCompletionResponse,
+        intermediate_messages: Vec<ChatMessage>,
+    ) -> ChatCompletionResponse {
+        if !intermediate_messages.is_empty() {
+            response.intermediate_messages = Some(intermediate_messages);
+        }
+        response
+    }
+
+    /// 提前结束工具调用循环：最后一条 assistant 消息已在中间消息中，回复替换为说明
+    fn stop_tool_loop(
+        mut response: ChatCompletionResponse,
+        intermediate_messages: Vec<ChatMessage>,
+        notice: String,
+    ) -> ChatCompletionResponse {
+        if let Some(choice) = response.choices.first_mut() {
+            choice.message = ChatMessage {
+                role: MessageRole::Assistant,
+                content: notice,
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            };
+            choice.finish_reason = "tool_loop_stopped".to_string();
         }
+        Self::finalize_response(response, intermediate_messages)
     }
 
     /// 执行单个工具调用
     async fn execute_single_tool_call(
         app_handle: &tauri::AppHandle,
+        character_uuid: Option<&str>,
+        overrides: &SessionOverrides,
         tool_name: &str,
         arguments: &str,
-        _messages: &[ChatMessage],
-    ) -> Option<serde_json::Value> {
-        // 解析参数
+        model: &str,
+    ) -> ToolCallOutcome {
+        let modifies_character = match crate::tools::ToolRegistry::modifies_character_global(tool_name) {
+            Some(modifies) => modifies,
+            None => return ToolCallOutcome::failed(ToolCallError::unknown_tool(tool_name)),
+        };
+
+        // 解析参数（空参数视为空对象）
+        let arguments = if arguments.trim().is_empty() { "{}" } else { arguments };
         let params: std::collections::HashMap<String, serde_json::Value> =
             match serde_json::from_str(arguments) {
                 Ok(parsed) => parsed,
-                Err(err) => {
-                    return Some(serde_json::json!({
-                        "success": false,
-                        "error": format!("Invalid tool arguments: {}", err)
-                    }));
-                }
+                Err(err) => return ToolCallOutcome::failed(ToolCallError::invalid_arguments(err)),
             };
 
-        // 从全局状态管理器获取当前角色UUID
-        let character_uuid = crate::character_state::CHARACTER_STATE.get_current_character();
+        // 按工具参数 Schema 校验，一次返回全部字段错误供模型修正
+        if let Err(errors) = crate::tools::ToolRegistry::validate_arguments_global(tool_name, &params) {
+            return ToolCallOutcome::failed(ToolCallError::invalid_arguments(errors.join("; ")));
+        }
+
+        let character_uuid = character_uuid.map(str::to_string);
 
         // 创建工具调用请求
-        let tool_request = crate::ai_tools::ToolCallRequest {
+        let mut tool_request = crate::ai_tools::ToolCallRequest {
             tool_name: tool_name.to_string(),
             parameters: params,
+            origin: Some(crate::ai_tools::ToolCallOrigin {
+                session: character_uuid.clone(),
+                model: Some(model.to_string()),
+            }),
             character_uuid,
             context: None, // 可以考虑添加角色上下文
         };
 
+        // 工具档案、AI 角色或会话设置中未启用的工具不执行
+        let settings = tool_request
+            .character_uuid
+            .as_deref()
+            .and_then(|uuid| crate::character_session::SESSION_MANAGER.get_session(uuid))
+            .map(|session| overrides.apply(&session.settings));
+        if let Some(settings) = &settings {
+            let allowed = ToolSettingsService::filter_for(app_handle, settings)
+                .map(|filter| crate::tools::ToolRegistry::tool_matches_global(tool_name, |tool| filter.allows(tool)))
+                .unwrap_or(true);
+            if !allowed {
+                return ToolCallOutcome::failed(ToolCallError::rejected(
+                    tool_name,
+                    Some("this tool is not enabled for the current session"),
+                ));
+            }
+        }
+
+        // 按审批策略决定是否需要用户确认
+        let approval_config = ToolApprovalService::get_config(app_handle).unwrap_or_default();
+        let policy = approval_config.policy_for(tool_name, modifies_character);
+        if policy == ApprovalPolicy::Deny {
+            return ToolCallOutcome::failed(ToolCallError::rejected(
+                tool_name,
+                Some("this tool is disabled by the approval policy"),
+            ));
+        }
+
+        // 提议模式：修改只加入待审阅修改集，由用户逐项应用或丢弃
+        let proposal_mode = settings.is_some_and(|settings| settings.proposal_mode);
+        if proposal_mode && modifies_character {
+            let start_time = std::time::Instant::now();
+            return match ChangeSetService::propose(app_handle, &tool_request).await {
+                Ok(proposal) => ToolCallOutcome {
+                    data: Some(serde_json::json!({
+                        "proposed": true,
+                        "change_id": proposal.id,
+                        "changes": proposal.changes,
+                        "message": "The change was added to the pending change set for user review and has not been written to the card yet.",
+                    })),
+                    error: None,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                },
+                Err(e) => ToolCallOutcome::failed(ToolCallError::execution_failed(e)),
+            };
+        }
+
+        match policy {
+            ApprovalPolicy::Auto | ApprovalPolicy::Deny => {}
+            ApprovalPolicy::Ask => {
+                let preview = crate::tools::ToolRegistry::preview_tool_call_global(app_handle, &tool_request)
+                    .map(Option::unwrap_or_default);
+                let decision = ToolApprovalService::request_approval(
+                    app_handle,
+                    tool_request.character_uuid.as_deref().unwrap_or_default(),
+                    tool_name,
+                    &tool_request.parameters,
+                    preview,
+                    std::time::Duration::from_secs(approval_config.timeout_secs.max(1)),
+                )
+                .await;
+
+                match decision {
+                    ApprovalDecision::Approve => {}
+                    ApprovalDecision::Edit { arguments } => tool_request.parameters = arguments,
+                    ApprovalDecision::Reject { reason } => {
+                        return ToolCallOutcome::failed(ToolCallError::rejected(tool_name, reason.as_deref()));
+                    }
+                }
+            }
+        }
+
         // 执行工具调用
-        let result =
-            crate::tools::ToolRegistry::execute_tool_call_global(app_handle, &tool_request).await;
-
-        if result.success {
-            Some(serde_json::json!({
-                "success": true,
-                "data": result.data,
-                "execution_time_ms": result.execution_time_ms
-            }))
+        crate::tools::ToolRegistry::execute_tool_call_global(app_handle, &tool_request)
+            .await
+            .into()
+    }
+
+    /// 文本补全请求（/v1/completions 或 KoboldCpp /api/v1/generate）
+    ///
+    /// 使用 API 配置中选择的指令模板渲染消息，并将结果包装为聊天完成响应，
+    /// 以便调用方无需区分两种模式。
+    pub async fn create_text_completion(
+        api_config: &ApiConfig,
+        request: &ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, ApiCallError> {
+        let template = InstructTemplate::get_or_default(api_config.instruct_template.as_deref());
+        let rendered = template.render(&request.messages, request.stop.as_ref());
+
+        let (url, mut body) = match api_config.prompt_mode {
+            PromptMode::Kobold => (
+                Self::kobold_generate_url(&api_config.endpoint),
+                serde_json::json!({
+                    "prompt": rendered.prompt,
+                    "max_length": request.max_tokens,
+                    "temperature": request.temperature,
+                    "top_p": request.top_p,
+                    "top_k": request.top_k,
+                    "min_p": request.min_p,
+                    "rep_pen": request.repetition_penalty,
+                    "sampler_seed": request.seed,
+                    "stop_sequence": rendered.stop,
+                }),
+            ),
+            _ => (
+                api_config.endpoint_url("completions"),
+                serde_json::json!({
+                    "model": request.model,
+                    "prompt": rendered.prompt,
+                    "max_tokens": request.max_tokens,
+                    "temperature": request.temperature,
+                    "top_p": request.top_p,
+                    "frequency_penalty": request.frequency_penalty,
+                    "presence_penalty": request.presence_penalty,
+                    "top_k": request.top_k,
+                    "min_p": request.min_p,
+                    "repetition_penalty": request.repetition_penalty,
+                    "seed": request.seed,
+                    "stop": Self::completion_stop(api_config, &rendered.stop),
+                    "stream": false,
+                }),
+            ),
+        };
+
+        // 未设置的参数不发送，避免严格校验的服务端拒绝 null 字段
+        if let Some(fields) = body.as_object_mut() {
+            fields.retain(|_, value| !value.is_null());
+        }
+
+        let client =
+            HttpClientFactory::build(&api_config.network).map_err(ApiCallError::permanent)?;
+        let response = api_config
+            .authorize(client.post(&url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ApiCallError::from_reqwest(&e))?;
+
+        let status = response.status();
+        let retry_after = Self::retry_after_header(&response);
+        let response_body = response
+            .text()
+            .await
+            .map_err(|e| ApiCallError::from_reqwest(&e))?;
+
+        if !status.is_success() {
+            eprintln!(
+                "⚠️ 文本补全API返回非成功状态，status={}, body={}",
+                status.as_u16(),
+                response_body
+            );
+            return Err(ApiCallError::from_status(
+                status.as_u16(),
+                Self::format_api_error(status, &response_body),
+                retry_after,
+            ));
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&response_body).map_err(|e| {
+            Self::log_response_body_debug(&response_body);
+            ApiCallError::permanent(format!("API响应解析失败: {}", e))
+        })?;
+
+        Ok(Self::convert_text_completion_response(
+            &value,
+            &request.model,
+            &rendered.stop,
+        ))
+    }
+
+    /// 将文本补全响应（OpenAI completions 或 KoboldCpp）转换为聊天完成响应
+    fn convert_text_completion_response(
+        value: &serde_json::Value,
+        model: &str,
+        stop: &[String],
+    ) -> ChatCompletionResponse {
+        let first_choice = value
+            .get("choices")
+            .or_else(|| value.get("results"))
+            .and_then(|choices| choices.as_array())
+            .and_then(|choices| choices.first());
+
+        let text = first_choice
+            .and_then(|choice| choice.get("text"))
+            .and_then(|text| text.as_str())
+            .unwrap_or_default();
+
+        let finish_reason = first_choice
+            .and_then(|choice| choice.get("finish_reason"))
+            .and_then(|reason| reason.as_str())
+            .unwrap_or("stop")
+            .to_string();
+
+        let usage = value.get("usage");
+        let usage_field = |name: &str| {
+            usage
+                .and_then(|u| u.get(name))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as u32
+        };
+
+        ChatCompletionResponse {
+            id: value
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("cmpl-{}", uuid::Uuid::new_v4())),
+            object: "chat.completion".to_string(),
+            created: value
+                .get("created")
+                .and_then(|v| v.as_u64())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64),
+            model: value
+                .get("model")
+                .and_then(|v| v.as_str())
+                .unwrap_or(model)
+                .to_string(),
+            system_fingerprint: None,
+            choices: vec![ChatCompletionChoice {
+                index: 0,
+                message: ChatMessage {
+                    role: MessageRole::Assistant,
+                    content: InstructTemplate::clean_completion(text, stop),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+                finish_reason,
+            }],
+            usage: Usage {
+                prompt_tokens: usage_field("prompt_tokens"),
+                completion_tokens: usage_field("completion_tokens"),
+                total_tokens: usage_field("total_tokens"),
+            },
+            intermediate_messages: None,
+            served_by: None,
+        }
+    }
+
+    /// KoboldCpp 生成接口地址
+    fn kobold_generate_url(endpoint: &str) -> String {
+        let trimmed = endpoint.trim_end_matches('/');
+        if trimmed.ends_with("/api/v1") {
+            format!("{}/generate", trimmed)
+        } else if trimmed.ends_with("/api") {
+            format!("{}/v1/generate", trimmed)
+        } else {
+            format!("{}/api/v1/generate", trimmed)
+        }
+    }
+
+    /// completions 接口的停止序列
+    ///
+    /// OpenAI 与 Azure 最多接受 4 个，超出时保留排在前面的模板停止序列；
+    /// 其他兼容后端原样传递完整列表
+    fn completion_stop<'a>(api_config: &ApiConfig, stop: &'a [String]) -> &'a [String] {
+        let is_openai_host = match api_config.dialect() {
+            ApiDialect::Azure => true,
+            ApiDialect::OpenAi => api_config.endpoint.to_lowercase().contains("api.openai.com"),
+            _ => false,
+        };
+        if is_openai_host {
+            &stop[..stop.len().min(4)]
         } else {
-            let error_message = result
-                .error
-                .clone()
-                .unwrap_or_else(|| "Tool execution failed".to_string());
-
-            Some(serde_json::json!({
-                "success": false,
-                "error": error_message,
-                "data": result.data,
-                "execution_time_ms": result.execution_time_ms
-            }))
+            stop
         }
     }
 
@@ -681,48 +1182,36 @@ impl AIChatService {
         Ok(result)
     }
 
-    fn normalize_api_base(endpoint: &str) -> String {
-        let trimmed = endpoint.trim_end_matches('/');
-        if trimmed.ends_with("/v1") {
-            trimmed.to_string()
-        } else {
-            format!("{}/v1", trimmed)
-        }
-    }
-
     async fn send_chat_request_via_http(
         api_config: &ApiConfig,
         request: &ChatCompletionRequest,
-        debug_log_raw: bool,
-    ) -> Result<ChatCompletionResponse, String> {
-        let base_url = Self::normalize_api_base(&api_config.endpoint);
-        let url = format!("{}/chat/completions", base_url);
-        let client = reqwest::Client::new();
-        let response = client
-            .post(&url)
-            .bearer_auth(&api_config.key)
+    ) -> Result<ChatCompletionResponse, ApiCallError> {
+        let url = api_config.endpoint_url("chat/completions");
+        let client =
+            HttpClientFactory::build(&api_config.network).map_err(ApiCallError::permanent)?;
+        let response = api_config
+            .authorize(client.post(&url))
             .json(request)
             .send()
             .await
-            .map_err(|e| format!("API请求失败: {}", e))?;
+            .map_err(|e| ApiCallError::from_reqwest(&e))?;
 
         let status = response.status();
+        let retry_after = Self::retry_after_header(&response);
         let body = response
             .text()
             .await
-            .map_err(|e| format!("读取API响应失败: {}", e))?;
-
-        if debug_log_raw {
-            eprintln!("=== 回退 HTTP 原始响应 ===");
-            eprintln!("{}", body);
-        }
+            .map_err(|e| ApiCallError::from_reqwest(&e))?;
 
         if status.is_success() {
             match serde_json::from_str::<ChatCompletionResponse>(&body) {
                 Ok(parsed) => Ok(parsed),
                 Err(e) => {
                     Self::log_response_body_debug(&body);
-                    Err(format!("API响应解析失败: {} - {}", e, body))
+                    Err(ApiCallError::permanent(format!(
+                        "API响应解析失败: {} - {}",
+                        e, body
+                    )))
                 }
             }
         } else {
@@ -731,10 +1220,23 @@ impl AIChatService {
                 status.as_u16(),
                 body
             );
-            Err(Self::format_api_error(status, &body))
+            Err(ApiCallError::from_status(
+                status.as_u16(),
+                Self::format_api_error(status, &body),
+                retry_after,
+            ))
         }
     }
 
+    /// 读取响应中的 Retry-After 头
+    fn retry_after_header(response: &reqwest::Response) -> Option<std::time::Duration> {
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after)
+    }
+
     fn format_api_error(status: StatusCode, body: &str) -> String {
         let detail = Self::extract_error_details(body)
             .unwrap_or_else(|| "未返回错误信息".to_string());
@@ -1,4 +1,6 @@
 use super::file_utils::FileUtils;
+use super::tool_loop::DEFAULT_MAX_TOOL_ITERATIONS;
+use super::tool_profiles::ToolSelection;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -12,6 +14,16 @@ pub struct AIRole {
     pub temperature: f32,
     pub max_tokens: u32,
     pub tools_enabled: bool,
+    /// 单次回复的工具调用轮数上限
+    #[serde(default = "default_max_tool_iterations")]
+    pub max_tool_iterations: u32,
+    /// 角色级工具启用规则（覆盖工具档案的设置）
+    #[serde(default)]
+    pub tools: ToolSelection,
+}
+
+fn default_max_tool_iterations() -> u32 {
+    DEFAULT_MAX_TOOL_ITERATIONS
 }
 
 /// AI配置
@@ -21,6 +33,22 @@ pub struct AIConfig {
     pub roles: std::collections::HashMap<String, AIRole>,
 }
 
+impl AIConfig {
+    /// 解析实际使用的角色（指定角色不存在时回退到默认角色）
+    pub fn resolve_role(&self, role_name: Option<&str>) -> Option<(String, AIRole)> {
+        if let Some(name) = role_name {
+            if let Some(role) = self.roles.get(name) {
+                return Some((name.to_string(), role.clone()));
+            }
+            eprintln!("⚠️ AI角色 '{}' 不存在，使用默认角色", name);
+        }
+
+        self.roles
+            .get(&self.default_role)
+            .map(|role| (self.default_role.clone(), role.clone()))
+    }
+}
+
 /// AI配置服务
 pub struct AIConfigService;
 
@@ -43,6 +71,8 @@ impl AIConfigService {
             temperature: 0.7,
             max_tokens: 2000,
             tools_enabled: true,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            tools: ToolSelection::default(),
         });
 
         // 创意写作助手
@@ -53,6 +83,8 @@ impl AIConfigService {
             temperature: 0.8,
             max_tokens: 1500,
             tools_enabled: true,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            tools: ToolSelection::default(),
         });
 
         // 角色分析师
@@ -63,6 +95,8 @@ impl AIConfigService {
             temperature: 0.6,
             max_tokens: 2500,
             tools_enabled: false,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            tools: ToolSelection::default(),
         });
 
         AIConfig {
@@ -110,6 +144,14 @@ impl AIConfigService {
         Ok(config.roles.get(role_name).cloned())
     }
 
+    /// 解析会话实际使用的角色（指定角色不存在时回退到默认角色）
+    pub fn resolve_role(
+        app_handle: &tauri::AppHandle,
+        role_name: Option<&str>,
+    ) -> Result<Option<(String, AIRole)>, String> {
+        Ok(Self::load_config(app_handle)?.resolve_role(role_name))
+    }
+
     /// 更新角色配置
     pub fn update_role(
         app_handle: &tauri::AppHandle,
@@ -168,3 +210,25 @@ impl AIConfigService {
         Ok(config.roles.into_iter().collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_role_falls_back_to_default() {
+        let mut config = AIConfigService::get_default_config();
+
+        let (name, role) = config.resolve_role(Some("character_analyst")).unwrap();
+        assert_eq!(name, "character_analyst");
+        assert!(!role.tools_enabled);
+
+        let (name, _) = config.resolve_role(Some("deleted_role")).unwrap();
+        assert_eq!(name, "character_assistant");
+        let (name, _) = config.resolve_role(None).unwrap();
+        assert_eq!(name, "character_assistant");
+
+        config.default_role = "deleted_role".to_string();
+        assert!(config.resolve_role(Some("deleted_role")).is_none());
+    }
+}
@@ -18,5 +18,17 @@ pub struct ToolCallRequest {
     pub parameters: HashMap<String, Value>,
     pub character_uuid: Option<String>, // 角色UUID
     pub context: Option<Value>,         // CharacterData or other context
+    /// 调用来源（写入审计日志）
+    #[serde(default)]
+    pub origin: Option<ToolCallOrigin>,
+}
+
+/// 工具调用来源
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCallOrigin {
+    /// 发起调用的会话
+    pub session: Option<String>,
+    /// 发起调用的模型
+    pub model: Option<String>,
 }
 
@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 use super::file_utils::FileUtils;
+use super::generation_preset::GenerationPreset;
+use super::http_client::{HttpClientFactory, NetworkOptions};
+use super::key_store::{KeyStore, KEYRING_PREFIX};
+use super::model_registry::ModelRegistry;
 
 /// API配置结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +15,186 @@ pub struct ApiConfig {
     pub model: String,
     pub default: bool,
     pub enabled: bool,
+    /// 提示词模式（chat 接口或文本补全接口）
+    #[serde(default)]
+    pub prompt_mode: PromptMode,
+    /// 文本补全模式使用的指令模板标识
+    #[serde(default)]
+    pub instruct_template: Option<String>,
+    /// 该配置下保存的生成参数预设
+    #[serde(default)]
+    pub generation_presets: Vec<GenerationPreset>,
+    /// 会话未选择预设时使用的预设名称
+    #[serde(default)]
+    pub default_preset: Option<String>,
+    /// 默认配置请求失败时是否作为备用配置（按列表顺序依次尝试）
+    #[serde(default)]
+    pub failover: bool,
+    /// 网络选项（代理、附加请求头、自定义 CA 与超时）
+    #[serde(default)]
+    pub network: NetworkOptions,
+    /// 接口方言（Azure 下 `model` 填写部署名称）
+    #[serde(default)]
+    pub dialect: ApiDialect,
+    /// Azure OpenAI 的 api-version 参数
+    #[serde(default)]
+    pub api_version: Option<String>,
+}
+
+/// 接口方言（决定 URL 拼接、鉴权方式与模型列表格式）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiDialect {
+    /// 根据端点地址自动识别
+    #[default]
+    Auto,
+    /// OpenAI 及兼容接口（{base}/v1/...，Bearer 鉴权）
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Azure OpenAI（/openai/deployments/{部署}/...?api-version=，api-key 鉴权）
+    Azure,
+    /// OpenRouter（{base}/api/v1/...，模型列表包含上下文长度与价格）
+    #[serde(rename = "openrouter")]
+    OpenRouter,
+}
+
+/// Azure OpenAI 默认的 api-version
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-06-01";
+
+impl ApiConfig {
+    /// 返回给前端的副本（密钥与敏感请求头以掩码形式展示）
+    pub fn masked(&self) -> ApiConfig {
+        let mut masked = self.clone();
+        for (_, value) in masked.secrets_mut() {
+            *value = KeyStore::mask(value);
+        }
+        masked
+    }
+
+    /// 需要加密保存的值及其在密钥存储中的名称：API 密钥与敏感请求头
+    fn secrets_mut(&mut self) -> Vec<(String, &mut String)> {
+        let ApiConfig { profile, key, network, .. } = self;
+        let mut secrets = vec![(profile.clone(), key)];
+        for (name, value) in network.headers.iter_mut() {
+            if NetworkOptions::is_sensitive_header(name) {
+                secrets.push((format!("{}#{}", profile, name.trim().to_lowercase()), value));
+            }
+        }
+        secrets
+    }
+
+    /// 前端传回的掩码替换为 `current` 中对应的真实值
+    fn restore_masks(&mut self, current: &ApiConfig) {
+        if KeyStore::is_mask_of(&self.key, &current.key) {
+            self.key = current.key.clone();
+        }
+        for (name, value) in self.network.headers.iter_mut() {
+            if let Some(current) = current.network.headers.get(name) {
+                if KeyStore::is_mask_of(value, current) {
+                    *value = current.clone();
+                }
+            }
+        }
+    }
+
+    /// 实际生效的接口方言（`Auto` 时根据端点域名识别）
+    pub fn dialect(&self) -> ApiDialect {
+        match self.dialect {
+            ApiDialect::Auto => {
+                let endpoint = self.endpoint.to_lowercase();
+                if endpoint.contains(".openai.azure.com") || endpoint.contains(".cognitiveservices.azure.com") {
+                    ApiDialect::Azure
+                } else if endpoint.contains("openrouter.ai") {
+                    ApiDialect::OpenRouter
+                } else {
+                    ApiDialect::OpenAi
+                }
+            }
+            dialect => dialect,
+        }
+    }
+
+    /// 接口根地址
+    pub fn api_base(&self) -> String {
+        let trimmed = self.endpoint.trim().trim_end_matches('/');
+        match self.dialect() {
+            // Azure 只保留资源地址，去掉用户可能填写的 /openai/... 部分
+            ApiDialect::Azure => match trimmed.find("/openai") {
+                Some(index) => trimmed[..index].to_string(),
+                None => trimmed.to_string(),
+            },
+            ApiDialect::OpenRouter if !trimmed.ends_with("/v1") => {
+                if trimmed.ends_with("/api") {
+                    format!("{}/v1", trimmed)
+                } else {
+                    format!("{}/api/v1", trimmed)
+                }
+            }
+            // 已带版本段的兼容接口（如 /v1beta/openai、/api/paas/v4）原样使用
+            _ if Self::has_version_segment(trimmed) => trimmed.to_string(),
+            _ => format!("{}/v1", trimmed),
+        }
+    }
+
+    /// 地址路径中是否包含版本段（`v` 加数字，可带字母后缀，如 `v1`、`v4`、`v1beta`）
+    fn has_version_segment(url: &str) -> bool {
+        let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+        path.split('/').skip(1).any(|segment| {
+            segment
+                .strip_prefix('v')
+                .and_then(|rest| {
+                    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                    (digits > 0).then(|| &rest[digits..])
+                })
+                .is_some_and(|suffix| suffix.chars().all(|c| c.is_ascii_lowercase()))
+        })
+    }
+
+    /// 拼接具体接口地址（`path` 如 `chat/completions`、`completions`、`models`）
+    pub fn endpoint_url(&self, path: &str) -> String {
+        match self.dialect() {
+            ApiDialect::Azure => {
+                let api_version = self
+                    .api_version
+                    .as_deref()
+                    .filter(|v| !v.is_empty())
+                    .unwrap_or(DEFAULT_AZURE_API_VERSION);
+                if path == "models" {
+                    format!("{}/openai/models?api-version={}", self.api_base(), api_version)
+                } else {
+                    format!(
+                        "{}/openai/deployments/{}/{}?api-version={}",
+                        self.api_base(),
+                        self.model,
+                        path,
+                        api_version
+                    )
+                }
+            }
+            _ => format!("{}/{}", self.api_base(), path),
+        }
+    }
+
+    /// 按方言为请求添加鉴权信息
+    pub fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.dialect() {
+            ApiDialect::Azure => builder.header("api-key", &self.key),
+            _ => builder.bearer_auth(&self.key),
+        }
+    }
+}
+
+/// 提示词模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptMode {
+    /// OpenAI 兼容的 /v1/chat/completions
+    #[default]
+    Chat,
+    /// OpenAI 兼容的 /v1/completions（单个 prompt 字符串）
+    Completion,
+    /// KoboldCpp 风格的 /api/v1/generate
+    Kobold,
 }
 
 /// 创建API请求
@@ -22,6 +206,14 @@ pub struct CreateApiRequest {
     pub model: Option<String>,
     pub default: Option<bool>,
     pub enabled: Option<bool>,
+    pub prompt_mode: Option<PromptMode>,
+    pub instruct_template: Option<String>,
+    pub generation_presets: Option<Vec<GenerationPreset>>,
+    pub default_preset: Option<String>,
+    pub failover: Option<bool>,
+    pub network: Option<NetworkOptions>,
+    pub dialect: Option<ApiDialect>,
+    pub api_version: Option<String>,
 }
 
 /// 更新API请求
@@ -34,6 +226,14 @@ pub struct UpdateApiRequest {
     pub model: Option<String>,
     pub default: Option<bool>,
     pub enabled: Option<bool>,
+    pub prompt_mode: Option<PromptMode>,
+    pub instruct_template: Option<String>,
+    pub generation_presets: Option<Vec<GenerationPreset>>,
+    pub default_preset: Option<String>,
+    pub failover: Option<bool>,
+    pub network: Option<NetworkOptions>,
+    pub dialect: Option<ApiDialect>,
+    pub api_version: Option<String>,
 }
 
 /// API测试结果
@@ -45,10 +245,98 @@ pub struct ApiTestResult {
 }
 
 /// 模型信息
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub id: String,
     pub object: String,
+    /// 上下文长度（服务端提供时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u64>,
+    /// 价格（服务端提供时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+    /// 最大输出 Token 数（服务端提供时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// 是否支持工具调用（服务端提供时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_tools: Option<bool>,
+    /// 是否支持图片输入（服务端提供时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_vision: Option<bool>,
+}
+
+/// 模型价格（美元 / Token）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub prompt: Option<f64>,
+    pub completion: Option<f64>,
+}
+
+impl ModelInfo {
+    /// 从模型列表中的单个条目解析（兼容 OpenAI、OpenRouter、vLLM 等格式）
+    pub fn from_value(value: &serde_json::Value) -> Option<ModelInfo> {
+        let id = value.get("id")?.as_str()?.to_string();
+        let object = value
+            .get("object")
+            .and_then(|o| o.as_str())
+            .unwrap_or("model")
+            .to_string();
+
+        let context_length = ["context_length", "max_model_len", "context_window"]
+            .iter()
+            .find_map(|field| value.get(*field).and_then(Self::number_field))
+            .or_else(|| {
+                value
+                    .get("top_provider")
+                    .and_then(|p| p.get("context_length"))
+                    .and_then(Self::number_field)
+            })
+            .map(|length| length as u64);
+
+        let pricing = value.get("pricing").map(|pricing| ModelPricing {
+            prompt: pricing.get("prompt").and_then(Self::number_field),
+            completion: pricing.get("completion").and_then(Self::number_field),
+        });
+
+        let max_output_tokens = ["max_completion_tokens", "max_output_tokens"]
+            .iter()
+            .find_map(|field| {
+                value
+                    .get(*field)
+                    .or_else(|| value.get("top_provider").and_then(|p| p.get(*field)))
+                    .and_then(Self::number_field)
+            })
+            .map(|tokens| tokens as u64);
+
+        // OpenRouter 在 supported_parameters / architecture 中声明能力
+        let supports_tools = value
+            .get("supported_parameters")
+            .and_then(|params| params.as_array())
+            .map(|params| params.iter().any(|p| p.as_str() == Some("tools")));
+        let supports_vision = value
+            .get("architecture")
+            .and_then(|arch| arch.get("input_modalities"))
+            .and_then(|modalities| modalities.as_array())
+            .map(|modalities| modalities.iter().any(|m| m.as_str() == Some("image")));
+
+        Some(ModelInfo {
+            id,
+            object,
+            context_length,
+            pricing,
+            max_output_tokens,
+            supports_tools,
+            supports_vision,
+        })
+    }
+
+    /// 数字字段（OpenRouter 的价格以字符串形式返回）
+    fn number_field(value: &serde_json::Value) -> Option<f64> {
+        value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok()))
+    }
 }
 
 /// API配置服务
@@ -63,7 +351,7 @@ impl ApiConfigService {
         Ok(api_dir.join("apis.json"))
     }
 
-    /// 读取所有API配置
+    /// 读取所有API配置（密钥保持存储形式，不解密）
     fn read_api_configs(app_handle: &tauri::AppHandle) -> Result<Vec<ApiConfig>, String> {
         let config_file = Self::get_api_config_file(app_handle)?;
 
@@ -74,27 +362,150 @@ impl ApiConfigService {
         FileUtils::read_json_file::<Vec<ApiConfig>>(&config_file)
     }
 
-    /// 写入API配置
+    /// 写入API配置（明文或其他后端的密钥加密后落盘，已加密的密钥保持不变；迁出钥匙串的旧条目随后删除）
     fn write_api_configs(app_handle: &tauri::AppHandle, configs: &[ApiConfig]) -> Result<(), String> {
         let config_file = Self::get_api_config_file(app_handle)?;
-        FileUtils::write_json_file(&config_file, configs)
+
+        let mut sealed_configs = configs.to_vec();
+        let mut replaced = Vec::new();
+        for config in &mut sealed_configs {
+            for (name, value) in config.secrets_mut() {
+                if KeyStore::needs_migration(app_handle, value)? {
+                    let plaintext = KeyStore::open(app_handle, &name, value)?;
+                    let sealed = KeyStore::seal(app_handle, &name, &plaintext)?;
+                    replaced.push((name, std::mem::replace(value, sealed)));
+                }
+            }
+        }
+
+        FileUtils::write_json_file(&config_file, &sealed_configs)?;
+
+        // 配置落盘后再清理迁移前留在钥匙串中的旧条目
+        for (name, previous) in replaced {
+            if previous.starts_with(KEYRING_PREFIX) {
+                if let Err(e) = KeyStore::remove(app_handle, &name, &previous) {
+                    eprintln!("清理钥匙串中的旧API密钥失败: {}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 解密单个配置的密钥与敏感请求头
+    fn unsealed(app_handle: &tauri::AppHandle, mut config: ApiConfig) -> Result<ApiConfig, String> {
+        for (name, value) in config.secrets_mut() {
+            *value = KeyStore::open(app_handle, &name, value)?;
+        }
+        Ok(config)
+    }
+
+    /// 清理配置保存在密钥存储后端中的值（`keep` 中仍在使用的名称除外）
+    fn remove_secrets(app_handle: &tauri::AppHandle, mut stored: ApiConfig, keep: &[String]) {
+        for (name, value) in stored.secrets_mut() {
+            if keep.contains(&name) {
+                continue;
+            }
+            if let Err(e) = KeyStore::remove(app_handle, &name, value) {
+                eprintln!("清理API密钥失败: {}", e);
+            }
+        }
+    }
+
+    /// 将旧版明文密钥（或其他后端的密钥）迁移到当前密钥存储后端，启动时调用一次
+    pub fn migrate_stored_keys(app_handle: &tauri::AppHandle) -> Result<usize, String> {
+        let mut configs = Self::read_api_configs(app_handle)?;
+        let mut migrated = 0;
+        for config in &mut configs {
+            for (_, value) in config.secrets_mut() {
+                if KeyStore::needs_migration(app_handle, value)? {
+                    migrated += 1;
+                }
+            }
+        }
+
+        if migrated > 0 {
+            Self::write_api_configs(app_handle, &configs)?;
+        }
+        Ok(migrated)
+    }
+
+    /// 前端传回掩码密钥或请求头时，替换为已保存的真实值
+    pub fn restore_masked_key(app_handle: &tauri::AppHandle, config: &ApiConfig) -> Result<ApiConfig, String> {
+        let mut restored = config.clone();
+        if let Some(stored) = Self::get_api_config_by_profile(app_handle, &config.profile)? {
+            restored.restore_masks(&stored);
+        }
+        Ok(restored)
+    }
+
+    /// 显式获取明文密钥
+    pub fn reveal_api_key(app_handle: &tauri::AppHandle, profile: &str) -> Result<String, String> {
+        Self::get_api_config_by_profile(app_handle, profile)?
+            .map(|config| config.key)
+            .ok_or_else(|| format!("未找到配置 '{}'", profile))
     }
 
-    /// 获取所有API配置
+    /// 获取所有API配置（密钥保持存储形式，需要明文或掩码时使用 `get_all_api_configs_masked`）
     pub fn get_all_api_configs(app_handle: &tauri::AppHandle) -> Result<Vec<ApiConfig>, String> {
         Self::read_api_configs(app_handle)
     }
 
-    /// 根据配置名称获取API配置
+    /// 获取所有API配置的前端副本（密钥以掩码形式展示）
+    pub fn get_all_api_configs_masked(app_handle: &tauri::AppHandle) -> Result<Vec<ApiConfig>, String> {
+        Self::read_api_configs(app_handle)?
+            .into_iter()
+            .map(|config| Ok(Self::unsealed(app_handle, config)?.masked()))
+            .collect()
+    }
+
+    /// 根据配置名称获取API配置（密钥已解密）
     pub fn get_api_config_by_profile(app_handle: &tauri::AppHandle, profile: &str) -> Result<Option<ApiConfig>, String> {
         let configs = Self::read_api_configs(app_handle)?;
-        Ok(configs.into_iter().find(|config| config.profile == profile))
+        configs
+            .into_iter()
+            .find(|config| config.profile == profile)
+            .map(|config| Self::unsealed(app_handle, config))
+            .transpose()
     }
 
-    /// 获取默认API配置
+    /// 获取默认API配置（密钥已解密）
     pub fn get_default_api_config(app_handle: &tauri::AppHandle) -> Result<Option<ApiConfig>, String> {
         let configs = Self::read_api_configs(app_handle)?;
-        Ok(configs.into_iter().find(|config| config.default))
+        configs
+            .into_iter()
+            .find(|config| config.default)
+            .map(|config| Self::unsealed(app_handle, config))
+            .transpose()
+    }
+
+    /// 获取默认API配置使用的模型（不解密密钥，供 Token 计数等高频调用）
+    pub fn get_default_model(app_handle: &tauri::AppHandle) -> Result<Option<String>, String> {
+        let configs = Self::read_api_configs(app_handle)?;
+        Ok(configs
+            .into_iter()
+            .find(|config| config.default)
+            .map(|config| config.model))
+    }
+
+    /// 获取故障转移链：默认配置在前，其后为启用且标记为备用的配置（密钥已解密）
+    pub fn get_failover_chain(app_handle: &tauri::AppHandle) -> Result<Vec<ApiConfig>, String> {
+        let configs = Self::read_api_configs(app_handle)?;
+
+        let mut chain: Vec<ApiConfig> = configs
+            .iter()
+            .filter(|config| config.default)
+            .cloned()
+            .collect();
+        chain.extend(
+            configs
+                .into_iter()
+                .filter(|config| !config.default && config.enabled && config.failover),
+        );
+
+        chain
+            .into_iter()
+            .map(|config| Self::unsealed(app_handle, config))
+            .collect()
     }
 
     /// 创建新的API配置
@@ -113,6 +524,14 @@ impl ApiConfigService {
             model: request.model.unwrap_or_default(),
             default: request.default.unwrap_or(false),
             enabled: request.enabled.unwrap_or(false),
+            prompt_mode: request.prompt_mode.unwrap_or_default(),
+            instruct_template: request.instruct_template,
+            generation_presets: request.generation_presets.unwrap_or_default(),
+            default_preset: request.default_preset,
+            failover: request.failover.unwrap_or(false),
+            network: request.network.unwrap_or_default(),
+            dialect: request.dialect.unwrap_or_default(),
+            api_version: request.api_version,
         };
 
         // 如果设置为默认，清除其他默认配置
@@ -136,8 +555,10 @@ impl ApiConfigService {
             .position(|config| config.profile == request.original_profile)
             .ok_or_else(|| format!("未找到配置 '{}'", request.original_profile))?;
 
-        // 克隆当前配置以避免借用冲突
-        let mut updated_config = configs[config_index].clone();
+        // 在解密后的副本上修改，写入时重新加密（重命名后以新名称保存到钥匙串）
+        let stored_config = configs[config_index].clone();
+        let current = Self::unsealed(app_handle, stored_config.clone())?;
+        let mut updated_config = current.clone();
 
         // 更新profile名称
         updated_config.profile = request.profile;
@@ -155,6 +576,32 @@ impl ApiConfigService {
         if let Some(enabled) = request.enabled {
             updated_config.enabled = enabled;
         }
+        if let Some(prompt_mode) = request.prompt_mode {
+            updated_config.prompt_mode = prompt_mode;
+        }
+        if let Some(instruct_template) = request.instruct_template {
+            updated_config.instruct_template = Some(instruct_template);
+        }
+        if let Some(generation_presets) = request.generation_presets {
+            updated_config.generation_presets = generation_presets;
+        }
+        if let Some(default_preset) = request.default_preset {
+            updated_config.default_preset = Some(default_preset);
+        }
+        if let Some(failover) = request.failover {
+            updated_config.failover = failover;
+        }
+        if let Some(network) = request.network {
+            updated_config.network = network;
+        }
+        // 前端未修改的密钥与敏感请求头会传回掩码，保留原值
+        updated_config.restore_masks(&current);
+        if let Some(dialect) = request.dialect {
+            updated_config.dialect = dialect;
+        }
+        if let Some(api_version) = request.api_version {
+            updated_config.api_version = Some(api_version);
+        }
 
         // 处理默认设置
         if let Some(default) = request.default {
@@ -169,10 +616,18 @@ impl ApiConfigService {
             }
         }
 
+        let kept_secrets: Vec<String> = updated_config
+            .secrets_mut()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
         // 替换配置
         configs[config_index] = updated_config;
 
         Self::write_api_configs(app_handle, &configs)?;
+        // 清理重命名前或已删除的请求头在钥匙串中的旧条目
+        Self::remove_secrets(app_handle, stored_config, &kept_secrets);
         Ok(())
     }
 
@@ -180,14 +635,14 @@ impl ApiConfigService {
     pub fn delete_api_config(app_handle: &tauri::AppHandle, profile: &str) -> Result<(), String> {
         let mut configs = Self::read_api_configs(app_handle)?;
 
-        let original_len = configs.len();
-        configs.retain(|config| config.profile != profile);
-
-        if configs.len() == original_len {
-            return Err(format!("未找到配置 '{}'", profile));
-        }
+        let index = configs
+            .iter()
+            .position(|config| config.profile == profile)
+            .ok_or_else(|| format!("未找到配置 '{}'", profile))?;
+        let removed = configs.remove(index);
 
         Self::write_api_configs(app_handle, &configs)?;
+        Self::remove_secrets(app_handle, removed, &[]);
         Ok(())
     }
 
@@ -228,7 +683,8 @@ impl ApiConfigService {
     }
 
     /// 测试API连接
-    pub async fn test_api_connection(_app_handle: &tauri::AppHandle, config: &ApiConfig) -> Result<ApiTestResult, String> {
+    pub async fn test_api_connection(app_handle: &tauri::AppHandle, config: &ApiConfig) -> Result<ApiTestResult, String> {
+        let config = &Self::restore_masked_key(app_handle, config)?;
         if config.endpoint.is_empty() || config.key.is_empty() {
             return Ok(ApiTestResult {
                 success: false,
@@ -238,18 +694,22 @@ impl ApiConfigService {
         }
 
         // 构建测试请求URL
-        let models_url = if config.endpoint.ends_with('/') {
-            format!("{}models", config.endpoint)
-        } else {
-            format!("{}/models", config.endpoint)
-        };
+        let models_url = config.endpoint_url("models");
 
         // 创建HTTP客户端
-        let client = reqwest::Client::new();
+        let client = match HttpClientFactory::build(&config.network) {
+            Ok(client) => client,
+            Err(e) => {
+                return Ok(ApiTestResult {
+                    success: false,
+                    message: "网络配置无效".to_string(),
+                    error: Some(e),
+                })
+            }
+        };
 
-        let result = match client
-            .get(&models_url)
-            .header("Authorization", format!("Bearer {}", config.key))
+        let result = match config
+            .authorize(client.get(&models_url))
             .header("Content-Type", "application/json")
             .send()
             .await
@@ -287,24 +747,20 @@ impl ApiConfigService {
     }
 
     /// 获取可用模型列表
-    pub async fn fetch_models(_app_handle: &tauri::AppHandle, config: &ApiConfig) -> Result<Vec<ModelInfo>, String> {
+    pub async fn fetch_models(app_handle: &tauri::AppHandle, config: &ApiConfig) -> Result<Vec<ModelInfo>, String> {
+        let config = &Self::restore_masked_key(app_handle, config)?;
         if config.endpoint.is_empty() || config.key.is_empty() {
             return Err("API端点和密钥不能为空".to_string());
         }
 
         // 构建模型请求URL
-        let models_url = if config.endpoint.ends_with('/') {
-            format!("{}models", config.endpoint)
-        } else {
-            format!("{}/models", config.endpoint)
-        };
+        let models_url = config.endpoint_url("models");
 
         // 创建HTTP客户端
-        let client = reqwest::Client::new();
+        let client = HttpClientFactory::build(&config.network)?;
 
-        let response = client
-            .get(&models_url)
-            .header("Authorization", format!("Bearer {}", config.key))
+        let response = config
+            .authorize(client.get(&models_url))
             .header("Content-Type", "application/json")
             .send()
             .await
@@ -319,23 +775,122 @@ impl ApiConfigService {
             .await
             .map_err(|e| format!("解析响应失败: {}", e))?;
 
-        // 解析模型列表（OpenAI格式）
+        // 解析模型列表（OpenAI / OpenRouter / Azure 均使用 data 数组）
         let models = if let Some(data) = response_json.get("data").and_then(|d| d.as_array()) {
-            data.iter()
-                .filter_map(|model| {
-                    let id = model.get("id")?.as_str()?.to_string();
-                    let object = model.get("object")
-                        .and_then(|o| o.as_str())
-                        .unwrap_or("model")
-                        .to_string();
-                    Some(ModelInfo { id, object })
-                })
-                .collect()
+            data.iter().filter_map(ModelInfo::from_value).collect()
         } else {
             // 如果不是标准格式，返回空列表
             Vec::new()
         };
 
+        // 记录接口返回的模型能力，供上下文预算使用
+        if let Err(e) = ModelRegistry::record_discovered(app_handle, &models) {
+            eprintln!("记录模型能力失败: {}", e);
+        }
+
         Ok(models)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_endpoint(endpoint: &str) -> ApiConfig {
+        serde_json::from_value(serde_json::json!({
+            "profile": "test",
+            "endpoint": endpoint,
+            "key": "k",
+            "model": "gpt-4o",
+            "default": true,
+            "enabled": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dialect_urls() {
+        let openai = config_with_endpoint("https://api.openai.com/");
+        assert_eq!(
+            openai.endpoint_url("chat/completions"),
+            "https://api.openai.com/v1/chat/completions"
+        );
+
+        let azure = config_with_endpoint("https://res.openai.azure.com/openai/deployments/x");
+        assert_eq!(azure.dialect(), ApiDialect::Azure);
+        assert_eq!(
+            azure.endpoint_url("chat/completions"),
+            "https://res.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-06-01"
+        );
+
+        let openrouter = config_with_endpoint("https://openrouter.ai");
+        assert_eq!(openrouter.endpoint_url("models"), "https://openrouter.ai/api/v1/models");
+    }
+
+    #[test]
+    fn test_sensitive_headers_are_masked_and_restored() {
+        let mut config = config_with_endpoint("https://res.openai.azure.com");
+        config.key = "sk-abcdefghijklmnop".to_string();
+        config
+            .network
+            .headers
+            .insert("api-key".to_string(), "azure-secret-value".to_string());
+        config
+            .network
+            .headers
+            .insert("HTTP-Referer".to_string(), "https://example.com".to_string());
+
+        let mut masked = config.masked();
+        assert_eq!(masked.key, "sk-****mnop");
+        assert_eq!(masked.network.headers["api-key"], "azu****alue");
+        assert_eq!(masked.network.headers["HTTP-Referer"], "https://example.com");
+
+        masked.restore_masks(&config);
+        assert_eq!(masked.key, config.key);
+        assert_eq!(masked.network.headers, config.network.headers);
+    }
+
+    #[test]
+    fn test_versioned_base_is_kept() {
+        let gemini = config_with_endpoint("https://generativelanguage.googleapis.com/v1beta/openai/");
+        assert_eq!(
+            gemini.endpoint_url("models"),
+            "https://generativelanguage.googleapis.com/v1beta/openai/models"
+        );
+
+        let zhipu = config_with_endpoint("https://open.bigmodel.cn/api/paas/v4");
+        assert_eq!(
+            zhipu.endpoint_url("chat/completions"),
+            "https://open.bigmodel.cn/api/paas/v4/chat/completions"
+        );
+
+        // 主机名或普通路径段中的 v 开头单词不算版本段
+        let local = config_with_endpoint("http://v1.local:8080/vllm");
+        assert_eq!(local.endpoint_url("models"), "http://v1.local:8080/vllm/v1/models");
+    }
+
+    #[test]
+    fn test_model_info_parses_openrouter_shape() {
+        let model = ModelInfo::from_value(&serde_json::json!({
+            "id": "anthropic/claude-3.5-sonnet",
+            "context_length": 200000,
+            "pricing": { "prompt": "0.000003", "completion": "0.000015" },
+            "top_provider": { "max_completion_tokens": 8192 },
+            "supported_parameters": ["tools", "temperature"]
+        }))
+        .unwrap();
+
+        assert_eq!(model.max_output_tokens, Some(8192));
+        assert_eq!(model.supports_tools, Some(true));
+        assert_eq!(model.supports_vision, None);
+
+        assert_eq!(model.object, "model");
+        assert_eq!(model.context_length, Some(200000));
+        assert_eq!(
+            model.pricing,
+            Some(ModelPricing {
+                prompt: Some(0.000003),
+                completion: Some(0.000015)
+            })
+        );
+    }
+}
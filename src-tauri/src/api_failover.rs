@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// 单次 API 调用错误（携带重试判断所需的状态码与 Retry-After）
+#[derive(Debug, Clone)]
+pub struct ApiCallError {
+    pub message: String,
+    pub status: Option<u16>,
+    pub retry_after: Option<Duration>,
+    /// 是否值得重试（429、5xx、超时、连接失败）
+    pub retryable: bool,
+}
+
+impl ApiCallError {
+    /// 不可重试的错误
+    pub fn permanent(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            status: None,
+            retry_after: None,
+            retryable: false,
+        }
+    }
+
+    /// 根据 HTTP 状态码构造错误
+    pub fn from_status(status: u16, message: impl Into<String>, retry_after: Option<Duration>) -> Self {
+        Self {
+            message: message.into(),
+            status: Some(status),
+            retry_after,
+            retryable: Self::is_retryable_status(status),
+        }
+    }
+
+    /// 网络层错误（超时与连接失败可重试）
+    pub fn from_reqwest(err: &reqwest::Error) -> Self {
+        if let Some(status) = err.status() {
+            return Self::from_status(status.as_u16(), format!("API请求失败: {}", err), None);
+        }
+
+        Self {
+            message: format!("API请求失败: {}", err),
+            status: None,
+            retry_after: None,
+            retryable: err.is_timeout() || err.is_connect(),
+        }
+    }
+
+    /// 可重试的状态码
+    pub fn is_retryable_status(status: u16) -> bool {
+        matches!(status, 408 | 409 | 425 | 429) || (500..=599).contains(&status)
+    }
+}
+
+impl fmt::Display for ApiCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<ApiCallError> for String {
+    fn from(err: ApiCallError) -> Self {
+        err.message
+    }
+}
+
+/// 重试策略（指数退避）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// 每个 API 配置的最大重试次数（不含首次请求）
+    pub max_retries: u32,
+    /// 首次重试等待时间（毫秒），之后每次翻倍
+    pub base_delay_ms: u64,
+    /// 单次等待上限（毫秒），同样限制服务端返回的 Retry-After
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 1000,
+            max_delay_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 计算第 `attempt` 次重试（从 0 开始）前的等待时间，优先使用 Retry-After
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max_delay = Duration::from_millis(self.max_delay_ms);
+
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(max_delay);
+        }
+
+        let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+        Duration::from_millis(self.base_delay_ms.saturating_mul(factor)).min(max_delay)
+    }
+}
+
+/// 解析 Retry-After 头（秒数或 HTTP 日期；负数、inf 等无法表示的秒数视为无效）
+///
+/// 返回值未经限制，等待前由 `RetryPolicy::delay_for` 限制在 max_delay_ms 以内
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_and_respects_retry_after() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.delay_for(0, None), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for(2, None), Duration::from_millis(4000));
+        assert_eq!(policy.delay_for(10, None), Duration::from_millis(30_000));
+        assert_eq!(
+            policy.delay_for(0, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            policy.delay_for(0, Some(Duration::from_secs(600))),
+            Duration::from_millis(30_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("12"), Some(Duration::from_secs(12)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+
+        for value in ["inf", "-inf", "NaN", "1e20", "-1"] {
+            assert_eq!(parse_retry_after(value), None, "{}", value);
+        }
+        let policy = RetryPolicy::default();
+        let huge = parse_retry_after("1e12");
+        assert_eq!(policy.delay_for(0, huge), Duration::from_millis(30_000));
+    }
+}
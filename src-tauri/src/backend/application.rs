@@ -1,3 +1,4 @@
+pub mod batch_service;
 pub mod command_service;
 pub mod event_bus;
 pub mod session_service;
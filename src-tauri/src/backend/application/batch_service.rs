@@ -0,0 +1,354 @@
+use crate::backend::application::command_service::CommandService;
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::{BatchCommandReport, BatchCommandRequest, BatchItemResult, BatchTarget};
+use crate::character_session::SESSION_MANAGER;
+use crate::character_storage::{CharacterData, CharacterStorage};
+use crate::command_system::arguments::{self, CommandArguments};
+use crate::command_system::command::{CommandContext, CommandExecutor};
+use crate::command_system::registry::COMMAND_REGISTRY;
+use crate::command_system::user_command::UserCommand;
+use crate::user_commands::UserCommandService;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 正在运行的批量任务（任务 ID → 是否已请求取消）
+static RUNNING_BATCHES: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 对每个角色执行的动作
+enum BatchAction {
+    /// 已注册的命令与解析好的参数
+    Command {
+        executor: Arc<dyn CommandExecutor>,
+        raw_arguments: String,
+        arguments: CommandArguments,
+    },
+    /// 展开后发送给 AI 的提示词
+    Prompt(Arc<UserCommand>),
+}
+
+impl BatchAction {
+    fn executor(&self) -> Arc<dyn CommandExecutor> {
+        match self {
+            Self::Command { executor, .. } => executor.clone(),
+            Self::Prompt(command) => command.clone(),
+        }
+    }
+
+    /// 针对某个角色的命令上下文
+    fn context(&self, app_handle: &tauri::AppHandle, uuid: &str) -> CommandContext {
+        let (raw_arguments, arguments) = match self {
+            Self::Command {
+                raw_arguments,
+                arguments,
+                ..
+            } => (raw_arguments.clone(), arguments.clone()),
+            Self::Prompt(_) => (String::new(), CommandArguments::default()),
+        };
+        CommandContext {
+            session_uuid: Some(uuid.to_string()),
+            raw_arguments,
+            arguments,
+            app_handle: app_handle.clone(),
+        }
+    }
+}
+
+fn item_result(uuid: &str, name: Option<String>) -> BatchItemResult {
+    BatchItemResult {
+        uuid: uuid.to_string(),
+        name,
+        success: false,
+        skipped: false,
+        message: None,
+        error: None,
+        data: None,
+    }
+}
+
+/// 批量命令服务：对按 UUID 列表或标签选出的角色依次执行同一命令或提示词
+pub struct BatchCommandService;
+
+impl BatchCommandService {
+    /// 执行批量命令，每处理完一个角色发送一次 batch-progress 事件
+    pub async fn run(
+        app_handle: &tauri::AppHandle,
+        request: BatchCommandRequest,
+    ) -> Result<BatchCommandReport, String> {
+        let action = Self::resolve_action(app_handle, &request).await?;
+        let characters = CharacterStorage::get_all_characters(app_handle)?;
+        let targets = Self::select_targets(&characters, &request.target);
+        if targets.is_empty() {
+            return Err("没有匹配的角色".to_string());
+        }
+
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        RUNNING_BATCHES.lock().unwrap().insert(batch_id.clone(), false);
+
+        let total = targets.len();
+        let mut report = BatchCommandReport {
+            batch_id: batch_id.clone(),
+            dry_run: request.dry_run,
+            cancelled: false,
+            total,
+            succeeded: 0,
+            failed: 0,
+            results: Vec::with_capacity(total),
+        };
+
+        let mut stopped = false;
+        for (index, target) in targets.into_iter().enumerate() {
+            if !stopped && RUNNING_BATCHES.lock().unwrap().get(&batch_id).copied().unwrap_or(false) {
+                report.cancelled = true;
+                stopped = true;
+            }
+
+            let executed = !stopped;
+            let result = match target {
+                _ if stopped => {
+                    let (uuid, name) = match &target {
+                        Ok(character) => (character.uuid.clone(), Some(character.card.data.name.clone())),
+                        Err(uuid) => (uuid.clone(), None),
+                    };
+                    BatchItemResult {
+                        skipped: true,
+                        message: Some("已停止，未执行".to_string()),
+                        ..item_result(&uuid, name)
+                    }
+                }
+                Ok(character) if request.dry_run => Self::preview(app_handle, &action, character).await,
+                Ok(character) => Self::execute_for(app_handle, &action, character).await,
+                Err(uuid) => BatchItemResult {
+                    error: Some(format!("角色 {} 不存在", uuid)),
+                    ..item_result(&uuid, None)
+                },
+            };
+
+            // 预览结果也计入成功与失败（预览失败表示该角色无法执行）
+            if executed {
+                if result.success {
+                    report.succeeded += 1;
+                } else {
+                    report.failed += 1;
+                    stopped = stopped || request.stop_on_error;
+                }
+            }
+
+            if let Err(e) = EventBus::batch_progress(app_handle, &batch_id, index + 1, total, &result) {
+                eprintln!("发送批量进度事件失败: {}", e);
+            }
+            report.results.push(result);
+
+            if !stopped && !request.dry_run && request.delay_ms > 0 && index + 1 < total {
+                tokio::time::sleep(Duration::from_millis(request.delay_ms)).await;
+            }
+        }
+
+        RUNNING_BATCHES.lock().unwrap().remove(&batch_id);
+        Ok(report)
+    }
+
+    /// 请求取消正在运行的批量任务（当前角色处理完后停止）
+    pub fn cancel(batch_id: &str) -> bool {
+        match RUNNING_BATCHES.lock().unwrap().get_mut(batch_id) {
+            Some(cancelled) => {
+                *cancelled = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 确定要执行的动作（命令在执行前统一校验参数）
+    async fn resolve_action(
+        app_handle: &tauri::AppHandle,
+        request: &BatchCommandRequest,
+    ) -> Result<BatchAction, String> {
+        let command = request.command.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        let prompt = request.prompt.as_deref().filter(|p| !p.trim().is_empty());
+
+        match (command, prompt) {
+            (Some(command), None) => {
+                let command_id = command
+                    .strip_prefix('/')
+                    .and_then(|line| line.split_whitespace().next())
+                    .ok_or_else(|| format!("命令必须以 / 开头: {}", command))?;
+                let executor = COMMAND_REGISTRY
+                    .get(command_id)
+                    .await
+                    .ok_or_else(|| format!("命令 {} 不存在", command_id))?;
+                if !executor.supports_batch() {
+                    return Err(format!("命令 /{} 不支持批量执行", command_id));
+                }
+
+                let metadata = executor.metadata();
+                let raw_arguments = CommandService::parse_arguments(Some(command));
+                let arguments = arguments::parse(&metadata.arguments, &raw_arguments).map_err(|e| {
+                    format!("{}\n用法：{}", e, arguments::usage(&metadata.name, &metadata.arguments))
+                })?;
+                Ok(BatchAction::Command {
+                    executor,
+                    raw_arguments,
+                    arguments,
+                })
+            }
+            (None, Some(prompt)) => {
+                let user_name = UserCommandService::get_config(app_handle)?.user_name;
+                Ok(BatchAction::Prompt(Arc::new(UserCommand::from_prompt(
+                    prompt.to_string(),
+                    user_name,
+                ))))
+            }
+            _ => Err("command 与 prompt 必须且只能提供一个".to_string()),
+        }
+    }
+
+    /// 选出目标角色（UUID 列表去重并保持顺序，不存在的 UUID 作为 Err 返回）
+    fn select_targets<'a>(
+        characters: &'a [CharacterData],
+        target: &BatchTarget,
+    ) -> Vec<Result<&'a CharacterData, String>> {
+        match target {
+            BatchTarget::Uuids { uuids } => {
+                let mut seen = HashSet::new();
+                uuids
+                    .iter()
+                    .filter(|uuid| seen.insert(uuid.as_str()))
+                    .map(|uuid| {
+                        characters
+                            .iter()
+                            .find(|character| &character.uuid == uuid)
+                            .ok_or_else(|| uuid.clone())
+                    })
+                    .collect()
+            }
+            BatchTarget::Tag { tag } => {
+                let tag = tag.trim();
+                characters
+                    .iter()
+                    .filter(|character| {
+                        character
+                            .card
+                            .data
+                            .tags
+                            .iter()
+                            .any(|t| t.trim().eq_ignore_ascii_case(tag))
+                    })
+                    .map(Ok)
+                    .collect()
+            }
+        }
+    }
+
+    /// 由命令的预览钩子说明将要执行的操作（不加载会话、不做任何修改）
+    async fn preview(
+        app_handle: &tauri::AppHandle,
+        action: &BatchAction,
+        character: &CharacterData,
+    ) -> BatchItemResult {
+        let mut result = item_result(&character.uuid, Some(character.card.data.name.clone()));
+        result.skipped = true;
+
+        let context = action.context(app_handle, &character.uuid);
+        match action.executor().preview(&context, character).await {
+            Ok(preview) => {
+                result.success = preview.success;
+                result.message = preview.message;
+                result.error = preview.error;
+                result.data = preview.data;
+            }
+            Err(e) => result.error = Some(e),
+        }
+        result
+    }
+
+    async fn execute_for(
+        app_handle: &tauri::AppHandle,
+        action: &BatchAction,
+        character: &CharacterData,
+    ) -> BatchItemResult {
+        let uuid = character.uuid.clone();
+        let mut result = item_result(&uuid, Some(character.card.data.name.clone()));
+
+        // 命令依赖会话，未加载的角色临时加载，执行后卸载
+        let loaded_here = SESSION_MANAGER.get_session(&uuid).is_none();
+        if loaded_here {
+            if let Err(e) = SESSION_MANAGER.get_or_create_session(app_handle, uuid.clone()) {
+                result.error = Some(e);
+                return result;
+            }
+        }
+
+        let executor = action.executor();
+        let context = action.context(app_handle, &uuid);
+
+        let outcome = if executor.is_available(&context).await {
+            executor.execute(context).await
+        } else {
+            Err(format!("命令 {} 对该角色不可用", executor.metadata().name))
+        };
+
+        if loaded_here {
+            if let Some(mut session) = SESSION_MANAGER.get_session(&uuid) {
+                if let Err(e) = session.save_history(app_handle).await {
+                    eprintln!("保存会话 {} 历史记录失败: {}", uuid, e);
+                }
+            }
+            let _ = SESSION_MANAGER.remove_session(&uuid);
+        }
+
+        match outcome {
+            Ok(command_result) => {
+                result.success = command_result.success;
+                result.message = command_result.message;
+                result.error = command_result.error;
+                result.data = command_result.data;
+            }
+            Err(e) => result.error = Some(e),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support;
+
+    fn character(uuid: &str, tags: &[&str]) -> CharacterData {
+        let mut card = test_support::card("", None);
+        card.data.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        test_support::character(uuid, card)
+    }
+
+    #[test]
+    fn test_select_targets() {
+        let characters = vec![
+            character("a", &["NSFW", "fantasy"]),
+            character("b", &["fantasy"]),
+            character("c", &[" nsfw "]),
+        ];
+
+        let uuids = |targets: Vec<Result<&CharacterData, String>>| -> Vec<Result<String, String>> {
+            targets
+                .into_iter()
+                .map(|target| target.map(|character| character.uuid.clone()))
+                .collect()
+        };
+
+        let by_tag = BatchCommandService::select_targets(&characters, &BatchTarget::Tag { tag: "nsfw".to_string() });
+        assert_eq!(uuids(by_tag), vec![Ok("a".to_string()), Ok("c".to_string())]);
+
+        let by_uuid = BatchCommandService::select_targets(
+            &characters,
+            &BatchTarget::Uuids {
+                uuids: vec!["b".to_string(), "x".to_string(), "b".to_string(), "a".to_string()],
+            },
+        );
+        assert_eq!(
+            uuids(by_uuid),
+            vec![Ok("b".to_string()), Err("x".to_string()), Ok("a".to_string())]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::backend::application::event_bus::EventBus;
-use crate::backend::domain::{CommandMetadata, CommandResult};
+use crate::backend::domain::{CommandCompletions, CommandMetadata, CommandResult};
+use crate::command_system::arguments::CommandArguments;
 use crate::command_system::command::CommandContext;
 use crate::command_system::registry::COMMAND_REGISTRY;
 
@@ -8,22 +9,36 @@ pub struct CommandService;
 impl CommandService {
     pub async fn initialize() {
         // 目前命令注册逻辑由 loader 负责，此处保持扩展点
-        println!("ℹ️ CommandService 初始化完成，等待加载内置/动态命令");
+        eprintln!("ℹ️ CommandService 初始化完成，等待加载内置/动态命令");
     }
 
-    fn build_context(app_handle: &tauri::AppHandle) -> CommandContext {
+    fn build_context(app_handle: &tauri::AppHandle, raw_arguments: String) -> CommandContext {
         let session_uuid = crate::character_state::get_active_character();
 
         CommandContext {
             session_uuid,
+            raw_arguments,
+            arguments: CommandArguments::default(),
             app_handle: app_handle.clone(),
         }
     }
 
+    /// 从用户输入中取出参数文本（输入以 `/命令名` 开头时去掉命令名）
+    pub(crate) fn parse_arguments(user_input: Option<&str>) -> String {
+        let input = user_input.unwrap_or_default().trim();
+        if !input.starts_with('/') {
+            return input.to_string();
+        }
+        input
+            .split_once(char::is_whitespace)
+            .map(|(_, rest)| rest.trim().to_string())
+            .unwrap_or_default()
+    }
+
     pub async fn get_available_commands(
         app_handle: &tauri::AppHandle,
     ) -> Result<Vec<CommandMetadata>, String> {
-        let context = Self::build_context(app_handle);
+        let context = Self::build_context(app_handle, String::new());
         Ok(COMMAND_REGISTRY.get_available_commands(&context).await)
     }
 
@@ -31,23 +46,28 @@ impl CommandService {
         app_handle: &tauri::AppHandle,
         query: String,
     ) -> Result<Vec<CommandMetadata>, String> {
-        let context = Self::build_context(app_handle);
+        let context = Self::build_context(app_handle, String::new());
         Ok(COMMAND_REGISTRY
             .search_commands(&query, &context)
             .await)
     }
 
+    /// 补全用户正在输入的命令行（命令名或参数）
+    pub async fn complete_command(
+        app_handle: &tauri::AppHandle,
+        input: String,
+    ) -> Result<CommandCompletions, String> {
+        let context = Self::build_context(app_handle, String::new());
+        Ok(COMMAND_REGISTRY.complete(&input, &context).await)
+    }
+
     pub async fn execute_command(
         app_handle: &tauri::AppHandle,
         command_id: String,
-        _user_input: Option<String>,
+        user_input: Option<String>,
     ) -> Result<CommandResult, String> {
-        let session_uuid = crate::character_state::get_active_character();
-
-        let context = CommandContext {
-            session_uuid: session_uuid.clone(),
-            app_handle: app_handle.clone(),
-        };
+        let context = Self::build_context(app_handle, Self::parse_arguments(user_input.as_deref()));
+        let session_uuid = context.session_uuid.clone();
 
         if let Some(ref uuid) = context.session_uuid {
             EventBus::progress(
@@ -1,9 +1,12 @@
 use crate::backend::domain::{
+    BatchItemResult,
     CharacterUpdateType,
     SessionInfo,
     SessionUnloadReason,
     TokenUsageStats,
+    ToolApprovalRequestedPayload,
 };
+use crate::change_set::ChangeSet;
 use crate::character_storage::CharacterData;
 use crate::chat_history::ChatMessage;
 use crate::context_builder::BuiltContextResult;
@@ -109,4 +112,25 @@ impl EventBus {
             execution_time_ms,
         )
     }
+
+    pub fn tool_approval_requested(
+        app: &tauri::AppHandle,
+        payload: &ToolApprovalRequestedPayload,
+    ) -> Result<(), String> {
+        EventEmitter::send_tool_approval_requested(app, payload)
+    }
+
+    pub fn change_set_updated(app: &tauri::AppHandle, change_set: &ChangeSet) -> Result<(), String> {
+        EventEmitter::send_change_set_updated(app, change_set)
+    }
+
+    pub fn batch_progress(
+        app: &tauri::AppHandle,
+        batch_id: &str,
+        completed: usize,
+        total: usize,
+        result: &BatchItemResult,
+    ) -> Result<(), String> {
+        EventEmitter::send_batch_progress(app, batch_id, completed, total, result)
+    }
 }
@@ -1,6 +1,13 @@
 use crate::backend::application::event_bus::EventBus;
-use crate::backend::domain::{SessionInfo, SessionUnloadReason, TokenUsageStats};
+use crate::backend::domain::{SessionInfo, SessionOverrides, SessionUnloadReason, TokenUsageStats};
+use crate::ai_chat::ChatTool;
+use crate::ai_config::AIConfigService;
 use crate::character_session::{CharacterSession, SESSION_MANAGER};
+use crate::generation_preset::GenerationPreset;
+use crate::model_registry::ModelRegistry;
+use crate::token_counter::TokenizerRegistry;
+use crate::tool_loop::ToolLoopPolicy;
+use crate::tool_profiles::{ToolSelection, ToolSettingsService};
 use crate::tools::ToolRegistry;
 use tauri::AppHandle;
 
@@ -27,8 +34,37 @@ impl SessionService {
         message: String,
     ) -> Result<(), String> {
         let uuid = crate::character_state::get_active_character().ok_or("没有活跃的角色会话")?;
+        Self::send_chat_message_to(app_handle, uuid, message).await
+    }
 
-        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid.clone())?;
+    /// 向指定角色的会话发送消息（不要求是当前活跃角色）
+    pub async fn send_chat_message_to(
+        app_handle: &AppHandle,
+        uuid: String,
+        message: String,
+    ) -> Result<(), String> {
+        Self::send_chat_message_with(app_handle, uuid, message, &SessionOverrides::default()).await
+    }
+
+    /// 发送消息，本次生成使用指定的 AI 角色与工具设置（只作用于这次生成，不修改会话设置）
+    pub async fn send_chat_message_with(
+        app_handle: &AppHandle,
+        uuid: String,
+        message: String,
+        overrides: &SessionOverrides,
+    ) -> Result<(), String> {
+        if let Some(name) = &overrides.ai_role {
+            if AIConfigService::get_role(app_handle, name)?.is_none() {
+                return Err(format!("AI角色 '{}' 不存在", name));
+            }
+        }
+        if let Some(name) = &overrides.tool_profile {
+            if ToolSettingsService::get_settings(app_handle)?.profile(name).is_none() {
+                return Err(format!("工具档案 {} 不存在", name));
+            }
+        }
+
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
 
         let user_message = session.add_user_message(message);
 
@@ -41,7 +77,7 @@ impl SessionService {
 
         SESSION_MANAGER.update_session(session.clone())?;
 
-        Self::generate_ai_response(app_handle, &mut session, "chat").await
+        Self::generate_ai_response(app_handle, &mut session, "chat", overrides).await
     }
 
     pub async fn unload_session(
@@ -59,7 +95,7 @@ impl SessionService {
         let removed_session = SESSION_MANAGER.remove_session(&uuid)?;
 
         if let Some(session) = removed_session {
-            println!("会话 {} 已卸载", uuid);
+            eprintln!("会话 {} 已卸载", uuid);
 
             let session_info = session.get_session_info();
             if let Err(e) = EventBus::session_unloaded(
@@ -83,6 +119,93 @@ impl SessionService {
         Ok(session.get_session_info())
     }
 
+    pub async fn set_generation_preset(
+        app_handle: &AppHandle,
+        uuid: String,
+        preset_name: Option<String>,
+    ) -> Result<SessionInfo, String> {
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
+
+        session.settings.generation_preset = preset_name.filter(|name| !name.trim().is_empty());
+        session.save_settings(app_handle)?;
+
+        let session_info = session.get_session_info();
+        SESSION_MANAGER.update_session(session)?;
+
+        Ok(session_info)
+    }
+
+    pub async fn set_ai_role(
+        app_handle: &AppHandle,
+        uuid: String,
+        role_name: Option<String>,
+    ) -> Result<SessionInfo, String> {
+        let role_name = role_name.filter(|name| !name.trim().is_empty());
+        if let Some(name) = &role_name {
+            if AIConfigService::get_role(app_handle, name)?.is_none() {
+                return Err(format!("AI角色 '{}' 不存在", name));
+            }
+        }
+
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
+
+        session.settings.ai_role = role_name;
+        session.save_settings(app_handle)?;
+
+        let session_info = session.get_session_info();
+        SESSION_MANAGER.update_session(session)?;
+
+        Ok(session_info)
+    }
+
+    pub async fn set_proposal_mode(
+        app_handle: &AppHandle,
+        uuid: String,
+        enabled: bool,
+    ) -> Result<SessionInfo, String> {
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
+
+        session.settings.proposal_mode = enabled;
+        session.save_settings(app_handle)?;
+
+        let session_info = session.get_session_info();
+        SESSION_MANAGER.update_session(session)?;
+
+        Ok(session_info)
+    }
+
+    /// 设置会话使用的工具档案与会话级工具启用规则
+    pub async fn set_tool_settings(
+        app_handle: &AppHandle,
+        uuid: String,
+        tool_profile: Option<String>,
+        tools: ToolSelection,
+    ) -> Result<SessionInfo, String> {
+        if let Some(name) = &tool_profile {
+            if ToolSettingsService::get_settings(app_handle)?.profile(name).is_none() {
+                return Err(format!("工具档案 {} 不存在", name));
+            }
+        }
+
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
+
+        session.settings.tool_profile = tool_profile;
+        session.settings.tools = tools;
+        session.save_settings(app_handle)?;
+
+        let session_info = session.get_session_info();
+        SESSION_MANAGER.update_session(session)?;
+
+        Ok(session_info)
+    }
+
+    /// 会话当前启用的工具
+    pub fn get_enabled_tools(app_handle: &AppHandle, uuid: String) -> Result<Vec<ChatTool>, String> {
+        let session = SESSION_MANAGER.get_or_create_session(app_handle, uuid)?;
+        let tool_filter = ToolSettingsService::filter_for(app_handle, &session.settings)?;
+        Ok(ToolRegistry::get_available_tools_where_global(|tool| tool_filter.allows(tool)))
+    }
+
     pub fn get_all_sessions() -> Result<Vec<SessionInfo>, String> {
         SESSION_MANAGER.get_all_sessions_info()
     }
@@ -122,7 +245,7 @@ impl SessionService {
         for uuid in expired_sessions {
             sessions.remove(&uuid);
             removed_count += 1;
-            println!("清理过期会话: {}", uuid);
+            eprintln!("清理过期会话: {}", uuid);
         }
 
         Ok(removed_count)
@@ -136,14 +259,12 @@ impl SessionService {
 
         let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid.clone())?;
 
-        let deleted_message = session.delete_message(index)?;
+        session.delete_message(index)?;
 
         session.rewrite_all_history(app_handle).await?;
 
         SESSION_MANAGER.update_session(session)?;
 
-        println!("删除消息 [{}]: {:?}", index, deleted_message.content);
-
         Ok(())
     }
 
@@ -156,17 +277,37 @@ impl SessionService {
 
         let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid.clone())?;
 
-        let edited_message = session.edit_message(index, new_content)?;
+        session.edit_message(index, new_content)?;
 
         session.rewrite_all_history(app_handle).await?;
 
         SESSION_MANAGER.update_session(session)?;
 
-        println!("编辑消息 [{}]: {:?}", index, edited_message.content);
-
         Ok(())
     }
 
+    /// 撤销最后几轮对话，返回删除的消息数量
+    pub async fn undo_last_exchanges(
+        app_handle: &AppHandle,
+        uuid: &str,
+        count: usize,
+    ) -> Result<usize, String> {
+        let mut session = SESSION_MANAGER.get_or_create_session(app_handle, uuid.to_string())?;
+
+        let mut removed = 0;
+        for _ in 0..count {
+            removed += session.delete_last_exchange()?.len();
+        }
+
+        session.rewrite_all_history(app_handle).await?;
+
+        EventBus::chat_history_loaded(app_handle, &session.uuid, &session.chat_history)?;
+
+        SESSION_MANAGER.update_session(session)?;
+
+        Ok(removed)
+    }
+
     pub async fn regenerate_last_message(
         app_handle: &AppHandle,
     ) -> Result<(), String> {
@@ -196,11 +337,9 @@ impl SessionService {
             return Err("倒数第二条消息不是用户消息，无法重新生成".to_string());
         }
 
-        println!("重新生成消息，基于用户消息: {:?}", user_message.content);
-
         SESSION_MANAGER.update_session(session.clone())?;
 
-        Self::generate_ai_response(app_handle, &mut session, "regenerate").await
+        Self::generate_ai_response(app_handle, &mut session, "regenerate", &SessionOverrides::default()).await
     }
 
     pub async fn continue_chat(app_handle: &AppHandle) -> Result<(), String> {
@@ -217,17 +356,33 @@ impl SessionService {
             return Err("最后一条消息不是用户消息，无法继续对话".to_string());
         }
 
-        println!("继续对话，基于最后一条用户消息: {:?}", last_message.content);
-
-        Self::generate_ai_response(app_handle, &mut session, "continue").await
+        Self::generate_ai_response(app_handle, &mut session, "continue", &SessionOverrides::default()).await
     }
 
     async fn generate_ai_response(
         app_handle: &AppHandle,
         session: &mut CharacterSession,
         operation_type: &str,
+        overrides: &SessionOverrides,
     ) -> Result<(), String> {
-        let context_builder = crate::context_builder::create_default_context_builder();
+        let settings = overrides.apply(&session.settings);
+        let role = AIConfigService::resolve_role(app_handle, settings.ai_role.as_deref())?;
+
+        let api_chain = crate::api_config::ApiConfigService::get_failover_chain(app_handle)?;
+        let api_config = api_chain.first().ok_or("没有可用的API配置")?;
+
+        // 按模型能力确定上下文预算、输出上限与工具支持
+        let capabilities = ModelRegistry::get(app_handle, &api_config.model)?;
+        let context_limit = capabilities.context_budget();
+        let tools_enabled = role.as_ref().map(|(_, role)| role.tools_enabled).unwrap_or(true)
+            && capabilities.supports_tools != Some(false);
+
+        let context_builder = match &role {
+            Some((_, role)) => crate::context_builder::create_context_builder_for_role(role),
+            None => crate::context_builder::create_default_context_builder(),
+        }
+        .with_token_limit(context_limit)
+        .with_token_counter(TokenizerRegistry::get(app_handle, capabilities.tokenizer.as_deref()));
         let context_result = context_builder
             .build_full_context(
                 &session.character_data,
@@ -302,21 +457,32 @@ impl SessionService {
             });
         }
 
-        let api_config =
-            crate::api_config::ApiConfigService::get_default_api_config(app_handle)?
-                .ok_or("没有可用的API配置")?;
+        let preset = GenerationPreset::resolve(
+            api_config,
+            role.as_ref().map(|(_, role)| role),
+            settings.generation_preset.as_deref(),
+        );
 
-        let chat_tools = ToolRegistry::get_available_tools_global();
+        // 按工具档案、AI 角色与会话设置筛选发送给模型的工具
+        let tool_filter = ToolSettingsService::filter_for(app_handle, &settings)?;
+        let chat_tools = ToolRegistry::get_available_tools_where_global(|tool| tool_filter.allows(tool));
 
         let disable_tools_for_debug = false;
-
-        println!("=== AI 请求调试信息 ===");
-        println!("模型: {}", api_config.model);
-        println!("API端点: {}", api_config.endpoint);
-        println!("消息数量: {}", ai_chat_messages.len());
-        println!("工具数量: {}", chat_tools.len());
+        let send_tools = tools_enabled && !disable_tools_for_debug;
+
+        eprintln!("=== AI 请求调试信息 ===");
+        eprintln!("模型: {}", api_config.model);
+        eprintln!("API端点: {}", api_config.endpoint);
+        eprintln!("备用配置数量: {}", api_chain.len() - 1);
+        eprintln!("消息数量: {}", ai_chat_messages.len());
+        eprintln!("工具数量: {}", chat_tools.len());
+        eprintln!("上下文预算: {} tokens", context_limit);
+        eprintln!("生成预设: {}", preset.name);
+        if let Some((role_name, _)) = &role {
+            eprintln!("AI角色: {} (工具{})", role_name, if tools_enabled { "启用" } else { "禁用" });
+        }
         if disable_tools_for_debug {
-            println!("⚠️ 工具已临时禁用（调试模式）");
+            eprintln!("⚠️ 工具已临时禁用（调试模式）");
         }
 
         for (idx, msg) in ai_chat_messages.iter().enumerate() {
@@ -326,7 +492,7 @@ impl SessionService {
                 crate::ai_chat::MessageRole::Assistant => "assistant",
                 crate::ai_chat::MessageRole::Tool => "tool",
             };
-            println!(
+            eprintln!(
                 "消息[{}] role={}, content_len={}, has_tool_calls={}, tool_call_id={:?}",
                 idx,
                 role_str,
@@ -335,39 +501,46 @@ impl SessionService {
                 msg.tool_call_id
             );
             if msg.content.is_empty() && msg.tool_calls.is_none() {
-                println!("⚠️ 警告: 消息[{}]内容为空且没有tool_calls", idx);
+                eprintln!("⚠️ 警告: 消息[{}]内容为空且没有tool_calls", idx);
             }
         }
-        println!("=====================");
+        eprintln!("=====================");
 
-        let request = crate::ai_chat::ChatCompletionRequest {
+        let mut request = crate::ai_chat::ChatCompletionRequest {
             model: api_config.model.clone(),
             messages: ai_chat_messages,
-            temperature: Some(0.7),
-            max_tokens: Some(2048),
+            temperature: None,
+            max_tokens: None,
             top_p: None,
+            top_k: None,
+            min_p: None,
             frequency_penalty: None,
             presence_penalty: None,
+            repetition_penalty: None,
+            seed: None,
             stop: None,
             stream: Some(false),
-            tools: if disable_tools_for_debug {
-                None
+            tools: if send_tools { Some(chat_tools) } else { None },
+            tool_choice: if send_tools {
+                Some(crate::ai_chat::ToolChoice::String("auto".to_string()))
             } else {
-                Some(chat_tools)
-            },
-            tool_choice: if disable_tools_for_debug {
                 None
-            } else {
-                Some(crate::ai_chat::ToolChoice::String("auto".to_string()))
             },
         };
+        preset.apply_to(&mut request);
+        if let Some(max_output) = capabilities.max_output_tokens {
+            request.max_tokens = request.max_tokens.map(|tokens| tokens.min(max_output));
+        }
 
         let start_time = std::time::Instant::now();
 
-        let ai_response_result = crate::ai_chat::AIChatService::create_chat_completion(
-            &api_config,
+        let ai_response_result = crate::ai_chat::AIChatService::create_chat_completion_with_failover(
+            &api_chain,
             &request,
             Some(app_handle),
+            &ToolLoopPolicy::for_role(role.as_ref().map(|(_, role)| role)),
+            Some(&session.uuid),
+            overrides,
         )
         .await
         .map_err(|e| {
@@ -405,23 +578,21 @@ impl SessionService {
         if let Some(intermediate_msgs) = &ai_response_result.intermediate_messages {
             for msg in intermediate_msgs {
                 match msg.role {
-                    crate::ai_chat::MessageRole::Assistant => {
-                        if msg.tool_calls.is_some() {
-                            let converted_calls = msg.tool_calls.as_ref().map(|calls| {
-                                calls
-                                    .iter()
-                                    .map(|call| crate::chat_history::ToolCall {
-                                        id: call.id.clone(),
-                                        r#type: call.call_type.clone(),
-                                        function: crate::chat_history::ToolFunction {
-                                            name: call.function.name.clone(),
-                                            arguments: call.function.arguments.clone(),
-                                        },
-                                    })
-                                    .collect::<Vec<_>>()
-                            });
-                            session.add_assistant_message(msg.content.clone(), converted_calls);
-                        }
+                    crate::ai_chat::MessageRole::Assistant if msg.tool_calls.is_some() => {
+                        let converted_calls = msg.tool_calls.as_ref().map(|calls| {
+                            calls
+                                .iter()
+                                .map(|call| crate::chat_history::ToolCall {
+                                    id: call.id.clone(),
+                                    r#type: call.call_type.clone(),
+                                    function: crate::chat_history::ToolFunction {
+                                        name: call.function.name.clone(),
+                                        arguments: call.function.arguments.clone(),
+                                    },
+                                })
+                                .collect::<Vec<_>>()
+                        });
+                        session.add_assistant_message(msg.content.clone(), converted_calls, None);
                     }
                     crate::ai_chat::MessageRole::Tool => {
                         if let Some(tool_call_id) = &msg.tool_call_id {
@@ -437,7 +608,11 @@ impl SessionService {
             }
         }
 
-        let ai_response = session.add_assistant_message(ai_content.clone(), converted_tool_calls);
+        let ai_response = session.add_assistant_message(
+            ai_content.clone(),
+            converted_tool_calls,
+            ai_response_result.served_by.clone(),
+        );
 
         let converted_intermediate_msgs =
             ai_response_result
@@ -469,6 +644,7 @@ impl SessionService {
                             }),
                             tool_call_id: msg.tool_call_id.clone(),
                             name: msg.name.clone(),
+                            served_by: None,
                         })
                         .collect()
                 });
@@ -485,7 +661,7 @@ impl SessionService {
             completion_tokens: ai_response_result.usage.completion_tokens as usize,
             total_tokens: ai_response_result.usage.total_tokens as usize,
             context_tokens: context_result.total_tokens,
-            budget_utilization: (ai_response_result.usage.total_tokens as f64 / 102400.0 * 100.0),
+            budget_utilization: (ai_response_result.usage.total_tokens as f64 / context_limit as f64 * 100.0),
         };
 
         EventBus::token_stats(app_handle, &session.uuid, token_stats)?;
@@ -2,8 +2,14 @@ pub mod commands;
 pub mod events;
 pub mod sessions;
 
-pub use commands::models::{CommandCategory, CommandMetadata, CommandResult};
+pub use commands::models::{
+    BatchCommandReport, BatchCommandRequest, BatchItemResult, BatchTarget, CommandArgument,
+    CommandArgumentType, CommandCategory, CommandCompletion, CommandCompletions, CommandMetadata,
+    CommandResult,
+};
 pub use events::payloads::{
+    BatchProgressPayload,
+    ChangeSetUpdatedPayload,
     CharacterLoadedPayload,
     CharacterUpdatedPayload,
     CharacterUpdateType,
@@ -15,8 +21,9 @@ pub use events::payloads::{
     SessionUnloadedPayload,
     TokenStatsPayload,
     TokenUsageStats,
+    ToolApprovalRequestedPayload,
     ToolExecutedPayload,
 };
-pub use sessions::config::{ContextBuilderOptions, TokenBudget};
+pub use sessions::config::{ContextBuilderOptions, SessionOverrides, SessionSettings, TokenBudget};
 pub use sessions::session::{SessionInfo, SessionStatus};
 
@@ -19,6 +19,98 @@ pub struct CommandMetadata {
     pub requires_confirmation: bool,
     /// 确认提示消息（可选）
     pub confirmation_message: Option<String>,
+    /// 参数声明（按位置参数在前的顺序）
+    #[serde(default)]
+    pub arguments: Vec<CommandArgument>,
+}
+
+/// 命令参数类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandArgumentType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// 剩余的整行文本（只能作为最后一个位置参数）
+    Text,
+}
+
+/// 命令参数声明
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type")]
+    pub arg_type: CommandArgumentType,
+    /// 位置参数按顺序填写，命名参数以 `--名称 值` 或 `--名称=值` 填写
+    #[serde(default)]
+    pub positional: bool,
+    #[serde(default)]
+    pub required: bool,
+    /// 可选值（为空表示不限制）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+impl CommandArgument {
+    /// 位置参数
+    pub fn positional(name: &str, arg_type: CommandArgumentType, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            arg_type,
+            positional: true,
+            required: false,
+            choices: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// 命名参数
+    pub fn named(name: &str, arg_type: CommandArgumentType, description: &str) -> Self {
+        Self {
+            positional: false,
+            ..Self::positional(name, arg_type, description)
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn with_choices(mut self, choices: &[&str]) -> Self {
+        self.choices = choices.iter().map(|choice| choice.to_string()).collect();
+        self
+    }
+
+    pub fn with_default(mut self, default: serde_json::Value) -> Self {
+        self.default = Some(default);
+        self
+    }
+}
+
+/// 补全候选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandCompletion {
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// 补全结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandCompletions {
+    /// 被替换文本在输入中的起始位置（字符偏移）
+    pub offset: usize,
+    /// 正在补全的参数（补全命令名时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument: Option<String>,
+    pub items: Vec<CommandCompletion>,
 }
 
 /// 命令分类
@@ -45,3 +137,89 @@ pub struct CommandResult {
     pub data: Option<serde_json::Value>,
 }
 
+
+impl CommandResult {
+    /// 成功结果
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            message: Some(message.into()),
+            error: None,
+            data: None,
+        }
+    }
+
+    /// 失败结果（命令已执行但未达成目的，如参数无效）
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: None,
+            error: Some(error.into()),
+            data: None,
+        }
+    }
+
+    /// 附带返回数据
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// 批量执行的目标角色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BatchTarget {
+    /// 按 UUID 列表选择（保持顺序）
+    Uuids { uuids: Vec<String> },
+    /// 选择带有指定标签的全部角色（不区分大小写）
+    Tag { tag: String },
+}
+
+/// 批量执行请求（`command` 与 `prompt` 二选一）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCommandRequest {
+    pub target: BatchTarget,
+    /// 要执行的命令行（如 `/snapshot 批量维护前`）
+    #[serde(default)]
+    pub command: Option<String>,
+    /// 直接发送给 AI 的提示词（支持 `{{char}}`、`{{user}}` 与 `{{card.字段}}` 占位符）
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// 相邻两个角色之间的等待时间（毫秒）
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// 只列出目标角色与将要执行的内容，不实际执行
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 遇到失败时停止处理剩余角色
+    #[serde(default)]
+    pub stop_on_error: bool,
+}
+
+/// 单个角色的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub uuid: String,
+    /// 角色名称（角色不存在时为空）
+    pub name: Option<String>,
+    pub success: bool,
+    /// 未执行（预览、取消或因先前失败而停止）
+    pub skipped: bool,
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub data: Option<serde_json::Value>,
+}
+
+/// 批量执行报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCommandReport {
+    pub batch_id: String,
+    pub dry_run: bool,
+    /// 是否被取消
+    pub cancelled: bool,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+}
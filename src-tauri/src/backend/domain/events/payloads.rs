@@ -1,4 +1,7 @@
+use crate::backend::domain::commands::models::BatchItemResult;
 use crate::backend::domain::sessions::session::SessionInfo;
+use crate::card_diff::FieldChange;
+use crate::change_set::ChangeSet;
 use crate::character_storage::CharacterData;
 use crate::chat_history::ChatMessage;
 use crate::context_builder::BuiltContextResult;
@@ -126,3 +129,36 @@ pub struct TokenUsageStats {
     pub budget_utilization: f64, // 预算使用百分比
 }
 
+/// 工具调用审批请求事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolApprovalRequestedPayload {
+    pub uuid: String,
+    /// 审批请求ID（回复审批时使用）
+    pub approval_id: String,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    /// 字段级修改预览
+    pub changes: Vec<FieldChange>,
+    /// 无法生成预览时的原因
+    pub preview_error: Option<String>,
+    pub timestamp: i64,
+}
+
+/// 提议修改集更新事件载荷
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeSetUpdatedPayload {
+    pub uuid: String,
+    pub change_set: ChangeSet,
+    pub timestamp: i64,
+}
+
+/// 批量命令进度事件载荷（每处理完一个角色发送一次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgressPayload {
+    pub batch_id: String,
+    pub completed: usize,
+    pub total: usize,
+    /// 刚处理完的角色结果
+    pub result: BatchItemResult,
+    pub timestamp: i64,
+}
@@ -1,10 +1,11 @@
+use crate::tool_profiles::ToolSelection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Token 预算分配策略
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenBudget {
-    /// 总限制：102400 (128k * 0.8)
+    /// 总限制：默认 102400 (128k * 0.8)，按模型上下文窗口调整
     pub total_limit: usize,
     /// System 消息保留：15%
     pub system_reserved: usize,
@@ -18,7 +19,13 @@ pub struct TokenBudget {
 
 impl Default for TokenBudget {
     fn default() -> Self {
-        let total = 102400; // 128k * 0.8
+        Self::with_total(102400) // 128k * 0.8
+    }
+}
+
+impl TokenBudget {
+    /// 按总限制比例分配各部分预算
+    pub fn with_total(total: usize) -> Self {
         Self {
             total_limit: total,
             system_reserved: (total as f64 * 0.15) as usize,
@@ -44,6 +51,13 @@ pub struct ContextBuilderOptions {
     pub prioritize_chat_history: bool,
     /// 占位符替换映射
     pub placeholders: HashMap<String, String>,
+    /// 是否在 System 消息中声明工具（AI 角色禁用工具时关闭）
+    #[serde(default = "default_tools_enabled")]
+    pub tools_enabled: bool,
+}
+
+fn default_tools_enabled() -> bool {
+    true
 }
 
 impl Default for ContextBuilderOptions {
@@ -62,7 +76,52 @@ impl Default for ContextBuilderOptions {
             ai_task: "{{TASK}}".to_string(),
             prioritize_chat_history: true,
             placeholders,
+            tools_enabled: true,
         }
     }
 }
 
+/// 会话级偏好设置（按角色持久化，卸载会话后仍保留）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionSettings {
+    /// 会话选择的生成参数预设名称（为空时使用 API 配置的默认预设）
+    #[serde(default)]
+    pub generation_preset: Option<String>,
+    /// 会话使用的 AI 角色标识（为空时使用 AI 配置的默认角色）
+    #[serde(default)]
+    pub ai_role: Option<String>,
+    /// 提议模式：修改角色卡的工具调用只加入待审阅修改集，不直接写入
+    #[serde(default)]
+    pub proposal_mode: bool,
+    /// 会话使用的工具档案（为空时使用全局档案）
+    #[serde(default)]
+    pub tool_profile: Option<String>,
+    /// 会话级工具启用规则（覆盖档案与 AI 角色的设置）
+    #[serde(default)]
+    pub tools: ToolSelection,
+}
+
+/// 只对一次生成生效的会话设置覆盖（不写入会话，也不持久化）
+#[derive(Debug, Clone, Default)]
+pub struct SessionOverrides {
+    pub ai_role: Option<String>,
+    pub tool_profile: Option<String>,
+    pub tools: Option<ToolSelection>,
+}
+
+impl SessionOverrides {
+    /// 本次生成实际使用的会话设置
+    pub fn apply(&self, settings: &SessionSettings) -> SessionSettings {
+        let mut effective = settings.clone();
+        if let Some(name) = &self.ai_role {
+            effective.ai_role = Some(name.clone());
+        }
+        if let Some(name) = &self.tool_profile {
+            effective.tool_profile = Some(name.clone());
+        }
+        if let Some(tools) = &self.tools {
+            effective.tools = tools.clone();
+        }
+        effective
+    }
+}
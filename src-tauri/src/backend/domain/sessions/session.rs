@@ -1,3 +1,4 @@
+use super::config::SessionSettings;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -23,5 +24,6 @@ pub struct SessionInfo {
     pub last_active: DateTime<Utc>,
     pub status: SessionStatus,
     pub last_context_tokens: usize,
+    pub settings: SessionSettings,
 }
 
@@ -4,6 +4,8 @@ pub mod api_config_commands;
 pub mod character_commands;
 pub mod chat_history_commands;
 pub mod general_commands;
+pub mod mcp_commands;
+pub mod model_commands;
 pub mod session_commands;
 pub mod token_commands;
 pub mod tool_commands;
@@ -14,6 +16,8 @@ pub use api_config_commands::*;
 pub use character_commands::*;
 pub use chat_history_commands::*;
 pub use general_commands::*;
+pub use mcp_commands::*;
+pub use model_commands::*;
 pub use session_commands::*;
 pub use token_commands::*;
 pub use tool_commands::*;
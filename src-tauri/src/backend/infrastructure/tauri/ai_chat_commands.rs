@@ -1,5 +1,5 @@
 use crate::ai_chat::{AIChatService, ChatCompletionRequest, ChatCompletionResponse};
-use crate::api_config::ApiConfig;
+use crate::api_config::{ApiConfig, ApiConfigService};
 
 #[tauri::command]
 pub async fn create_chat_completion(
@@ -7,14 +7,17 @@ pub async fn create_chat_completion(
     api_config: ApiConfig,
     request: ChatCompletionRequest,
 ) -> Result<ChatCompletionResponse, String> {
+    let api_config = ApiConfigService::restore_masked_key(&app, &api_config)?;
     AIChatService::create_chat_completion(&api_config, &request, Some(&app)).await
 }
 
 #[tauri::command]
 pub async fn create_streaming_chat_completion(
+    app: tauri::AppHandle,
     api_config: ApiConfig,
     request: ChatCompletionRequest,
 ) -> Result<String, String> {
+    let api_config = ApiConfigService::restore_masked_key(&app, &api_config)?;
     AIChatService::create_streaming_chat_completion(&api_config, &request).await
 }
 
@@ -4,7 +4,7 @@ use crate::api_config::{
 
 #[tauri::command]
 pub async fn get_all_api_configs(app_handle: tauri::AppHandle) -> Result<Vec<ApiConfig>, String> {
-    ApiConfigService::get_all_api_configs(&app_handle)
+    ApiConfigService::get_all_api_configs_masked(&app_handle)
 }
 
 #[tauri::command]
@@ -12,14 +12,15 @@ pub async fn get_api_config_by_profile(
     app_handle: tauri::AppHandle,
     profile: String,
 ) -> Result<Option<ApiConfig>, String> {
-    ApiConfigService::get_api_config_by_profile(&app_handle, &profile)
+    Ok(ApiConfigService::get_api_config_by_profile(&app_handle, &profile)?
+        .map(|config| config.masked()))
 }
 
 #[tauri::command]
 pub async fn get_default_api_config(
     app_handle: tauri::AppHandle,
 ) -> Result<Option<ApiConfig>, String> {
-    ApiConfigService::get_default_api_config(&app_handle)
+    Ok(ApiConfigService::get_default_api_config(&app_handle)?.map(|config| config.masked()))
 }
 
 #[tauri::command]
@@ -27,7 +28,7 @@ pub async fn create_api_config(
     app_handle: tauri::AppHandle,
     request: CreateApiRequest,
 ) -> Result<ApiConfig, String> {
-    ApiConfigService::create_api_config(&app_handle, request)
+    ApiConfigService::create_api_config(&app_handle, request).map(|config| config.masked())
 }
 
 #[tauri::command]
@@ -79,3 +80,9 @@ pub async fn fetch_models(
     ApiConfigService::fetch_models(&app_handle, &config).await
 }
 
+
+/// 显式获取指定配置的明文密钥（前端默认只拿到掩码）
+#[tauri::command]
+pub async fn reveal_api_key(app_handle: tauri::AppHandle, profile: String) -> Result<String, String> {
+    ApiConfigService::reveal_api_key(&app_handle, &profile)
+}
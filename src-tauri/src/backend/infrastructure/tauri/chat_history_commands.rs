@@ -31,9 +31,9 @@ pub async fn clear_chat_history(
     if let Some(mut session) = SESSION_MANAGER.get_session(&character_id) {
         session.clear_history();
         SESSION_MANAGER.update_session(session)?;
-        println!("✅ 已清空角色 {} 的聊天历史（内存+磁盘）", character_id);
+        eprintln!("✅ 已清空角色 {} 的聊天历史（内存+磁盘）", character_id);
     } else {
-        println!("✅ 已清空角色 {} 的聊天历史（仅磁盘）", character_id);
+        eprintln!("✅ 已清空角色 {} 的聊天历史（仅磁盘）", character_id);
     }
 
     Ok(())
@@ -0,0 +1,54 @@
+use crate::mcp_client::{McpServerConfig, McpServerStatus, McpService};
+use crate::mcp_server::{McpServer, McpServerInfo};
+
+#[tauri::command]
+pub async fn get_mcp_servers(app_handle: tauri::AppHandle) -> Result<Vec<McpServerConfig>, String> {
+    McpService::get_servers(&app_handle)
+}
+
+#[tauri::command]
+pub async fn get_mcp_server_status(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<McpServerStatus>, String> {
+    McpService::list_status(&app_handle)
+}
+
+#[tauri::command]
+pub async fn save_mcp_server(
+    app_handle: tauri::AppHandle,
+    config: McpServerConfig,
+) -> Result<McpServerStatus, String> {
+    McpService::upsert_server(&app_handle, config).await
+}
+
+#[tauri::command]
+pub async fn remove_mcp_server(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    McpService::remove_server(&app_handle, &name)
+}
+
+#[tauri::command]
+pub async fn reconnect_mcp_server(
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<McpServerStatus, String> {
+    McpService::reconnect(&app_handle, &name).await
+}
+
+#[tauri::command]
+pub async fn start_mcp_server(
+    app_handle: tauri::AppHandle,
+    port: Option<u16>,
+) -> Result<McpServerInfo, String> {
+    McpServer::start_tcp(app_handle, port.unwrap_or(0)).await
+}
+
+#[tauri::command]
+pub async fn stop_mcp_server() -> Result<bool, String> {
+    Ok(McpServer::stop_tcp())
+}
+
+/// 本地端口服务器的地址与访问令牌（未启动时为空）
+#[tauri::command]
+pub async fn get_mcp_server_info() -> Result<Option<McpServerInfo>, String> {
+    Ok(McpServer::tcp_info())
+}
@@ -0,0 +1,32 @@
+use crate::model_registry::{ModelCapabilities, ModelRegistry};
+
+#[tauri::command]
+pub async fn get_model_capabilities(
+    app_handle: tauri::AppHandle,
+    model: String,
+) -> Result<ModelCapabilities, String> {
+    ModelRegistry::get(&app_handle, &model)
+}
+
+#[tauri::command]
+pub async fn list_model_capabilities(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ModelCapabilities>, String> {
+    ModelRegistry::list(&app_handle)
+}
+
+#[tauri::command]
+pub async fn set_model_capabilities(
+    app_handle: tauri::AppHandle,
+    capabilities: ModelCapabilities,
+) -> Result<ModelCapabilities, String> {
+    ModelRegistry::set_override(&app_handle, capabilities)
+}
+
+#[tauri::command]
+pub async fn remove_model_capabilities(
+    app_handle: tauri::AppHandle,
+    model: String,
+) -> Result<(), String> {
+    ModelRegistry::remove_override(&app_handle, &model)
+}
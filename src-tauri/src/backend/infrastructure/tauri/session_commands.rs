@@ -1,5 +1,7 @@
+use crate::ai_chat::ChatTool;
 use crate::backend::application::session_service::SessionService;
 use crate::backend::domain::sessions::session::SessionInfo;
+use crate::tool_profiles::ToolSelection;
 
 /// 加载角色会话
 #[tauri::command]
@@ -34,6 +36,56 @@ pub async fn get_session_info(uuid: String) -> Result<SessionInfo, String> {
     SessionService::get_session_info(uuid)
 }
 
+/// 设置会话使用的生成参数预设（传入空值恢复为 API 配置的默认预设）
+#[tauri::command]
+pub async fn set_session_generation_preset(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    preset_name: Option<String>,
+) -> Result<SessionInfo, String> {
+    SessionService::set_generation_preset(&app_handle, uuid, preset_name).await
+}
+
+/// 切换会话使用的 AI 角色（传入空值恢复为默认角色，下一次生成时生效）
+#[tauri::command]
+pub async fn set_session_ai_role(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    role_name: Option<String>,
+) -> Result<SessionInfo, String> {
+    SessionService::set_ai_role(&app_handle, uuid, role_name).await
+}
+
+/// 设置会话的提议模式（AI 修改先进入待审阅修改集）
+#[tauri::command]
+pub async fn set_session_proposal_mode(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    enabled: bool,
+) -> Result<SessionInfo, String> {
+    SessionService::set_proposal_mode(&app_handle, uuid, enabled).await
+}
+
+/// 设置会话的工具档案与会话级工具启用规则
+#[tauri::command]
+pub async fn set_session_tool_settings(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    tool_profile: Option<String>,
+    tools: ToolSelection,
+) -> Result<SessionInfo, String> {
+    SessionService::set_tool_settings(&app_handle, uuid, tool_profile, tools).await
+}
+
+/// 获取会话当前启用的工具
+#[tauri::command]
+pub async fn get_session_enabled_tools(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+) -> Result<Vec<ChatTool>, String> {
+    SessionService::get_enabled_tools(&app_handle, uuid)
+}
+
 /// 获取所有活跃会话信息
 #[tauri::command]
 pub async fn get_all_sessions() -> Result<Vec<SessionInfo>, String> {
@@ -1,26 +1,80 @@
-use crate::token_counter::{get_token_counter, TokenCountResult};
+use crate::character_storage::CharacterStorage;
+use crate::token_breakdown::CardTokenBreakdown;
+use crate::token_counter::{TokenCountResult, TokenCounter, TokenizerRegistry};
+use std::sync::Arc;
+
+/// 按指定模型选择分词器，未指定时使用默认 API 配置的模型
+fn resolve_counter(
+    app_handle: &tauri::AppHandle,
+    model: Option<String>,
+) -> Result<Arc<TokenCounter>, String> {
+    match model.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(model) => TokenizerRegistry::for_model(app_handle, model),
+        None => TokenizerRegistry::for_active_model(app_handle),
+    }
+}
 
 #[tauri::command]
-pub async fn count_tokens(text: String) -> Result<TokenCountResult, String> {
-    let counter = get_token_counter();
+pub async fn count_tokens(
+    app_handle: tauri::AppHandle,
+    text: String,
+    model: Option<String>,
+) -> Result<TokenCountResult, String> {
+    let counter = resolve_counter(&app_handle, model)?;
     Ok(counter.count_tokens(&text))
 }
 
 #[tauri::command]
-pub async fn count_tokens_batch(texts: Vec<String>) -> Result<Vec<TokenCountResult>, String> {
-    let counter = get_token_counter();
+pub async fn count_tokens_batch(
+    app_handle: tauri::AppHandle,
+    texts: Vec<String>,
+    model: Option<String>,
+) -> Result<Vec<TokenCountResult>, String> {
+    let counter = resolve_counter(&app_handle, model)?;
     Ok(counter.count_tokens_batch(&texts))
 }
 
 #[tauri::command]
-pub async fn check_token_limit(text: String, limit: usize) -> Result<bool, String> {
-    let counter = get_token_counter();
+pub async fn check_token_limit(
+    app_handle: tauri::AppHandle,
+    text: String,
+    limit: usize,
+    model: Option<String>,
+) -> Result<bool, String> {
+    let counter = resolve_counter(&app_handle, model)?;
     Ok(counter.is_within_limit(&text, limit))
 }
 
 #[tauri::command]
-pub async fn truncate_to_token_limit(text: String, limit: usize) -> Result<String, String> {
-    let counter = get_token_counter();
+pub async fn truncate_to_token_limit(
+    app_handle: tauri::AppHandle,
+    text: String,
+    limit: usize,
+    model: Option<String>,
+) -> Result<String, String> {
+    let counter = resolve_counter(&app_handle, model)?;
     Ok(counter.truncate_to_limit(&text, limit))
 }
 
+#[tauri::command]
+pub async fn get_card_token_breakdown(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    model: Option<String>,
+) -> Result<CardTokenBreakdown, String> {
+    let character = CharacterStorage::get_character_by_uuid(&app_handle, &uuid)?
+        .ok_or_else(|| format!("角色 {} 不存在", uuid))?;
+    let counter = resolve_counter(&app_handle, model)?;
+    Ok(CardTokenBreakdown::build(&counter, &character))
+}
+
+#[tauri::command]
+pub async fn list_tokenizers(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    TokenizerRegistry::list(&app_handle)
+}
+
+#[tauri::command]
+pub async fn reload_tokenizers() -> Result<(), String> {
+    TokenizerRegistry::clear_cache();
+    Ok(())
+}
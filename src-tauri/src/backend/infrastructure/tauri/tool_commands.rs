@@ -1,6 +1,12 @@
 use crate::ai_chat::ChatTool;
 use crate::ai_tools::{ToolCallRequest, ToolResult};
 use crate::backend::application::tool_service::ToolService;
+use crate::backend::domain::ToolApprovalRequestedPayload;
+use crate::change_set::{ChangeSet, ChangeSetService};
+use crate::tool_approval::{ApprovalDecision, ToolApprovalConfig, ToolApprovalService};
+use crate::tool_audit::{ToolAuditQuery, ToolAuditRecord, ToolAuditService};
+use crate::tool_profiles::{ToolSettings, ToolSettingsService};
+use crate::user_tools::{UserToolService, UserToolStatus};
 
 #[tauri::command]
 pub async fn get_available_tools() -> Result<Vec<ChatTool>, String> {
@@ -25,3 +31,119 @@ pub async fn get_tool_categories() -> Result<Vec<&'static str>, String> {
     Ok(ToolService::get_tool_categories())
 }
 
+
+#[tauri::command]
+pub async fn get_tool_approval_config(
+    app_handle: tauri::AppHandle,
+) -> Result<ToolApprovalConfig, String> {
+    ToolApprovalService::get_config(&app_handle)
+}
+
+#[tauri::command]
+pub async fn update_tool_approval_config(
+    app_handle: tauri::AppHandle,
+    config: ToolApprovalConfig,
+) -> Result<(), String> {
+    ToolApprovalService::save_config(&app_handle, &config)
+}
+
+#[tauri::command]
+pub async fn respond_tool_approval(
+    approval_id: String,
+    decision: ApprovalDecision,
+) -> Result<(), String> {
+    ToolApprovalService::respond(&approval_id, decision)
+}
+
+#[tauri::command]
+pub async fn get_pending_tool_approvals() -> Result<Vec<ToolApprovalRequestedPayload>, String> {
+    Ok(ToolApprovalService::list_pending())
+}
+
+#[tauri::command]
+pub async fn get_change_set(app_handle: tauri::AppHandle, uuid: String) -> Result<ChangeSet, String> {
+    ChangeSetService::get(&app_handle, &uuid)
+}
+
+#[tauri::command]
+pub async fn apply_proposed_change(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    change_id: String,
+) -> Result<ChangeSet, String> {
+    ChangeSetService::apply(&app_handle, &uuid, Some(&change_id)).await
+}
+
+#[tauri::command]
+pub async fn discard_proposed_change(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    change_id: String,
+) -> Result<ChangeSet, String> {
+    ChangeSetService::discard(&app_handle, &uuid, Some(&change_id)).await
+}
+
+#[tauri::command]
+pub async fn apply_change_set(app_handle: tauri::AppHandle, uuid: String) -> Result<ChangeSet, String> {
+    ChangeSetService::apply(&app_handle, &uuid, None).await
+}
+
+#[tauri::command]
+pub async fn discard_change_set(app_handle: tauri::AppHandle, uuid: String) -> Result<ChangeSet, String> {
+    ChangeSetService::discard(&app_handle, &uuid, None).await
+}
+
+#[tauri::command]
+pub async fn get_user_tool_status() -> Result<Vec<UserToolStatus>, String> {
+    Ok(UserToolService::list_status())
+}
+
+#[tauri::command]
+pub async fn reload_user_tools(app_handle: tauri::AppHandle) -> Result<Vec<UserToolStatus>, String> {
+    UserToolService::reload(&app_handle)
+}
+
+#[tauri::command]
+pub async fn get_user_tools_dir(app_handle: tauri::AppHandle) -> Result<String, String> {
+    UserToolService::tools_dir(&app_handle).map(|dir| dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn get_tool_settings(app_handle: tauri::AppHandle) -> Result<ToolSettings, String> {
+    ToolSettingsService::get_settings(&app_handle)
+}
+
+#[tauri::command]
+pub async fn update_tool_settings(
+    app_handle: tauri::AppHandle,
+    settings: ToolSettings,
+) -> Result<(), String> {
+    ToolSettingsService::save_settings(&app_handle, &settings)
+}
+
+#[tauri::command]
+pub async fn set_active_tool_profile(
+    app_handle: tauri::AppHandle,
+    name: Option<String>,
+) -> Result<ToolSettings, String> {
+    ToolSettingsService::set_active_profile(&app_handle, name)
+}
+
+#[tauri::command]
+pub async fn query_tool_audit_log(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    query: Option<ToolAuditQuery>,
+) -> Result<Vec<ToolAuditRecord>, String> {
+    ToolAuditService::query(&app_handle, &uuid, &query.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn revert_tool_call(
+    app_handle: tauri::AppHandle,
+    uuid: String,
+    record_id: String,
+    force: Option<bool>,
+) -> Result<ToolAuditRecord, String> {
+    ToolAuditService::revert(&app_handle, &uuid, &record_id, force.unwrap_or(false)).await
+}
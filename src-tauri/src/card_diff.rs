@@ -0,0 +1,105 @@
+use crate::character_storage::TavernCardV2;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// 角色卡字段级修改
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// 字段路径（如 description、tags、character_book.entries[3]）
+    pub path: String,
+    /// 修改前的值（新增时为空）
+    pub before: Option<Value>,
+    /// 修改后的值（删除时为空）
+    pub after: Option<Value>,
+}
+
+/// 比较两个角色卡，列出发生变化的字段（世界书按条目比较）
+pub fn diff_cards(before: &TavernCardV2, after: &TavernCardV2) -> Vec<FieldChange> {
+    let before = serde_json::to_value(&before.data).unwrap_or(Value::Null);
+    let after = serde_json::to_value(&after.data).unwrap_or(Value::Null);
+
+    let mut changes = Vec::new();
+    diff_objects("", &before, &after, &mut changes, &|key| key == "character_book");
+
+    let before_book = before.get("character_book").filter(|book| !book.is_null());
+    let after_book = after.get("character_book").filter(|book| !book.is_null());
+    match (before_book, after_book) {
+        (None, None) => {}
+        (Some(before_book), Some(after_book)) => {
+            diff_objects("character_book.", before_book, after_book, &mut changes, &|key| key == "entries");
+
+            let empty = Vec::new();
+            let before_entries = before_book.get("entries").and_then(Value::as_array).unwrap_or(&empty);
+            let after_entries = after_book.get("entries").and_then(Value::as_array).unwrap_or(&empty);
+            for index in 0..before_entries.len().max(after_entries.len()) {
+                let before_entry = before_entries.get(index);
+                let after_entry = after_entries.get(index);
+                if before_entry != after_entry {
+                    changes.push(FieldChange {
+                        path: format!("character_book.entries[{}]", index),
+                        before: before_entry.cloned(),
+                        after: after_entry.cloned(),
+                    });
+                }
+            }
+        }
+        (before_book, after_book) => changes.push(FieldChange {
+            path: "character_book".to_string(),
+            before: before_book.cloned(),
+            after: after_book.cloned(),
+        }),
+    }
+
+    changes
+}
+
+/// 比较两个 JSON 对象的顶层字段
+fn diff_objects(
+    prefix: &str,
+    before: &Value,
+    after: &Value,
+    changes: &mut Vec<FieldChange>,
+    skip: &dyn Fn(&str) -> bool,
+) {
+    let keys: BTreeSet<&String> = before
+        .as_object()
+        .into_iter()
+        .chain(after.as_object())
+        .flat_map(|map| map.keys())
+        .collect();
+
+    for key in keys.into_iter().filter(|key| !skip(key)) {
+        let before_value = before.get(key).filter(|value| !value.is_null());
+        let after_value = after.get(key).filter(|value| !value.is_null());
+        if before_value != after_value {
+            changes.push(FieldChange {
+                path: format!("{}{}", prefix, key),
+                before: before_value.cloned(),
+                after: after_value.cloned(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support::card;
+
+    #[test]
+    fn test_diff_reports_fields_and_lorebook_entries() {
+        let entry = serde_json::json!({ "keys": ["k"], "content": "c", "enabled": true, "insertion_order": 1 });
+        let before = card("old", Some(serde_json::json!([])));
+        let after = card("new", Some(serde_json::json!([entry])));
+
+        let changes = diff_cards(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "description");
+        assert_eq!(changes[0].before, Some(Value::String("old".to_string())));
+        assert_eq!(changes[1].path, "character_book.entries[0]");
+        assert!(changes[1].before.is_none());
+
+        assert!(diff_cards(&before, &before).is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::ai_tools::{ToolCallRequest, ToolResult};
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::CharacterUpdateType;
+use crate::card_diff::{diff_cards, FieldChange};
+use crate::character_storage::{CharacterStorage, TavernCardV2};
+use crate::file_utils::FileUtils;
+use crate::tool_audit::ToolAuditService;
+use crate::tools::{CharacterLock, ToolRegistry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// AI 提议的一次修改（提议模式下工具调用不直接写入角色卡）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedChange {
+    pub id: String,
+    pub tool_name: String,
+    pub arguments: HashMap<String, Value>,
+    /// 相对当前角色卡的字段级修改（读取修改集时重新计算）
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+    /// 无法应用到当前角色卡时的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+    pub created_at: i64,
+}
+
+impl ProposedChange {
+    fn to_request(&self, character_uuid: &str) -> ToolCallRequest {
+        ToolCallRequest {
+            tool_name: self.tool_name.clone(),
+            parameters: self.arguments.clone(),
+            character_uuid: Some(character_uuid.to_string()),
+            context: None,
+            origin: None,
+        }
+    }
+
+    /// 以当前角色卡为基准重新计算修改预览
+    fn refresh(&mut self, character_uuid: &str, card: &TavernCardV2) {
+        let mut preview = card.clone();
+        match ToolRegistry::apply_to_card_global(&mut preview, &self.to_request(character_uuid)) {
+            Ok(()) => {
+                self.changes = diff_cards(card, &preview);
+                self.conflict = None;
+            }
+            Err(e) => {
+                self.changes.clear();
+                self.conflict = Some(e);
+            }
+        }
+    }
+}
+
+/// 角色的待审阅修改集
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub uuid: String,
+    pub proposals: Vec<ProposedChange>,
+}
+
+/// 提议模式修改集服务
+pub struct ChangeSetService;
+
+impl ChangeSetService {
+    fn get_change_set_file(app_handle: &tauri::AppHandle, uuid: &str) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir
+            .join("character-cards")
+            .join(uuid)
+            .join("change_set.json"))
+    }
+
+    fn load(app_handle: &tauri::AppHandle, uuid: &str) -> Result<ChangeSet, String> {
+        let change_set_file = Self::get_change_set_file(app_handle, uuid)?;
+        if !change_set_file.exists() {
+            return Ok(ChangeSet {
+                uuid: uuid.to_string(),
+                proposals: Vec::new(),
+            });
+        }
+        FileUtils::read_json_file(&change_set_file)
+    }
+
+    /// 保存修改集并通知前端
+    fn save(app_handle: &tauri::AppHandle, change_set: &ChangeSet) -> Result<(), String> {
+        let change_set_file = Self::get_change_set_file(app_handle, &change_set.uuid)?;
+        FileUtils::write_json_file(&change_set_file, change_set)?;
+
+        if let Err(e) = EventBus::change_set_updated(app_handle, change_set) {
+            eprintln!("{}", e);
+        }
+        Ok(())
+    }
+
+    fn get_card(app_handle: &tauri::AppHandle, uuid: &str) -> Result<TavernCardV2, String> {
+        CharacterStorage::get_character_by_uuid(app_handle, uuid)?
+            .map(|data| data.card)
+            .ok_or_else(|| "角色不存在".to_string())
+    }
+
+    /// 获取修改集（每项修改都与当前角色卡比较）
+    pub fn get(app_handle: &tauri::AppHandle, uuid: &str) -> Result<ChangeSet, String> {
+        let mut change_set = Self::load(app_handle, uuid)?;
+        if change_set.proposals.is_empty() {
+            return Ok(change_set);
+        }
+
+        let card = Self::get_card(app_handle, uuid)?;
+        for proposal in &mut change_set.proposals {
+            proposal.refresh(uuid, &card);
+        }
+        Ok(change_set)
+    }
+
+    /// 将工具调用记录为提议修改（参数无法应用时返回错误，不加入修改集）
+    pub async fn propose(
+        app_handle: &tauri::AppHandle,
+        request: &ToolCallRequest,
+    ) -> Result<ProposedChange, String> {
+        let uuid = request
+            .character_uuid
+            .as_deref()
+            .ok_or_else(|| "缺少角色UUID".to_string())?;
+        let _character_guard = CharacterLock::acquire(uuid).await;
+
+        let card = Self::get_card(app_handle, uuid)?;
+        let mut proposal = ProposedChange {
+            id: FileUtils::generate_uuid(),
+            tool_name: request.tool_name.clone(),
+            arguments: request.parameters.clone(),
+            changes: Vec::new(),
+            conflict: None,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        proposal.refresh(uuid, &card);
+        if let Some(conflict) = proposal.conflict {
+            return Err(conflict);
+        }
+
+        let mut change_set = Self::load(app_handle, uuid)?;
+        change_set.proposals.push(proposal.clone());
+        Self::save(app_handle, &change_set)?;
+
+        Ok(proposal)
+    }
+
+    /// 应用修改（`change_id` 为空时按提议顺序应用整个修改集）
+    ///
+    /// 所有选中的修改在同一角色卡副本上依次应用，任一失败则不写入
+    pub async fn apply(
+        app_handle: &tauri::AppHandle,
+        uuid: &str,
+        change_id: Option<&str>,
+    ) -> Result<ChangeSet, String> {
+        let _character_guard = CharacterLock::acquire(uuid).await;
+
+        let mut change_set = Self::load(app_handle, uuid)?;
+        let selected = Self::select(&change_set, change_id)?;
+        if selected.is_empty() {
+            return Ok(change_set);
+        }
+
+        let start_time = std::time::Instant::now();
+        let mut card = Self::get_card(app_handle, uuid)?;
+        let mut applied = Vec::with_capacity(selected.len());
+        for proposal in change_set.proposals.iter().filter(|p| selected.contains(&p.id)) {
+            let before = card.clone();
+            let request = proposal.to_request(uuid);
+            ToolRegistry::apply_to_card_global(&mut card, &request)
+                .map_err(|e| format!("应用修改 {} 失败: {}", proposal.id, e))?;
+            applied.push((request, diff_cards(&before, &card)));
+        }
+
+        CharacterStorage::update_character(app_handle, uuid, &card)?;
+
+        // 每项修改单独记入审计日志，便于逐项撤销
+        let result = ToolResult {
+            success: true,
+            data: None,
+            error: None,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        };
+        for (request, changes) in applied {
+            ToolAuditService::record_changes(app_handle, &request, &result, changes);
+        }
+        if let Some(character_data) = CharacterStorage::get_character_by_uuid(app_handle, uuid)? {
+            if let Err(e) = EventBus::character_updated(
+                app_handle,
+                uuid,
+                &character_data,
+                CharacterUpdateType::FullData,
+            ) {
+                eprintln!("发送角色更新事件失败: {}", e);
+            }
+        }
+
+        change_set.proposals.retain(|p| !selected.contains(&p.id));
+        Self::save(app_handle, &change_set)?;
+
+        Self::get(app_handle, uuid)
+    }
+
+    /// 丢弃修改（`change_id` 为空时清空整个修改集）
+    pub async fn discard(
+        app_handle: &tauri::AppHandle,
+        uuid: &str,
+        change_id: Option<&str>,
+    ) -> Result<ChangeSet, String> {
+        let _character_guard = CharacterLock::acquire(uuid).await;
+
+        let mut change_set = Self::load(app_handle, uuid)?;
+        let selected = Self::select(&change_set, change_id)?;
+        change_set.proposals.retain(|p| !selected.contains(&p.id));
+        Self::save(app_handle, &change_set)?;
+
+        Self::get(app_handle, uuid)
+    }
+
+    fn select(change_set: &ChangeSet, change_id: Option<&str>) -> Result<Vec<String>, String> {
+        match change_id {
+            None => Ok(change_set.proposals.iter().map(|p| p.id.clone()).collect()),
+            Some(id) if change_set.proposals.iter().any(|p| p.id == id) => Ok(vec![id.to_string()]),
+            Some(id) => Err(format!("修改 {} 不存在", id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support;
+
+    #[test]
+    fn test_refresh_previews_against_current_card() {
+        let card = test_support::card("old", None);
+
+        let mut proposal = ProposedChange {
+            id: "p1".to_string(),
+            tool_name: "edit_character".to_string(),
+            arguments: HashMap::from([("description".to_string(), Value::from("new"))]),
+            changes: Vec::new(),
+            conflict: None,
+            created_at: 0,
+        };
+        proposal.refresh("uuid", &card);
+        assert!(proposal.conflict.is_none());
+        assert_eq!(proposal.changes.len(), 1);
+        assert_eq!(proposal.changes[0].after, Some(Value::from("new")));
+
+        proposal.arguments.clear();
+        proposal.refresh("uuid", &card);
+        assert!(proposal.conflict.is_some());
+    }
+}
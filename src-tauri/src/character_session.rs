@@ -1,8 +1,10 @@
-use crate::backend::domain::{SessionInfo, SessionStatus};
+use crate::backend::domain::{SessionInfo, SessionSettings, SessionStatus};
 use crate::character_storage::CharacterData;
-use crate::chat_history::{ChatHistoryManager, ChatMessage};
+use crate::chat_history::{ChatHistoryManager, ChatMessage, ServedBy};
+use crate::file_utils::FileUtils;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::AppHandle;
@@ -24,6 +26,8 @@ pub struct CharacterSession {
     pub status: SessionStatus,
     /// 已保存到磁盘的消息数量（用于增量保存）
     pub last_saved_index: usize,
+    /// 会话级偏好设置
+    pub settings: SessionSettings,
 }
 
 impl CharacterSession {
@@ -38,6 +42,7 @@ impl CharacterSession {
             last_active: now,
             status: SessionStatus::Loading,
             last_saved_index: 0,
+            settings: SessionSettings::default(),
         }
     }
 
@@ -56,12 +61,44 @@ impl CharacterSession {
         let history_len = chat_history.len();
         session.chat_history = chat_history;
         session.last_saved_index = history_len; // 已加载的历史已经在磁盘上
+        session.settings = Self::load_settings(app_handle, &session.uuid)?;
         session.status = SessionStatus::Active;
         session.last_active = Utc::now();
 
         Ok(session)
     }
 
+    /// 获取会话设置文件路径
+    fn get_settings_file_path(app_handle: &AppHandle, uuid: &str) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir
+            .join("character-cards")
+            .join(uuid)
+            .join("session_settings.json"))
+    }
+
+    /// 读取会话设置（文件不存在或损坏时使用默认设置）
+    fn load_settings(app_handle: &AppHandle, uuid: &str) -> Result<SessionSettings, String> {
+        let settings_file = Self::get_settings_file_path(app_handle, uuid)?;
+        if !settings_file.exists() {
+            return Ok(SessionSettings::default());
+        }
+
+        match FileUtils::read_json_file::<SessionSettings>(&settings_file) {
+            Ok(settings) => Ok(settings),
+            Err(e) => {
+                eprintln!("读取会话设置失败，使用默认设置: {}", e);
+                Ok(SessionSettings::default())
+            }
+        }
+    }
+
+    /// 保存会话设置
+    pub fn save_settings(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let settings_file = Self::get_settings_file_path(app_handle, &self.uuid)?;
+        FileUtils::write_json_file(&settings_file, &self.settings)
+    }
+
     /// 添加用户消息到历史记录
     pub fn add_user_message(&mut self, content: String) -> ChatMessage {
         let message = ChatMessage {
@@ -76,6 +113,7 @@ impl CharacterSession {
                     .unwrap()
                     .as_secs() as i64,
             ),
+            served_by: None,
         };
 
         self.chat_history.push(message.clone());
@@ -88,6 +126,7 @@ impl CharacterSession {
         &mut self,
         content: String,
         tool_calls: Option<Vec<crate::chat_history::ToolCall>>,
+        served_by: Option<ServedBy>,
     ) -> ChatMessage {
         let message = ChatMessage {
             role: "assistant".to_string(),
@@ -101,6 +140,7 @@ impl CharacterSession {
                     .unwrap()
                     .as_secs() as i64,
             ),
+            served_by,
         };
 
         self.chat_history.push(message.clone());
@@ -127,6 +167,7 @@ impl CharacterSession {
                     .unwrap()
                     .as_secs() as i64,
             ),
+            served_by: None,
         };
 
         self.chat_history.push(message.clone());
@@ -216,6 +257,19 @@ impl CharacterSession {
         Ok(removed)
     }
 
+    /// 删除最后一轮对话（最后一条用户消息及其后的 AI 回复与工具消息）
+    pub fn delete_last_exchange(&mut self) -> Result<Vec<ChatMessage>, String> {
+        let start = self
+            .chat_history
+            .iter()
+            .rposition(|message| message.role == "user")
+            .ok_or("聊天历史中没有用户消息，无法撤销")?;
+
+        let removed = self.chat_history.split_off(start);
+        self.last_active = Utc::now();
+        Ok(removed)
+    }
+
     /// 获取会话信息摘要
     pub fn get_session_info(&self) -> SessionInfo {
         SessionInfo {
@@ -225,6 +279,7 @@ impl CharacterSession {
             last_active: self.last_active,
             status: self.status.clone(),
             last_context_tokens: self.last_context_tokens,
+            settings: self.settings.clone(),
         }
     }
 }
@@ -1,5 +1,6 @@
 use super::file_utils::FileUtils;
 use super::png_utils::PngMetadataUtils;
+use super::token_counter::{CardTokenCounts, TokenizerRegistry};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use image::{imageops::FilterType, DynamicImage, ImageFormat};
 use serde::{Deserialize, Serialize};
@@ -89,6 +90,38 @@ pub struct TavernCardV2Data {
     pub character_book: Option<CharacterBook>,
 }
 
+impl TavernCardV2Data {
+    /// 参与 Token 计数的文本字段（字段路径 → 内容）
+    pub fn text_fields(&self) -> Vec<(String, &str)> {
+        let mut fields: Vec<(String, &str)> = [
+            ("name", self.name.as_str()),
+            ("description", self.description.as_str()),
+            ("personality", self.personality.as_str()),
+            ("scenario", self.scenario.as_str()),
+            ("first_mes", self.first_mes.as_str()),
+            ("mes_example", self.mes_example.as_str()),
+            ("creator_notes", self.creator_notes.as_str()),
+            ("system_prompt", self.system_prompt.as_str()),
+            ("post_history_instructions", self.post_history_instructions.as_str()),
+        ]
+        .into_iter()
+        .map(|(path, text)| (path.to_string(), text))
+        .collect();
+
+        for (index, greeting) in self.alternate_greetings.iter().enumerate() {
+            fields.push((format!("alternate_greetings[{}]", index), greeting.as_str()));
+        }
+
+        if let Some(book) = &self.character_book {
+            for (index, entry) in book.entries.iter().enumerate() {
+                fields.push((format!("character_book.entries[{}]", index), entry.content.as_str()));
+            }
+        }
+
+        fields
+    }
+}
+
 /// Tavern Card V2 结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TavernCardV2 {
@@ -107,6 +140,9 @@ pub struct CharacterData {
     pub background_path: String,
     #[serde(rename = "thumbnailPath", default)]
     pub thumbnail_path: String,
+    /// 各字段 Token 计数缓存（不写入导出的角色卡）
+    #[serde(rename = "tokenCounts", default, skip_serializing_if = "Option::is_none")]
+    pub token_counts: Option<CardTokenCounts>,
 }
 
 const CARD_FILE_NAME: &str = "card.png";
@@ -451,6 +487,7 @@ impl CharacterStorage {
             card,
             background_path: String::new(),
             thumbnail_path: String::new(),
+            token_counts: None,
         };
 
         // 保存角色卡文件
@@ -478,6 +515,17 @@ impl CharacterStorage {
         character_data.card = card.clone();
         character_data.meta.updated_at = chrono::Utc::now().to_rfc3339();
 
+        // 刷新字段 Token 计数（仅重新计算变化的字段）
+        match TokenizerRegistry::for_active_model(app_handle) {
+            Ok(counter) => {
+                character_data
+                    .token_counts
+                    .get_or_insert_with(CardTokenCounts::default)
+                    .refresh(&counter, &card.data.text_fields());
+            }
+            Err(e) => eprintln!("刷新Token计数失败: {}", e),
+        }
+
         FileUtils::write_json_file(&card_file, &character_data)?;
         Ok(())
     }
@@ -585,6 +633,11 @@ impl CharacterStorage {
         Ok(())
     }
 
+    /// 角色是否有卡面图片（决定导出为 PNG 还是 JSON）
+    pub fn has_card_image(app_handle: &tauri::AppHandle, uuid: &str) -> Result<bool, String> {
+        Ok(Self::get_card_image_path(app_handle, uuid)?.exists())
+    }
+
     /// 导出角色卡
     ///
     /// # 参数
@@ -684,6 +737,7 @@ impl CharacterStorage {
             card,
             background_path: String::new(),
             thumbnail_path: String::new(),
+            token_counts: None,
         };
 
         // 保存角色卡及图片
@@ -751,6 +805,7 @@ impl CharacterStorage {
             card,
             background_path: String::new(),
             thumbnail_path: String::new(),
+            token_counts: None,
         };
 
         // 保存角色卡
@@ -772,3 +827,40 @@ impl CharacterStorage {
         Ok(response)
     }
 }
+
+/// 单元测试共用的角色卡与角色数据
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::{CharacterData, TavernCardV2};
+    use serde_json::Value;
+
+    /// 名为 Alice 的角色卡，除描述外的文本字段为空；提供条目时附带世界书
+    pub(crate) fn card(description: &str, entries: Option<Value>) -> TavernCardV2 {
+        let mut data = serde_json::json!({
+            "name": "Alice", "description": description, "personality": "", "scenario": "",
+            "first_mes": "", "mes_example": "", "creator_notes": "", "system_prompt": "",
+            "post_history_instructions": "", "alternate_greetings": [], "tags": [],
+            "creator": "", "character_version": ""
+        });
+        if let Some(entries) = entries {
+            data["character_book"] = serde_json::json!({ "entries": entries });
+        }
+        serde_json::from_value(serde_json::json!({
+            "spec": "chara_card_v2",
+            "spec_version": "2.0",
+            "data": data
+        }))
+        .unwrap()
+    }
+
+    /// 以给定角色卡构造的角色数据
+    pub(crate) fn character(uuid: &str, card: TavernCardV2) -> CharacterData {
+        serde_json::from_value(serde_json::json!({
+            "uuid": uuid,
+            "meta": { "uuid": uuid, "version": "1", "created_at": "", "updated_at": "" },
+            "card": card,
+            "backgroundPath": ""
+        }))
+        .unwrap()
+    }
+}
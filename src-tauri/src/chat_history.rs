@@ -12,6 +12,16 @@ pub struct ChatMessage {
     pub tool_calls: Option<Vec<ToolCall>>,
     pub tool_call_id: Option<String>,
     pub timestamp: Option<i64>,
+    /// 实际生成该回复的 API 配置与模型（仅 AI 回复）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<ServedBy>,
+}
+
+/// 生成回复的 API 配置与模型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServedBy {
+    pub profile: String,
+    pub model: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,6 +1,8 @@
+pub mod arguments;
 pub mod builtin;
 pub mod command;
 pub mod loader;
 pub mod registry;
 pub mod tauri_commands;
+pub mod user_command;
 
@@ -0,0 +1,341 @@
+use crate::backend::domain::{CommandArgument, CommandArgumentType};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 解析并校验后的命令参数
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandArguments {
+    values: HashMap<String, Value>,
+}
+
+impl CommandArguments {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// 字符串参数（空字符串视为未填写）
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get(name)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(Value::as_i64)
+    }
+
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.get(name).and_then(Value::as_bool).unwrap_or(false)
+    }
+
+    pub fn values(&self) -> &HashMap<String, Value> {
+        &self.values
+    }
+}
+
+/// 命令行中的一个词（引号内的空白不分词）
+#[derive(Debug)]
+struct Token {
+    text: String,
+    /// 在原始文本中的字节范围
+    start: usize,
+    end: usize,
+    /// 以引号开头（不会被当作命名参数）
+    quoted: bool,
+}
+
+impl Token {
+    /// 命名参数的名称与内联值（`--name` 或 `--name=value`）
+    fn named(&self) -> Option<(&str, Option<&str>)> {
+        if self.quoted {
+            return None;
+        }
+        let name = self.text.strip_prefix("--").filter(|name| !name.is_empty())?;
+        Some(match name.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (name, None),
+        })
+    }
+}
+
+fn tokenize(raw: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = Token {
+            text: String::new(),
+            start,
+            end: raw.len(),
+            quoted: c == '"' || c == '\'',
+        };
+        let mut quote = None;
+        while let Some(&(index, c)) = chars.peek() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => token.text.push(c),
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c.is_whitespace() => {
+                    token.end = index;
+                    break;
+                }
+                None => token.text.push(c),
+            }
+            chars.next();
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// 按参数声明转换单个值
+fn convert(argument: &CommandArgument, text: &str) -> Result<Value, String> {
+    if !argument.choices.is_empty() && !argument.choices.iter().any(|choice| choice == text) {
+        return Err(format!(
+            "参数 {} 的值必须是 {} 之一",
+            argument.name,
+            argument.choices.join("、")
+        ));
+    }
+
+    match argument.arg_type {
+        CommandArgumentType::String | CommandArgumentType::Text => Ok(Value::String(text.to_string())),
+        CommandArgumentType::Integer => text
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("参数 {} 需要整数，收到 {}", argument.name, text)),
+        CommandArgumentType::Number => text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| format!("参数 {} 需要数字，收到 {}", argument.name, text)),
+        CommandArgumentType::Boolean => match text.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+            "false" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("参数 {} 需要 true 或 false，收到 {}", argument.name, text)),
+        },
+    }
+}
+
+/// 解析命令行参数
+///
+/// 位置参数按声明顺序填写；命名参数写作 `--名称 值` 或 `--名称=值`，布尔命名参数可省略值；
+/// `text` 类型的位置参数取走其后的各个词（去掉引号、以单个空格连接），遇到已声明的命名参数为止
+pub fn parse(spec: &[CommandArgument], raw: &str) -> Result<CommandArguments, String> {
+    let tokens = tokenize(raw);
+    let mut values = HashMap::new();
+    let mut positionals = spec.iter().filter(|argument| argument.positional);
+    let find_named = |name: &str| spec.iter().find(|argument| !argument.positional && argument.name == name);
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        index += 1;
+
+        if let Some((name, inline_value)) = token.named() {
+            let argument = find_named(name).ok_or_else(|| format!("未知参数 --{}", name))?;
+            let text = match inline_value {
+                Some(value) => value.to_string(),
+                None if argument.arg_type == CommandArgumentType::Boolean => "true".to_string(),
+                None => {
+                    let value = tokens
+                        .get(index)
+                        .ok_or_else(|| format!("参数 --{} 缺少值", name))?;
+                    index += 1;
+                    value.text.clone()
+                }
+            };
+            values.insert(argument.name.clone(), convert(argument, &text)?);
+            continue;
+        }
+
+        let argument = positionals
+            .next()
+            .ok_or_else(|| format!("多余的参数: {}", token.text))?;
+        let text = if argument.arg_type == CommandArgumentType::Text {
+            let end = tokens[index..]
+                .iter()
+                .position(|token| token.named().is_some_and(|(name, _)| find_named(name).is_some()))
+                .map_or(tokens.len(), |offset| index + offset);
+            let words: Vec<&str> = tokens[index - 1..end].iter().map(|token| token.text.as_str()).collect();
+            index = end;
+            words.join(" ")
+        } else {
+            token.text.clone()
+        };
+        values.insert(argument.name.clone(), convert(argument, &text)?);
+    }
+
+    for argument in spec {
+        if values.contains_key(&argument.name) {
+            continue;
+        }
+        if let Some(default) = &argument.default {
+            values.insert(argument.name.clone(), default.clone());
+        } else if argument.required {
+            return Err(format!("缺少参数 {}", argument.name));
+        }
+    }
+
+    Ok(CommandArguments { values })
+}
+
+/// 命令用法（如 `/lore <test> <text...> [--limit <limit>]`）
+pub fn usage(command_name: &str, spec: &[CommandArgument]) -> String {
+    let mut usage = command_name.to_string();
+    for argument in spec {
+        let placeholder = match argument.arg_type {
+            CommandArgumentType::Text => format!("{}...", argument.name),
+            _ if !argument.choices.is_empty() => argument.choices.join("|"),
+            _ => argument.name.clone(),
+        };
+        let part = match (argument.positional, argument.arg_type) {
+            (true, _) => format!("<{}>", placeholder),
+            (false, CommandArgumentType::Boolean) => format!("--{}", argument.name),
+            (false, _) => format!("--{} <{}>", argument.name, placeholder),
+        };
+        if argument.required {
+            usage.push_str(&format!(" {}", part));
+        } else {
+            usage.push_str(&format!(" [{}]", part));
+        }
+    }
+    usage
+}
+
+/// 光标（输入末尾）处正在填写的内容
+#[derive(Debug, PartialEq)]
+pub enum CompletionTarget<'a> {
+    /// 命名参数的名称
+    Name { prefix: String, offset: usize },
+    /// 参数值
+    Value {
+        argument: &'a CommandArgument,
+        prefix: String,
+        offset: usize,
+    },
+}
+
+/// 确定补全位置（offset 为被替换文本在参数文本中的字节偏移）
+pub fn completion_target<'a>(spec: &'a [CommandArgument], raw: &str) -> Option<CompletionTarget<'a>> {
+    let mut tokens = tokenize(raw);
+    let current = match tokens.last() {
+        Some(token) if token.end == raw.len() => tokens.pop(),
+        _ => None,
+    };
+
+    let find_named = |name: &str| spec.iter().find(|argument| !argument.positional && argument.name == name);
+
+    let mut pending = None;
+    let mut positional_count = 0;
+    for token in &tokens {
+        if pending.take().is_some() {
+            continue;
+        }
+        if let Some((name, inline_value)) = token.named() {
+            pending = find_named(name)
+                .filter(|argument| inline_value.is_none() && argument.arg_type != CommandArgumentType::Boolean);
+            continue;
+        }
+        let argument = spec.iter().filter(|argument| argument.positional).nth(positional_count);
+        if argument.is_some_and(|argument| argument.arg_type == CommandArgumentType::Text) {
+            return None;
+        }
+        positional_count += 1;
+    }
+
+    let (prefix, offset) = current
+        .as_ref()
+        .map(|token| (token.text.clone(), token.start))
+        .unwrap_or((String::new(), raw.len()));
+
+    if let Some(argument) = pending {
+        return Some(CompletionTarget::Value { argument, prefix, offset });
+    }
+    if let Some((name, inline_value)) = current.as_ref().and_then(Token::named) {
+        return match inline_value {
+            Some(value) => Some(CompletionTarget::Value {
+                argument: find_named(name)?,
+                prefix: value.to_string(),
+                offset: offset + name.len() + 3,
+            }),
+            None => Some(CompletionTarget::Name { prefix, offset }),
+        };
+    }
+
+    match spec.iter().filter(|argument| argument.positional).nth(positional_count) {
+        Some(argument) => Some(CompletionTarget::Value { argument, prefix, offset }),
+        None if spec.iter().any(|argument| !argument.positional) => {
+            Some(CompletionTarget::Name { prefix, offset })
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> Vec<CommandArgument> {
+        vec![
+            CommandArgument::positional("action", CommandArgumentType::String, "").with_choices(&["test"]).required(),
+            CommandArgument::positional("text", CommandArgumentType::Text, "").required(),
+            CommandArgument::named("limit", CommandArgumentType::Integer, "").with_default(Value::from(5)),
+            CommandArgument::named("verbose", CommandArgumentType::Boolean, ""),
+        ]
+    }
+
+    #[test]
+    fn test_parse_and_complete() {
+        let spec = spec();
+
+        let parsed = parse(&spec, r#"--limit=3 --verbose test "the king"  crown"#).unwrap();
+        assert_eq!(parsed.get_str("action"), Some("test"));
+        assert_eq!(parsed.get_str("text"), Some("the king crown"));
+        assert_eq!(parsed.get_i64("limit"), Some(3));
+        assert!(parsed.get_bool("verbose"));
+
+        let parsed = parse(&spec, "test 'the king'").unwrap();
+        assert_eq!(parsed.get_str("text"), Some("the king"));
+        assert_eq!(parsed.get_i64("limit"), Some(5));
+
+        let parsed = parse(&spec, "test crown --limit 3").unwrap();
+        assert_eq!(parsed.get_str("text"), Some("crown"));
+        assert_eq!(parsed.get_i64("limit"), Some(3));
+        assert!(!parsed.get_bool("verbose"));
+        assert!(parse(&spec, r#"test crown --limit 3 "--verbose""#).is_err());
+
+        let parsed = parse(&spec, r#"test "the king" --other "--limit" --verbose"#).unwrap();
+        assert_eq!(parsed.get_str("text"), Some("the king --other --limit"));
+        assert_eq!(parsed.get_i64("limit"), Some(5));
+        assert!(parsed.get_bool("verbose"));
+
+        assert!(parse(&spec, "check dragon").unwrap_err().contains("test"));
+        assert!(parse(&spec, "test").unwrap_err().contains("text"));
+        assert!(parse(&spec, "--limit x test a").is_err());
+        assert!(parse(&spec, "--unknown test a").is_err());
+
+        assert_eq!(usage("/lore", &spec), "/lore <test> <text...> [--limit <limit>] [--verbose]");
+
+        let target = completion_target(&spec, "--verbose te").unwrap();
+        assert_eq!(
+            target,
+            CompletionTarget::Value { argument: &spec[0], prefix: "te".to_string(), offset: 10 }
+        );
+        assert_eq!(
+            completion_target(&spec, "--li").unwrap(),
+            CompletionTarget::Name { prefix: "--li".to_string(), offset: 0 }
+        );
+        assert_eq!(
+            completion_target(&spec, "--limit=").unwrap(),
+            CompletionTarget::Value { argument: &spec[2], prefix: String::new(), offset: 8 }
+        );
+        assert!(completion_target(&spec, "test some words ").is_none());
+    }
+}
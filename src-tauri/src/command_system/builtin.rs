@@ -5,8 +5,30 @@ use std::sync::Arc;
 use super::command::CommandExecutor;
 
 mod clear_command;
+mod continue_command;
+mod export_command;
+mod help_command;
+mod lore_command;
+mod model_command;
+mod regenerate_command;
+mod role_command;
+mod snapshot_command;
+mod summarize_command;
+mod tokens_command;
+mod undo_command;
 
 pub use clear_command::ClearCommand;
+pub use continue_command::ContinueCommand;
+pub use export_command::ExportCommand;
+pub use help_command::HelpCommand;
+pub use lore_command::LoreCommand;
+pub use model_command::ModelCommand;
+pub use regenerate_command::RegenerateCommand;
+pub use role_command::RoleCommand;
+pub use snapshot_command::SnapshotCommand;
+pub use summarize_command::SummarizeCommand;
+pub use tokens_command::TokensCommand;
+pub use undo_command::UndoCommand;
 
 pub type CommandBuilder = fn() -> Arc<dyn CommandExecutor>;
 
@@ -16,16 +38,117 @@ pub struct BuiltinCommandDescriptor {
     pub builder: CommandBuilder,
 }
 
+fn build_regenerate_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(RegenerateCommand::new())
+}
+
+fn build_continue_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(ContinueCommand::new())
+}
+
+fn build_summarize_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(SummarizeCommand::new())
+}
+
 fn build_clear_command() -> Arc<dyn CommandExecutor> {
     Arc::new(ClearCommand::new())
 }
 
+fn build_undo_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(UndoCommand::new())
+}
+
+fn build_snapshot_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(SnapshotCommand::new())
+}
+
+fn build_export_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(ExportCommand::new())
+}
+
+fn build_tokens_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(TokensCommand::new())
+}
+
+fn build_lore_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(LoreCommand::new())
+}
+
+fn build_role_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(RoleCommand::new())
+}
+
+fn build_model_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(ModelCommand::new())
+}
+
+fn build_help_command() -> Arc<dyn CommandExecutor> {
+    Arc::new(HelpCommand::new())
+}
+
 pub fn builtin_manifest() -> Vec<BuiltinCommandDescriptor> {
-    vec![BuiltinCommandDescriptor {
-        id: "clear",
-        description: "清空当前会话历史记录",
-        builder: build_clear_command,
-    }]
+    vec![
+        BuiltinCommandDescriptor {
+            id: "regenerate",
+            description: "重新生成最后一条 AI 回复",
+            builder: build_regenerate_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "continue",
+            description: "基于最后一条用户消息继续对话",
+            builder: build_continue_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "summarize",
+            description: "请 AI 总结当前对话",
+            builder: build_summarize_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "clear",
+            description: "清空当前会话历史记录",
+            builder: build_clear_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "undo",
+            description: "撤销最后一轮对话",
+            builder: build_undo_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "snapshot",
+            description: "保存当前角色卡快照",
+            builder: build_snapshot_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "export",
+            description: "导出当前角色卡",
+            builder: build_export_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "tokens",
+            description: "统计角色卡 Token 数量",
+            builder: build_tokens_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "lore",
+            description: "测试世界书条目激活",
+            builder: build_lore_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "role",
+            description: "切换 AI 角色",
+            builder: build_role_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "model",
+            description: "切换默认 API 配置",
+            builder: build_model_command,
+        },
+        BuiltinCommandDescriptor {
+            id: "help",
+            description: "列出可用命令",
+            builder: build_help_command,
+        },
+    ]
 }
 
 static DISABLED_COMMANDS: Lazy<HashSet<String>> = Lazy::new(|| {
@@ -22,6 +22,7 @@ impl ClearCommand {
                 priority: 1,
                 requires_confirmation: true,
                 confirmation_message: Some("确定要清空所有对话记录吗？此操作不可撤销。".to_string()),
+                arguments: Vec::new(),
             },
         }
     }
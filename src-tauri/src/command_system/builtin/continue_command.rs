@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::{CommandCategory, CommandMetadata, CommandResult};
+use crate::command_system::command::*;
+
+/// /continue 命令 - 基于最后一条用户消息继续生成回复
+pub struct ContinueCommand {
+    metadata: CommandMetadata,
+}
+
+impl ContinueCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "continue".to_string(),
+                name: "/continue".to_string(),
+                description: "基于最后一条用户消息继续对话".to_string(),
+                icon: Some("MdOutlinePlayArrow".to_string()),
+                category: Some(CommandCategory::Chat),
+                priority: 11,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: Vec::new(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for ContinueCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.last_message_role().as_deref() == Some("user")
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        SessionService::continue_chat(&context.app_handle).await?;
+        Ok(CommandResult::ok("已继续对话"))
+    }
+}
@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use crate::backend::domain::{CommandCategory, CommandMetadata, CommandResult};
+use crate::character_storage::{CharacterData, CharacterStorage};
+use crate::command_system::command::*;
+use crate::file_utils::FileUtils;
+
+/// /export 命令 - 将当前角色卡导出到应用数据目录下的 exports 目录
+pub struct ExportCommand {
+    metadata: CommandMetadata,
+}
+
+impl ExportCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "export".to_string(),
+                name: "/export".to_string(),
+                description: "导出当前角色卡（有卡面时为 PNG，否则为 JSON）".to_string(),
+                icon: Some("MdOutlineFileDownload".to_string()),
+                category: Some(CommandCategory::Export),
+                priority: 30,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: Vec::new(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for ExportCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session_uuid.is_some()
+    }
+
+    async fn preview(&self, context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        let app_handle = &context.app_handle;
+        let format = if CharacterStorage::has_card_image(app_handle, &character.uuid)? {
+            "png"
+        } else {
+            "json"
+        };
+        let export_dir = FileUtils::get_app_data_dir(app_handle)?.join("exports");
+        Ok(CommandResult::ok(format!(
+            "将导出为 {} 到 {}",
+            format.to_uppercase(),
+            export_dir.to_string_lossy()
+        ))
+        .with_data(serde_json::json!({ "format": format })))
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let uuid = context.session_uuid.ok_or("没有活跃的会话")?;
+        let app_handle = &context.app_handle;
+
+        let character = CharacterStorage::get_character_by_uuid(app_handle, &uuid)?
+            .ok_or_else(|| format!("角色 {} 不存在", uuid))?;
+
+        let export_dir = FileUtils::get_app_data_dir(app_handle)?.join("exports");
+        FileUtils::ensure_dir_exists(&export_dir)?;
+
+        let extension = if CharacterStorage::has_card_image(app_handle, &uuid)? {
+            "png"
+        } else {
+            "json"
+        };
+        let file_name: String = character
+            .card
+            .data
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let output_path = export_dir.join(format!(
+            "{}_{}.{}",
+            file_name,
+            chrono::Utc::now().timestamp(),
+            extension
+        ));
+        let output_path = output_path.to_string_lossy().to_string();
+
+        let format = CharacterStorage::export_character_card(app_handle, &uuid, &output_path)?;
+
+        Ok(CommandResult::ok(format!("已导出到 {}", output_path))
+            .with_data(serde_json::json!({ "path": output_path, "format": format })))
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandCompletion, CommandMetadata, CommandResult,
+};
+use crate::command_system::arguments;
+use crate::command_system::command::*;
+use crate::command_system::registry::COMMAND_REGISTRY;
+
+/// /help 命令 - 列出当前可用的命令，或显示指定命令的用法
+pub struct HelpCommand {
+    metadata: CommandMetadata,
+}
+
+impl HelpCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "help".to_string(),
+                name: "/help".to_string(),
+                description: "列出当前可用的命令".to_string(),
+                icon: Some("MdOutlineHelpOutline".to_string()),
+                category: Some(CommandCategory::Other),
+                priority: 100,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::positional(
+                    "command",
+                    CommandArgumentType::String,
+                    "要查看用法的命令",
+                )],
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for HelpCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn complete_argument(&self, context: &CommandContext, _argument: &str) -> Vec<CommandCompletion> {
+        COMMAND_REGISTRY
+            .get_available_commands(context)
+            .await
+            .into_iter()
+            .map(|command| CommandCompletion {
+                value: command.id,
+                description: Some(command.description),
+            })
+            .collect()
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let commands = COMMAND_REGISTRY.get_available_commands(&context).await;
+
+        if let Some(id) = context.arguments.get_str("command") {
+            let id = id.trim_start_matches('/');
+            let Some(command) = commands.iter().find(|command| command.id == id) else {
+                return Ok(CommandResult::failed(format!("命令 /{} 不存在或当前不可用", id)));
+            };
+
+            let mut message = format!(
+                "{}\n用法：{}",
+                command.description,
+                arguments::usage(&command.name, &command.arguments)
+            );
+            for argument in &command.arguments {
+                message.push_str(&format!("\n  {} - {}", argument.name, argument.description));
+            }
+            return Ok(CommandResult::ok(message)
+                .with_data(serde_json::to_value(command).map_err(|e| e.to_string())?));
+        }
+
+        let message = commands
+            .iter()
+            .map(|command| format!("{} - {}", command.name, command.description))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(CommandResult::ok(message)
+            .with_data(serde_json::to_value(&commands).map_err(|e| e.to_string())?))
+    }
+}
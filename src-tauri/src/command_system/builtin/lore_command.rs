@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandMetadata, CommandResult,
+};
+use crate::character_storage::{CharacterBook, CharacterData, WorldBookEntry};
+use crate::command_system::command::*;
+
+/// 被测试文本激活的世界书条目
+#[derive(Debug, Clone, Serialize)]
+pub struct LoreMatch {
+    /// 条目在世界书中的索引
+    pub index: usize,
+    /// 条目名称（无名称时为首个关键词）
+    pub label: String,
+    /// 命中的关键词（常驻条目为空）
+    pub matched_keys: Vec<String>,
+    pub constant: bool,
+}
+
+/// /lore 命令 - 测试一段文本会激活哪些世界书条目：/lore test <文本>
+pub struct LoreCommand {
+    metadata: CommandMetadata,
+}
+
+impl LoreCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "lore".to_string(),
+                name: "/lore".to_string(),
+                description: "测试文本会激活哪些世界书条目：/lore test <文本>".to_string(),
+                icon: Some("MdOutlineMenuBook".to_string()),
+                category: Some(CommandCategory::Other),
+                priority: 41,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![
+                    CommandArgument::positional("action", CommandArgumentType::String, "操作")
+                        .with_choices(&["test"])
+                        .required(),
+                    CommandArgument::positional("text", CommandArgumentType::Text, "要测试的文本").required(),
+                ],
+            },
+        }
+    }
+
+    /// 按关键词匹配世界书条目（已禁用的条目不参与；选择性条目还需命中次要关键词）
+    pub fn match_entries(book: &CharacterBook, text: &str) -> Vec<LoreMatch> {
+        book.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.enabled)
+            .filter_map(|(index, entry)| {
+                let constant = entry.constant.unwrap_or(false);
+                let matched_keys = Self::matched_keys(entry, &entry.keys, text);
+                if !constant && matched_keys.is_empty() {
+                    return None;
+                }
+
+                let secondary_keys = entry.secondary_keys.as_deref().unwrap_or_default();
+                if !constant
+                    && entry.selective.unwrap_or(false)
+                    && !secondary_keys.is_empty()
+                    && Self::matched_keys(entry, secondary_keys, text).is_empty()
+                {
+                    return None;
+                }
+
+                Some(LoreMatch {
+                    index,
+                    label: entry
+                        .name
+                        .clone()
+                        .filter(|name| !name.is_empty())
+                        .or_else(|| entry.keys.first().cloned())
+                        .unwrap_or_default(),
+                    matched_keys,
+                    constant,
+                })
+            })
+            .collect()
+    }
+
+    fn matched_keys(entry: &WorldBookEntry, keys: &[String], text: &str) -> Vec<String> {
+        let case_sensitive = entry.case_sensitive.unwrap_or(false);
+        let haystack = if case_sensitive { text.to_string() } else { text.to_lowercase() };
+
+        keys.iter()
+            .filter(|key| {
+                let key = key.trim();
+                !key.is_empty()
+                    && if case_sensitive {
+                        haystack.contains(key)
+                    } else {
+                        haystack.contains(&key.to_lowercase())
+                    }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for LoreCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session().is_some_and(|session| {
+            session
+                .character_data
+                .card
+                .data
+                .character_book
+                .is_some_and(|book| !book.entries.is_empty())
+        })
+    }
+
+    /// 只读命令，预览即测试结果
+    async fn preview(&self, context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        Self::report(context, character)
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let session = context.session().ok_or("没有活跃的会话")?;
+        Self::report(&context, &session.character_data)
+    }
+}
+
+impl LoreCommand {
+    fn report(context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        let text = context.arguments.get_str("text").unwrap_or_default();
+        let Some(book) = &character.card.data.character_book else {
+            return Ok(CommandResult::failed("当前角色没有世界书"));
+        };
+
+        let matches = Self::match_entries(book, text);
+        let message = if matches.is_empty() {
+            "没有条目被激活".to_string()
+        } else {
+            format!(
+                "激活 {} 个条目：{}",
+                matches.len(),
+                matches
+                    .iter()
+                    .map(|m| m.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join("、")
+            )
+        };
+        Ok(CommandResult::ok(message)
+            .with_data(serde_json::to_value(&matches).map_err(|e| e.to_string())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(keys: &[&str], extra: serde_json::Value) -> serde_json::Value {
+        let mut entry = serde_json::json!({
+            "keys": keys, "content": "c", "enabled": true, "insertion_order": 0
+        });
+        entry.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        entry
+    }
+
+    #[test]
+    fn test_match_entries() {
+        let book: CharacterBook = serde_json::from_value(serde_json::json!({
+            "entries": [
+                entry(&["Dragon"], serde_json::json!({})),
+                entry(&["castle"], serde_json::json!({ "case_sensitive": true })),
+                entry(&["king"], serde_json::json!({ "selective": true, "secondary_keys": ["crown"] })),
+                entry(&[], serde_json::json!({ "constant": true, "name": "Rules" })),
+                entry(&["dragon"], serde_json::json!({ "enabled": false })),
+            ]
+        }))
+        .unwrap();
+
+        let matches = LoreCommand::match_entries(&book, "The dragon flew over the Castle to the king.");
+        let indexes: Vec<usize> = matches.iter().map(|m| m.index).collect();
+        assert_eq!(indexes, vec![0, 3]);
+        assert_eq!(matches[0].matched_keys, vec!["Dragon"]);
+        assert_eq!(matches[1].label, "Rules");
+
+        let matches = LoreCommand::match_entries(&book, "the king lost his crown in the castle");
+        let indexes: Vec<usize> = matches.iter().map(|m| m.index).collect();
+        assert_eq!(indexes, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use crate::api_config::ApiConfigService;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandCompletion, CommandMetadata, CommandResult,
+};
+use crate::command_system::command::*;
+
+/// /model 命令 - 切换默认 API 配置（无参数时列出可用配置）
+pub struct ModelCommand {
+    metadata: CommandMetadata,
+}
+
+impl ModelCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "model".to_string(),
+                name: "/model".to_string(),
+                description: "切换默认 API 配置（不带参数时列出可用配置）".to_string(),
+                icon: Some("MdOutlineSmartToy".to_string()),
+                category: Some(CommandCategory::Settings),
+                priority: 51,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::positional(
+                    "profile",
+                    CommandArgumentType::String,
+                    "API 配置名称",
+                )],
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for ModelCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn complete_argument(&self, context: &CommandContext, _argument: &str) -> Vec<CommandCompletion> {
+        ApiConfigService::get_all_api_configs(&context.app_handle)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|config| config.enabled)
+            .map(|config| CommandCompletion {
+                value: config.profile,
+                description: Some(config.model),
+            })
+            .collect()
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let configs = ApiConfigService::get_all_api_configs(&context.app_handle)?;
+
+        let Some(profile) = context.arguments.get_str("profile") else {
+            let current = configs.iter().find(|config| config.default);
+            let profiles: Vec<serde_json::Value> = configs
+                .iter()
+                .filter(|config| config.enabled)
+                .map(|config| serde_json::json!({ "profile": config.profile, "model": config.model }))
+                .collect();
+
+            return Ok(CommandResult::ok(match current {
+                Some(config) => format!("当前 API 配置：{}（{}）", config.profile, config.model),
+                None => "尚未设置默认 API 配置".to_string(),
+            })
+            .with_data(serde_json::json!({
+                "current": current.map(|config| &config.profile),
+                "profiles": profiles,
+            })));
+        };
+
+        let Some(config) = configs.iter().find(|config| config.profile == profile) else {
+            return Ok(CommandResult::failed(format!("未找到配置 '{}'", profile)));
+        };
+        if !config.enabled {
+            return Ok(CommandResult::failed(format!("配置 '{}' 已禁用", profile)));
+        }
+
+        ApiConfigService::set_default_api_config(&context.app_handle, profile)?;
+        Ok(CommandResult::ok(format!("已切换到 {}（{}）", config.profile, config.model)))
+    }
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::{CommandCategory, CommandMetadata, CommandResult};
+use crate::command_system::command::*;
+
+/// /regenerate 命令 - 重新生成最后一条 AI 回复
+pub struct RegenerateCommand {
+    metadata: CommandMetadata,
+}
+
+impl RegenerateCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "regenerate".to_string(),
+                name: "/regenerate".to_string(),
+                description: "重新生成最后一条 AI 回复".to_string(),
+                icon: Some("MdOutlineRefresh".to_string()),
+                category: Some(CommandCategory::Chat),
+                priority: 10,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: Vec::new(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for RegenerateCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.last_message_role().as_deref() == Some("assistant")
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        SessionService::regenerate_last_message(&context.app_handle).await?;
+        Ok(CommandResult::ok("已重新生成回复"))
+    }
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use crate::ai_config::AIConfigService;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandCompletion, CommandMetadata, CommandResult,
+};
+use crate::command_system::command::*;
+
+/// /role 命令 - 切换当前会话使用的 AI 角色（无参数时列出可用角色）
+pub struct RoleCommand {
+    metadata: CommandMetadata,
+}
+
+impl RoleCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "role".to_string(),
+                name: "/role".to_string(),
+                description: "切换当前会话的 AI 角色（不带参数时列出可用角色）".to_string(),
+                icon: Some("MdOutlinePsychology".to_string()),
+                category: Some(CommandCategory::Settings),
+                priority: 50,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::positional(
+                    "name",
+                    CommandArgumentType::String,
+                    "AI 角色名称",
+                )],
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for RoleCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session_uuid.is_some()
+    }
+
+    async fn complete_argument(&self, context: &CommandContext, _argument: &str) -> Vec<CommandCompletion> {
+        let Ok(config) = AIConfigService::load_config(&context.app_handle) else {
+            return Vec::new();
+        };
+        let mut roles: Vec<CommandCompletion> = config
+            .roles
+            .into_iter()
+            .map(|(name, role)| CommandCompletion {
+                value: name,
+                description: Some(role.description).filter(|d| !d.is_empty()),
+            })
+            .collect();
+        roles.sort_by(|a, b| a.value.cmp(&b.value));
+        roles
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let uuid = context.session_uuid.clone().ok_or("没有活跃的会话")?;
+
+        let Some(role_name) = context.arguments.get_str("name").map(str::to_string) else {
+            let config = AIConfigService::load_config(&context.app_handle)?;
+            let current = AIConfigService::resolve_role(
+                &context.app_handle,
+                context.session().and_then(|session| session.settings.ai_role).as_deref(),
+            )?
+            .map(|(name, _)| name);
+            let mut roles: Vec<String> = config.roles.into_keys().collect();
+            roles.sort();
+
+            return Ok(CommandResult::ok(format!(
+                "当前 AI 角色：{}；可用角色：{}",
+                current.as_deref().unwrap_or("无"),
+                roles.join("、")
+            ))
+            .with_data(serde_json::json!({ "current": current, "roles": roles })));
+        };
+
+        match SessionService::set_ai_role(&context.app_handle, uuid, Some(role_name.clone())).await {
+            Ok(session_info) => Ok(CommandResult::ok(format!("已切换到 AI 角色 {}", role_name))
+                .with_data(serde_json::to_value(session_info).map_err(|e| e.to_string())?)),
+            Err(e) => Ok(CommandResult::failed(e)),
+        }
+    }
+}
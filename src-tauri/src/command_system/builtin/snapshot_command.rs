@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandMetadata, CommandResult,
+};
+use crate::card_diff;
+use crate::character_storage::{CharacterData, CharacterStorage, TavernCardV2};
+use crate::command_system::command::*;
+use crate::file_utils::FileUtils;
+
+/// /snapshot 命令 - 将当前角色卡保存为快照（参数作为快照备注）
+pub struct SnapshotCommand {
+    metadata: CommandMetadata,
+}
+
+impl SnapshotCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "snapshot".to_string(),
+                name: "/snapshot".to_string(),
+                description: "保存当前角色卡快照，可附加备注".to_string(),
+                icon: Some("MdOutlinePhotoCamera".to_string()),
+                category: Some(CommandCategory::History),
+                priority: 3,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::positional(
+                    "label",
+                    CommandArgumentType::Text,
+                    "快照备注",
+                )],
+            },
+        }
+    }
+}
+
+impl SnapshotCommand {
+    /// 最近保存的快照中的角色卡
+    fn latest_snapshot(snapshot_dir: &std::path::Path) -> Option<TavernCardV2> {
+        let latest = std::fs::read_dir(snapshot_dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .max_by_key(|(modified, _)| *modified)?;
+
+        let snapshot: serde_json::Value = FileUtils::read_json_file(&latest.1).ok()?;
+        serde_json::from_value(snapshot.get("card")?.clone()).ok()
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for SnapshotCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session_uuid.is_some()
+    }
+
+    async fn preview(&self, context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        let snapshot_dir = FileUtils::get_app_data_dir(&context.app_handle)?
+            .join("character-cards")
+            .join(&character.uuid)
+            .join("snapshots");
+        let label = context
+            .arguments
+            .get_str("label")
+            .map(|label| format!("（备注：{}）", label))
+            .unwrap_or_default();
+
+        // 与最近一次快照比较，列出本次快照将记录的字段变化
+        let Some(previous) = Self::latest_snapshot(&snapshot_dir) else {
+            return Ok(CommandResult::ok(format!("将保存首个快照{}", label)));
+        };
+        let changes = card_diff::diff_cards(&previous, &character.card);
+        Ok(CommandResult::ok(format!(
+            "将保存快照{}，与上次快照相比 {} 处字段变化",
+            label,
+            changes.len()
+        ))
+        .with_data(serde_json::json!({ "changes": changes })))
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let uuid = context.session_uuid.as_deref().ok_or("没有活跃的会话")?;
+
+        let character = CharacterStorage::get_character_by_uuid(&context.app_handle, uuid)?
+            .ok_or_else(|| format!("角色 {} 不存在", uuid))?;
+
+        let snapshot_dir = FileUtils::get_app_data_dir(&context.app_handle)?
+            .join("character-cards")
+            .join(uuid)
+            .join("snapshots");
+        FileUtils::ensure_dir_exists(&snapshot_dir)?;
+
+        // 文件名带毫秒与随机后缀，同一秒内的多次快照不会互相覆盖
+        let now = chrono::Utc::now();
+        let timestamp = now.timestamp();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let snapshot_file = snapshot_dir.join(format!("{}-{}.json", now.timestamp_millis(), &suffix[..8]));
+        let snapshot = serde_json::json!({
+            "timestamp": timestamp,
+            "label": context.arguments.get_str("label"),
+            "card": character.card,
+        });
+        FileUtils::write_json_file(&snapshot_file, &snapshot)?;
+
+        let path = snapshot_file.to_string_lossy().to_string();
+        Ok(CommandResult::ok(format!("已保存快照 {}", path))
+            .with_data(serde_json::json!({ "path": path, "timestamp": timestamp })))
+    }
+}
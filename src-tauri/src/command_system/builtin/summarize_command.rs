@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandMetadata, CommandResult,
+};
+use crate::character_storage::CharacterData;
+use crate::command_system::command::*;
+
+/// 发送给 AI 的总结请求
+const SUMMARIZE_PROMPT: &str =
+    "请总结到目前为止的对话：列出已经确定的角色设定、对角色卡做出的修改，以及尚未解决的问题。";
+
+/// /summarize 命令 - 请 AI 总结当前对话（参数作为额外的关注点）
+pub struct SummarizeCommand {
+    metadata: CommandMetadata,
+}
+
+impl SummarizeCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "summarize".to_string(),
+                name: "/summarize".to_string(),
+                description: "请 AI 总结当前对话，可附加关注点".to_string(),
+                icon: Some("MdOutlineSummarize".to_string()),
+                category: Some(CommandCategory::Chat),
+                priority: 12,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::positional(
+                    "focus",
+                    CommandArgumentType::Text,
+                    "额外的关注点",
+                )],
+            },
+        }
+    }
+}
+
+impl SummarizeCommand {
+    fn prompt(context: &CommandContext) -> String {
+        match context.arguments.get_str("focus") {
+            Some(focus) => format!("{}\n重点关注：{}", SUMMARIZE_PROMPT, focus),
+            None => SUMMARIZE_PROMPT.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for SummarizeCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context
+            .session()
+            .is_some_and(|session| !session.chat_history.is_empty())
+    }
+
+    async fn preview(&self, context: &CommandContext, _character: &CharacterData) -> Result<CommandResult, String> {
+        let prompt = Self::prompt(context);
+        Ok(CommandResult::ok(format!("将请 AI 总结对话，总结追加到聊天记录：{}", prompt))
+            .with_data(serde_json::json!({ "prompt": prompt })))
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let prompt = Self::prompt(&context);
+
+        let uuid = context.session_uuid.clone().ok_or("没有活跃的会话")?;
+        SessionService::send_chat_message_to(&context.app_handle, uuid, prompt).await?;
+        Ok(CommandResult::ok("已生成对话总结"))
+    }
+}
@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandMetadata, CommandResult,
+};
+use crate::character_storage::{CharacterData, CharacterStorage};
+use crate::command_system::command::*;
+use crate::token_breakdown::CardTokenBreakdown;
+use crate::token_counter::TokenizerRegistry;
+
+/// /tokens 命令 - 统计当前角色卡的 Token 分布
+pub struct TokensCommand {
+    metadata: CommandMetadata,
+}
+
+impl TokensCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "tokens".to_string(),
+                name: "/tokens".to_string(),
+                description: "统计当前角色卡与上次上下文的 Token 数量".to_string(),
+                icon: Some("MdOutlineDataUsage".to_string()),
+                category: Some(CommandCategory::Other),
+                priority: 40,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::named(
+                    "detail",
+                    CommandArgumentType::Boolean,
+                    "列出各字段的 Token 数量",
+                )],
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for TokensCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session_uuid.is_some()
+    }
+
+    /// 只读命令，预览即统计结果
+    async fn preview(&self, context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        Self::report(context, character)
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let uuid = context.session_uuid.as_deref().ok_or("没有活跃的会话")?;
+
+        let character = CharacterStorage::get_character_by_uuid(&context.app_handle, uuid)?
+            .ok_or_else(|| format!("角色 {} 不存在", uuid))?;
+        Self::report(&context, &character)
+    }
+}
+
+impl TokensCommand {
+    fn report(context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        let counter = TokenizerRegistry::for_active_model(&context.app_handle)?;
+        let breakdown = CardTokenBreakdown::build(&counter, character);
+        let context_tokens = context
+            .session()
+            .map(|session| session.last_context_tokens)
+            .unwrap_or_default();
+
+        let mut message = format!(
+            "角色卡共 {} tokens（常驻 {}，条件 {}），上次上下文 {} tokens",
+            breakdown.total_tokens, breakdown.permanent_tokens, breakdown.conditional_tokens, context_tokens
+        );
+        if context.arguments.get_bool("detail") {
+            for field in breakdown.fields.iter().filter(|field| field.tokens > 0) {
+                message.push_str(&format!(
+                    "\n  {}{}: {}",
+                    field.path,
+                    field.label.as_deref().map(|label| format!("（{}）", label)).unwrap_or_default(),
+                    field.tokens
+                ));
+            }
+        }
+        let data = serde_json::json!({
+            "breakdown": breakdown,
+            "last_context_tokens": context_tokens,
+        });
+        Ok(CommandResult::ok(message).with_data(data))
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::{
+    CommandArgument, CommandArgumentType, CommandCategory, CommandMetadata, CommandResult,
+};
+use crate::command_system::command::*;
+
+/// /undo 命令 - 撤销最后几轮对话
+pub struct UndoCommand {
+    metadata: CommandMetadata,
+}
+
+impl UndoCommand {
+    pub fn new() -> Self {
+        Self {
+            metadata: CommandMetadata {
+                id: "undo".to_string(),
+                name: "/undo".to_string(),
+                description: "删除最后一轮对话（用户消息及其后的 AI 回复）".to_string(),
+                icon: Some("MdOutlineUndo".to_string()),
+                category: Some(CommandCategory::History),
+                priority: 2,
+                requires_confirmation: false,
+                confirmation_message: None,
+                arguments: vec![CommandArgument::named("count", CommandArgumentType::Integer, "撤销的轮数")
+                    .with_default(serde_json::json!(1))],
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for UndoCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session().is_some_and(|session| {
+            session.chat_history.iter().any(|message| message.role == "user")
+        })
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let count = context.arguments.get_i64("count").unwrap_or(1);
+        if count < 1 {
+            return Ok(CommandResult::failed("撤销的轮数至少为 1"));
+        }
+
+        let uuid = context.session_uuid.as_deref().ok_or("没有活跃的会话")?;
+        let removed = SessionService::undo_last_exchanges(&context.app_handle, uuid, count as usize).await?;
+        Ok(CommandResult::ok(format!("已撤销 {} 轮对话（{} 条消息）", count, removed)))
+    }
+}
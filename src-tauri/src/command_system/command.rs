@@ -1,15 +1,37 @@
 use async_trait::async_trait;
-use crate::backend::domain::{CommandMetadata, CommandResult};
+use crate::backend::domain::{CommandCompletion, CommandMetadata, CommandResult};
+use crate::character_session::{CharacterSession, SESSION_MANAGER};
+use crate::character_storage::CharacterData;
+use super::arguments::CommandArguments;
 
 /// 命令执行上下文
 #[derive(Debug, Clone)]
 pub struct CommandContext {
     /// 当前会话UUID（可选）
     pub session_uuid: Option<String>,
+    /// 命令名之后的参数文本（已去除首尾空白）
+    pub raw_arguments: String,
+    /// 按命令参数声明解析后的参数（由注册表在执行前填充）
+    pub arguments: CommandArguments,
     /// Tauri应用句柄
     pub app_handle: tauri::AppHandle,
 }
 
+impl CommandContext {
+    /// 当前会话（未加载时为 None）
+    pub fn session(&self) -> Option<CharacterSession> {
+        self.session_uuid
+            .as_ref()
+            .and_then(|uuid| SESSION_MANAGER.get_session(uuid))
+    }
+
+    /// 当前会话最后一条消息的角色
+    pub fn last_message_role(&self) -> Option<String> {
+        self.session()
+            .and_then(|session| session.chat_history.last().map(|message| message.role.clone()))
+    }
+}
+
 /// 命令执行器特征
 #[async_trait]
 pub trait CommandExecutor: Send + Sync {
@@ -22,6 +44,27 @@ pub trait CommandExecutor: Send + Sync {
         true
     }
 
+    /// 是否可在批量执行中对多个角色运行（依赖当前活跃角色的命令不可批量执行）
+    /// 默认实现：不可批量执行
+    fn supports_batch(&self) -> bool {
+        false
+    }
+
+    /// 参数补全候选值（用于没有固定可选值的参数，如角色名）
+    /// 默认实现：没有候选值
+    async fn complete_argument(&self, _context: &CommandContext, _argument: &str) -> Vec<CommandCompletion> {
+        Vec::new()
+    }
+
+    /// 批量预览：说明对该角色将要执行的操作，不产生任何修改
+    /// 涉及角色卡的命令应在副本上比较（card_diff::diff_cards）并在 data 中列出字段变化
+    /// 默认实现：回显命令行
+    async fn preview(&self, context: &CommandContext, _character: &CharacterData) -> Result<CommandResult, String> {
+        Ok(CommandResult::ok(
+            format!("{} {}", self.metadata().name, context.raw_arguments).trim_end(),
+        ))
+    }
+
     /// 执行命令
     async fn execute(&self, context: CommandContext) -> Result<CommandResult, String>;
 }
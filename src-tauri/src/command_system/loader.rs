@@ -7,13 +7,13 @@ pub async fn register_builtin_commands() -> usize {
     for descriptor in builtin_manifest() {
         if is_enabled(descriptor.id) {
             COMMAND_REGISTRY.register((descriptor.builder)()).await;
-            println!(
+            eprintln!(
                 "➡️ 已注册命令 {} ({})",
                 descriptor.id, descriptor.description
             );
             registered += 1;
         } else {
-            println!(
+            eprintln!(
                 "⚠️ 跳过命令 {}({})，已通过环境变量禁用",
                 descriptor.id,
                 descriptor.description
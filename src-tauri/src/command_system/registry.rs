@@ -1,4 +1,7 @@
-use crate::backend::domain::{CommandMetadata, CommandResult};
+use crate::backend::domain::{
+    CommandArgumentType, CommandCompletion, CommandCompletions, CommandMetadata, CommandResult,
+};
+use super::arguments::{self, CompletionTarget};
 use super::command::{CommandContext, CommandExecutor};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -24,6 +27,33 @@ impl CommandRegistry {
         commands.insert(id, executor);
     }
 
+    /// 注销命令
+    pub async fn unregister(&self, command_id: &str) -> bool {
+        self.commands.write().await.remove(command_id).is_some()
+    }
+
+    /// 获取命令执行器
+    pub async fn get(&self, command_id: &str) -> Option<Arc<dyn CommandExecutor>> {
+        self.commands.read().await.get(command_id).cloned()
+    }
+
+    /// 可批量执行的命令元数据
+    pub async fn get_batch_commands(&self) -> Vec<CommandMetadata> {
+        let commands = self.commands.read().await;
+        let mut metadata_list: Vec<CommandMetadata> = commands
+            .values()
+            .filter(|executor| executor.supports_batch())
+            .map(|executor| executor.metadata().clone())
+            .collect();
+        metadata_list.sort_by_key(|m| m.priority);
+        metadata_list
+    }
+
+    /// 是否已注册指定命令
+    pub async fn contains(&self, command_id: &str) -> bool {
+        self.commands.read().await.contains_key(command_id)
+    }
+
     /// 获取所有可用命令元数据
     pub async fn get_available_commands(&self, context: &CommandContext) -> Vec<CommandMetadata> {
         let commands = self.commands.read().await;
@@ -67,15 +97,115 @@ impl CommandRegistry {
             .collect()
     }
 
-    /// 执行命令
+    /// 补全命令行：输入命令名时补全可用命令，输入参数时补全参数名或参数值
+    pub async fn complete(&self, input: &str, context: &CommandContext) -> CommandCompletions {
+        let trimmed = input.trim_start();
+        let leading = input.len() - trimmed.len();
+        let Some(command_line) = trimmed.strip_prefix('/') else {
+            return CommandCompletions::default();
+        };
+        let char_offset = |byte_offset: usize| input[..byte_offset].chars().count();
+
+        let Some((command_id, raw_arguments)) = command_line.split_once(char::is_whitespace) else {
+            let prefix = command_line.to_lowercase();
+            let items = self
+                .get_available_commands(context)
+                .await
+                .into_iter()
+                .filter(|command| command.id.to_lowercase().starts_with(&prefix))
+                .map(|command| CommandCompletion {
+                    value: command.name,
+                    description: Some(command.description),
+                })
+                .collect();
+            return CommandCompletions {
+                offset: char_offset(leading),
+                argument: None,
+                items,
+            };
+        };
+
+        let executor = self.commands.read().await.get(command_id).cloned();
+        let Some(executor) = executor else {
+            return CommandCompletions::default();
+        };
+        if !executor.is_available(context).await {
+            return CommandCompletions::default();
+        }
+
+        let spec = &executor.metadata().arguments;
+        let arguments_start = input.len() - raw_arguments.len();
+        match arguments::completion_target(spec, raw_arguments) {
+            Some(CompletionTarget::Name { prefix, offset }) => CommandCompletions {
+                offset: char_offset(arguments_start + offset),
+                argument: None,
+                items: spec
+                    .iter()
+                    .filter(|argument| !argument.positional)
+                    .map(|argument| CommandCompletion {
+                        value: format!("--{}", argument.name),
+                        description: Some(argument.description.clone()).filter(|d| !d.is_empty()),
+                    })
+                    .filter(|item| item.value.starts_with(&prefix))
+                    .collect(),
+            },
+            Some(CompletionTarget::Value {
+                argument,
+                prefix,
+                offset,
+            }) => {
+                let candidates = if !argument.choices.is_empty() {
+                    argument
+                        .choices
+                        .iter()
+                        .map(|choice| CommandCompletion {
+                            value: choice.clone(),
+                            description: None,
+                        })
+                        .collect()
+                } else if argument.arg_type == CommandArgumentType::Boolean {
+                    ["true", "false"]
+                        .iter()
+                        .map(|value| CommandCompletion {
+                            value: value.to_string(),
+                            description: None,
+                        })
+                        .collect()
+                } else {
+                    executor.complete_argument(context, &argument.name).await
+                };
+
+                let prefix = prefix.to_lowercase();
+                CommandCompletions {
+                    offset: char_offset(arguments_start + offset),
+                    argument: Some(argument.name.clone()),
+                    items: candidates
+                        .into_iter()
+                        .filter(|item| item.value.to_lowercase().starts_with(&prefix))
+                        .map(|mut item| {
+                            // 含空白的值加上引号，保证补全后仍是一个参数
+                            if item.value.contains(char::is_whitespace) {
+                                item.value = format!("\"{}\"", item.value);
+                            }
+                            item
+                        })
+                        .collect(),
+                }
+            }
+            None => CommandCompletions::default(),
+        }
+    }
+
+    /// 执行命令（先按命令的参数声明解析参数）
     pub async fn execute_command(
         &self,
         command_id: &str,
-        context: CommandContext,
+        mut context: CommandContext,
     ) -> Result<CommandResult, String> {
-        let commands = self.commands.read().await;
+        // 执行前释放读锁，避免耗时命令（如重新生成）阻塞注册
+        let executor = self.commands.read().await.get(command_id).cloned();
 
-        if let Some(executor) = commands.get(command_id) {
+        if let Some(executor) = executor {
             if !executor.is_available(&context).await {
                 return Ok(CommandResult {
                     success: false,
@@ -85,6 +215,18 @@ impl CommandRegistry {
                 });
             }
 
+            let metadata = executor.metadata();
+            context.arguments = match arguments::parse(&metadata.arguments, &context.raw_arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => {
+                    return Ok(CommandResult::failed(format!(
+                        "{}\n用法：{}",
+                        e,
+                        arguments::usage(&metadata.name, &metadata.arguments)
+                    )))
+                }
+            };
+
             executor.execute(context).await
         } else {
             Err(format!("命令 {} 不存在", command_id))
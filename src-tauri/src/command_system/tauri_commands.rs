@@ -1,13 +1,33 @@
+use crate::backend::application::batch_service::BatchCommandService;
 use crate::backend::application::command_service::CommandService;
-use crate::backend::domain::{CommandMetadata, CommandResult};
+use crate::backend::domain::{
+    BatchCommandReport, BatchCommandRequest, CommandCompletions, CommandMetadata, CommandResult,
+};
+use crate::command_system::registry::COMMAND_REGISTRY;
 use crate::command_system::loader;
+use crate::user_commands::{UserCommandService, UserCommandStatus, UserCommandsConfig};
 
 /// 初始化命令系统
-/// 在应用启动时调用，注册所有内置命令
-pub async fn initialize_command_system() {
+/// 在应用启动时调用，注册所有内置命令与用户命令
+pub async fn initialize_command_system(app_handle: tauri::AppHandle) {
     CommandService::initialize().await;
     let count = loader::register_builtin_commands().await;
-    println!("✅ 命令系统初始化完成，已注册 {} 个内置命令", count);
+    eprintln!("✅ 命令系统初始化完成，已注册 {} 个内置命令", count);
+
+    match UserCommandService::reload(&app_handle).await {
+        Ok(statuses) => {
+            for status in statuses.iter().filter(|status| status.error.is_some()) {
+                eprintln!(
+                    "加载用户命令 {} 失败: {}",
+                    status.id,
+                    status.error.as_deref().unwrap_or_default()
+                );
+            }
+            let loaded = statuses.iter().filter(|status| status.loaded).count();
+            eprintln!("✅ 已注册 {} 个用户命令", loaded);
+        }
+        Err(e) => eprintln!("加载用户命令失败: {}", e),
+    }
 }
 
 /// 获取可用命令列表
@@ -25,12 +45,69 @@ pub async fn search_commands(
     CommandService::search_commands(&app_handle, query).await
 }
 
+/// 补全命令行（命令名、参数名或参数值）
+#[tauri::command]
+pub async fn complete_command(
+    app_handle: tauri::AppHandle,
+    input: String,
+) -> Result<CommandCompletions, String> {
+    CommandService::complete_command(&app_handle, input).await
+}
+
 /// 执行命令
 #[tauri::command]
 pub async fn execute_command(
     app_handle: tauri::AppHandle,
     command_id: String,
-    _user_input: Option<String>,
+    user_input: Option<String>,
 ) -> Result<CommandResult, String> {
-    CommandService::execute_command(&app_handle, command_id, _user_input).await
+    CommandService::execute_command(&app_handle, command_id, user_input).await
+}
+
+/// 获取用户命令配置
+#[tauri::command]
+pub async fn get_user_commands_config(app_handle: tauri::AppHandle) -> Result<UserCommandsConfig, String> {
+    UserCommandService::get_config(&app_handle)
+}
+
+/// 保存用户命令配置并重新加载
+#[tauri::command]
+pub async fn save_user_commands_config(
+    app_handle: tauri::AppHandle,
+    config: UserCommandsConfig,
+) -> Result<Vec<UserCommandStatus>, String> {
+    UserCommandService::save_config(&app_handle, &config).await
+}
+
+/// 重新加载用户命令
+#[tauri::command]
+pub async fn reload_user_commands(app_handle: tauri::AppHandle) -> Result<Vec<UserCommandStatus>, String> {
+    UserCommandService::reload(&app_handle).await
+}
+
+/// 获取用户命令的加载状态
+#[tauri::command]
+pub async fn get_user_command_status() -> Result<Vec<UserCommandStatus>, String> {
+    Ok(UserCommandService::list_status().await)
+}
+
+/// 获取可批量执行的命令
+#[tauri::command]
+pub async fn get_batch_commands() -> Result<Vec<CommandMetadata>, String> {
+    Ok(COMMAND_REGISTRY.get_batch_commands().await)
+}
+
+/// 对多个角色批量执行命令或提示词
+#[tauri::command]
+pub async fn run_batch_command(
+    app_handle: tauri::AppHandle,
+    request: BatchCommandRequest,
+) -> Result<BatchCommandReport, String> {
+    BatchCommandService::run(&app_handle, request).await
+}
+
+/// 取消正在运行的批量任务
+#[tauri::command]
+pub async fn cancel_batch_command(batch_id: String) -> Result<bool, String> {
+    Ok(BatchCommandService::cancel(&batch_id))
 }
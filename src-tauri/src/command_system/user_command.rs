@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use crate::backend::application::session_service::SessionService;
+use crate::backend::domain::SessionOverrides;
+use crate::backend::domain::{CommandArgument, CommandCategory, CommandMetadata, CommandResult};
+use crate::character_storage::{CharacterData, TavernCardV2Data};
+use crate::tool_profiles::ToolSelection;
+use crate::tools::user_tool::render_string;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::command::{CommandContext, CommandExecutor};
+
+/// 用户命令的默认优先级（排在内置命令之后）
+const DEFAULT_PRIORITY: i32 = 200;
+
+/// 用户定义的命令（展开为提示词后发送给 AI）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCommandDefinition {
+    /// 命令标识（输入 `/标识` 调用）
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub category: Option<CommandCategory>,
+    #[serde(default)]
+    pub priority: Option<i32>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub requires_confirmation: bool,
+    /// 提示词模板：`{{char}}` 为角色名，`{{user}}` 为用户名，
+    /// `{{card.字段}}` 为角色卡字段，`{{参数名}}` 为命令参数
+    pub prompt: String,
+    #[serde(default)]
+    pub arguments: Vec<CommandArgument>,
+    /// 本次生成使用的 AI 角色
+    #[serde(default)]
+    pub ai_role: Option<String>,
+    /// 本次生成使用的工具档案
+    #[serde(default)]
+    pub tool_profile: Option<String>,
+    /// 本次生成的工具启用规则（替换会话级规则）
+    #[serde(default)]
+    pub tools: Option<ToolSelection>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 由用户命令配置创建的命令执行器
+pub struct UserCommand {
+    definition: UserCommandDefinition,
+    /// `{{user}}` 的替换值
+    user_name: String,
+    metadata: CommandMetadata,
+}
+
+impl UserCommand {
+    pub fn new(definition: UserCommandDefinition, user_name: String) -> Self {
+        let metadata = CommandMetadata {
+            id: definition.id.clone(),
+            name: format!("/{}", definition.id),
+            description: definition.description.clone(),
+            icon: Some(definition.icon.clone().unwrap_or_else(|| "MdOutlineBolt".to_string())),
+            category: Some(definition.category.clone().unwrap_or(CommandCategory::Chat)),
+            priority: definition.priority.unwrap_or(DEFAULT_PRIORITY),
+            requires_confirmation: definition.requires_confirmation,
+            confirmation_message: None,
+            arguments: definition.arguments.clone(),
+        };
+        Self {
+            definition,
+            user_name,
+            metadata,
+        }
+    }
+
+    /// 由单条提示词创建临时命令（用于批量执行提示词）
+    pub fn from_prompt(prompt: String, user_name: String) -> Self {
+        let definition = UserCommandDefinition {
+            id: "prompt".to_string(),
+            description: "发送提示词".to_string(),
+            icon: None,
+            category: None,
+            priority: None,
+            enabled: true,
+            requires_confirmation: false,
+            prompt,
+            arguments: Vec::new(),
+            ai_role: None,
+            tool_profile: None,
+            tools: None,
+        };
+        Self::new(definition, user_name)
+    }
+
+    /// 展开提示词模板
+    pub(crate) fn expand(&self, card: &TavernCardV2Data, arguments: &HashMap<String, Value>) -> Result<String, String> {
+        let card_value = serde_json::to_value(card).map_err(|e| e.to_string())?;
+        let mut values = arguments.clone();
+        values.insert("char".to_string(), Value::String(card.name.clone()));
+        values.insert("user".to_string(), Value::String(self.user_name.clone()));
+
+        Ok(match render_string(&self.definition.prompt, &values, &card_value) {
+            Value::String(prompt) => prompt,
+            Value::Null => String::new(),
+            value => value.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for UserCommand {
+    fn metadata(&self) -> &CommandMetadata {
+        &self.metadata
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn is_available(&self, context: &CommandContext) -> bool {
+        context.session().is_some()
+    }
+
+    async fn preview(&self, context: &CommandContext, character: &CharacterData) -> Result<CommandResult, String> {
+        let prompt = self.expand(&character.card.data, context.arguments.values())?;
+        if prompt.trim().is_empty() {
+            return Ok(CommandResult::failed("展开后的提示词为空"));
+        }
+        Ok(CommandResult::ok(prompt.clone()).with_data(serde_json::json!({ "prompt": prompt })))
+    }
+
+    async fn execute(&self, context: CommandContext) -> Result<CommandResult, String> {
+        let session = context.session().ok_or("没有活跃的会话")?;
+        let uuid = session.uuid.clone();
+
+        let prompt = self.expand(&session.character_data.card.data, context.arguments.values())?;
+        if prompt.trim().is_empty() {
+            return Ok(CommandResult::failed("展开后的提示词为空"));
+        }
+
+        let overrides = SessionOverrides {
+            ai_role: self.definition.ai_role.clone(),
+            tool_profile: self.definition.tool_profile.clone(),
+            tools: self.definition.tools.clone(),
+        };
+        SessionService::send_chat_message_with(&context.app_handle, uuid, prompt.clone(), &overrides).await?;
+
+        Ok(CommandResult::ok(format!("已执行 {}", self.metadata.name))
+            .with_data(serde_json::json!({ "prompt": prompt })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_placeholders() {
+        let definition: UserCommandDefinition = serde_json::from_value(serde_json::json!({
+            "id": "villainize",
+            "prompt": "Rewrite {{char}} as a villain who hates {{user}}. Tone: {{tone}}. Old: {{card.description}}{{missing}}"
+        }))
+        .unwrap();
+        let command = UserCommand::new(definition, "Bob".to_string());
+        assert_eq!(command.metadata().priority, DEFAULT_PRIORITY);
+
+        let card: TavernCardV2Data = serde_json::from_value(serde_json::json!({
+            "name": "Alice", "description": "kind", "personality": "", "scenario": "",
+            "first_mes": "", "mes_example": "", "creator_notes": "", "system_prompt": "",
+            "post_history_instructions": "", "alternate_greetings": [], "tags": [],
+            "creator": "", "character_version": ""
+        }))
+        .unwrap();
+        let arguments = HashMap::from([
+            ("tone".to_string(), Value::from("grim")),
+            ("char".to_string(), Value::from("ignored")),
+        ]);
+
+        assert_eq!(
+            command.expand(&card, &arguments).unwrap(),
+            "Rewrite Alice as a villain who hates Bob. Tone: grim. Old: kind"
+        );
+    }
+}
@@ -1,8 +1,10 @@
+use crate::ai_config::AIRole;
 use crate::chat_history::ChatMessage;
 use crate::character_storage::{CharacterData, CharacterBook};
 use crate::backend::domain::{ContextBuilderOptions, TokenBudget};
-use crate::token_counter::get_token_counter;
+use crate::token_counter::{get_token_counter, TokenCounter};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// OpenAI 消息结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,15 +63,38 @@ pub struct BuiltContextResult {
 pub struct ContextBuilder {
     token_budget: TokenBudget,
     options: ContextBuilderOptions,
+    /// 当前模型的分词器（未设置时使用 cl100k_base）
+    token_counter: Option<Arc<TokenCounter>>,
 }
 
 impl ContextBuilder {
     /// 创建新的上下文构建器
     pub fn new(options: ContextBuilderOptions) -> Self {
-        let token_budget = TokenBudget::default();
+        let token_budget = TokenBudget::with_total(options.token_limit);
         Self {
             token_budget,
             options,
+            token_counter: None,
+        }
+    }
+
+    /// 按模型上下文预算调整 Token 限制
+    pub fn with_token_limit(mut self, token_limit: usize) -> Self {
+        self.options.token_limit = token_limit;
+        self.token_budget = TokenBudget::with_total(token_limit);
+        self
+    }
+
+    /// 使用指定模型的分词器计数
+    pub fn with_token_counter(mut self, token_counter: Arc<TokenCounter>) -> Self {
+        self.token_counter = Some(token_counter);
+        self
+    }
+
+    fn counter(&self) -> &TokenCounter {
+        match &self.token_counter {
+            Some(counter) => counter,
+            None => get_token_counter(),
         }
     }
 
@@ -142,22 +167,24 @@ impl ContextBuilder {
         let task = self.process_placeholders(&self.options.ai_task, character_data);
         content.push_str(&format!("task: {}\n", task));
 
-        // 添加工具定义
-        content.push_str("tools:\n");
-        content.push_str("  - name: \"edit_character\"\n");
-        content.push_str("    description: \"编辑角色字段\"\n");
-        content.push_str("    parameters: {\"type\": \"object\", \"properties\": {\"field\": {\"type\": \"string\"}, \"value\": {\"type\": \"string\"}}}\n");
+        // 工具定义与调用指令（AI 角色禁用工具时省略）
+        if self.options.tools_enabled {
+            content.push_str("tools:\n");
+            content.push_str("  - name: \"edit_character\"\n");
+            content.push_str("    description: \"编辑角色字段\"\n");
+            content.push_str("    parameters: {\"type\": \"object\", \"properties\": {\"field\": {\"type\": \"string\"}, \"value\": {\"type\": \"string\"}}}\n");
 
-        content.push_str("  - name: \"create_worldbook_entry\"\n");
-        content.push_str("    description: \"创建世界书条目\"\n");
-        content.push_str("    parameters: {\"type\": \"object\", \"properties\": {\"name\": {\"type\": \"string\"}, \"content\": {\"type\": \"string\"}, \"keys\": {\"type\": \"array\", \"items\": {\"type\": \"string\"}}}}\n");
+            content.push_str("  - name: \"create_worldbook_entry\"\n");
+            content.push_str("    description: \"创建世界书条目\"\n");
+            content.push_str("    parameters: {\"type\": \"object\", \"properties\": {\"name\": {\"type\": \"string\"}, \"content\": {\"type\": \"string\"}, \"keys\": {\"type\": \"array\", \"items\": {\"type\": \"string\"}}}}\n");
 
-        // 添加指令
-        content.push_str("instructions: |\n");
-        content.push_str("  基于用户需求分析现有角色设定，提供建议并调用相应工具。\n");
-        content.push_str("  始终保持角色设定的一致性和逻辑性，遵循用户的具体要求。\n");
-        content.push_str("  如果需要修改角色信息，请使用 edit_character 工具。\n");
-        content.push_str("  如果需要添加世界书条目，请使用 create_worldbook_entry 工具。\n");
+            // 添加指令
+            content.push_str("instructions: |\n");
+            content.push_str("  基于用户需求分析现有角色设定，提供建议并调用相应工具。\n");
+            content.push_str("  始终保持角色设定的一致性和逻辑性，遵循用户的具体要求。\n");
+            content.push_str("  如果需要修改角色信息，请使用 edit_character 工具。\n");
+            content.push_str("  如果需要添加世界书条目，请使用 create_worldbook_entry 工具。\n");
+        }
 
         Ok(vec![OpenAIMessage {
             role: "system".to_string(),
@@ -435,15 +462,13 @@ impl ContextBuilder {
 
     /// 计算 Token 数量
     fn count_tokens(&self, text: &str) -> usize {
-        let counter = get_token_counter();
-        counter.count_tokens(text).token_count
+        self.counter().count(text)
     }
 
     /// 计算消息的 Token 数量
     fn count_message_tokens(&self, message: &OpenAIMessage) -> usize {
-        let counter = get_token_counter();
         let content = serde_json::to_string(message).unwrap_or_default();
-        counter.count_tokens(&content).token_count
+        self.counter().count(&content)
     }
 
     /// 计算多个消息的 Token 数量
@@ -459,6 +484,20 @@ pub fn create_default_context_builder() -> ContextBuilder {
     ContextBuilder::new(ContextBuilderOptions::default())
 }
 
+/// 根据 AI 角色创建上下文构建器（角色名称与系统提示词分别填充 {{ROLE}} / {{TASK}}）
+pub fn create_context_builder_for_role(role: &AIRole) -> ContextBuilder {
+    let mut options = ContextBuilderOptions::default();
+    options
+        .placeholders
+        .insert("{{ROLE}}".to_string(), role.name.clone());
+    options
+        .placeholders
+        .insert("{{TASK}}".to_string(), role.system_prompt.clone());
+    options.tools_enabled = role.tools_enabled;
+
+    ContextBuilder::new(options)
+}
+
 // ====================== Tauri命令 ======================
 
 /// 构建上下文（用于测试）
@@ -470,3 +509,41 @@ pub async fn build_context(
     // TODO: 在任务1.3中实现完整的会话集成
     Err("build_context 命令将在后续任务中完整实现".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support;
+    use crate::tool_profiles::ToolSelection;
+
+    fn role(tools_enabled: bool) -> AIRole {
+        AIRole {
+            name: "分析师".to_string(),
+            description: String::new(),
+            system_prompt: "分析 {{CHARACTER_NAME}} 的设定".to_string(),
+            temperature: 0.6,
+            max_tokens: 1000,
+            tools_enabled,
+            max_tool_iterations: 1,
+            tools: ToolSelection::default(),
+        }
+    }
+
+    #[test]
+    fn test_role_fills_placeholders_and_controls_tools() {
+        let character = test_support::character("u", test_support::card("", None));
+        let system = |role: &AIRole| {
+            let context = create_context_builder_for_role(role)
+                .build_full_context(&character, &[], None)
+                .unwrap();
+            context.system_messages[0].content.clone()
+        };
+
+        let with_tools = system(&role(true));
+        assert!(with_tools.starts_with("role: 分析师\ntask: 分析 Alice 的设定\n"));
+        assert!(with_tools.contains("edit_character"));
+
+        let without_tools = system(&role(false));
+        assert_eq!(without_tools, "role: 分析师\ntask: 分析 Alice 的设定\n");
+    }
+}
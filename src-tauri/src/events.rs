@@ -1,4 +1,7 @@
 use crate::backend::domain::{
+    BatchItemResult,
+    BatchProgressPayload,
+    ChangeSetUpdatedPayload,
     CharacterLoadedPayload,
     CharacterUpdatedPayload,
     CharacterUpdateType,
@@ -11,8 +14,10 @@ use crate::backend::domain::{
     SessionUnloadedPayload,
     TokenStatsPayload,
     TokenUsageStats,
+    ToolApprovalRequestedPayload,
     ToolExecutedPayload,
 };
+use crate::change_set::ChangeSet;
 use crate::character_storage::CharacterData;
 use crate::chat_history::ChatMessage;
 use crate::context_builder::BuiltContextResult;
@@ -161,6 +166,53 @@ impl EventEmitter {
         Ok(())
     }
 
+    /// 发送工具调用审批请求事件
+    pub fn send_tool_approval_requested(
+        app: &AppHandle,
+        payload: &ToolApprovalRequestedPayload,
+    ) -> Result<(), String> {
+        app.emit("tool-approval-requested", payload)
+            .map_err(|e| format!("发送工具审批请求事件失败: {}", e))?;
+
+        Ok(())
+    }
+
+    /// 发送提议修改集更新事件
+    pub fn send_change_set_updated(app: &AppHandle, change_set: &ChangeSet) -> Result<(), String> {
+        let payload = ChangeSetUpdatedPayload {
+            uuid: change_set.uuid.clone(),
+            change_set: change_set.clone(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        app.emit("change-set-updated", &payload)
+            .map_err(|e| format!("发送修改集更新事件失败: {}", e))?;
+
+        Ok(())
+    }
+
+    /// 发送批量命令进度事件
+    pub fn send_batch_progress(
+        app: &AppHandle,
+        batch_id: &str,
+        completed: usize,
+        total: usize,
+        result: &BatchItemResult,
+    ) -> Result<(), String> {
+        let payload = BatchProgressPayload {
+            batch_id: batch_id.to_string(),
+            completed,
+            total,
+            result: result.clone(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        app.emit("batch-progress", &payload)
+            .map_err(|e| format!("发送批量进度事件失败: {}", e))?;
+
+        Ok(())
+    }
+
     /// 发送会话卸载事件
     pub fn send_session_unloaded(
         app: &AppHandle,
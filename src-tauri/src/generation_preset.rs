@@ -0,0 +1,153 @@
+use crate::ai_chat::{ChatCompletionRequest, StopSequence};
+use crate::ai_config::AIRole;
+use crate::api_config::ApiConfig;
+use serde::{Deserialize, Serialize};
+
+/// 默认温度（未配置任何预设时使用）
+pub const DEFAULT_TEMPERATURE: f64 = 0.7;
+/// 默认最大生成 Token 数（未配置任何预设时使用）
+pub const DEFAULT_MAX_TOKENS: u32 = 2048;
+
+/// 生成参数预设
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerationPreset {
+    /// 预设名称（在同一 API 配置内唯一）
+    pub name: String,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    #[serde(default)]
+    pub top_k: Option<u32>,
+    #[serde(default)]
+    pub min_p: Option<f64>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f64>,
+    #[serde(default)]
+    pub presence_penalty: Option<f64>,
+    #[serde(default)]
+    pub repetition_penalty: Option<f64>,
+    #[serde(default)]
+    pub seed: Option<i64>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+impl GenerationPreset {
+    /// 内置的兜底预设
+    pub fn fallback() -> Self {
+        Self {
+            name: "default".to_string(),
+            temperature: Some(DEFAULT_TEMPERATURE),
+            max_tokens: Some(DEFAULT_MAX_TOKENS),
+            ..Default::default()
+        }
+    }
+
+    /// 解析本次请求实际生效的生成参数
+    ///
+    /// 优先级（由低到高）：内置默认值 → AI 角色的温度/最大 Token → API 配置中选中的预设。
+    /// `preset_name` 为会话选择的预设，未选择时使用 API 配置的默认预设。
+    pub fn resolve(
+        api_config: &ApiConfig,
+        role: Option<&AIRole>,
+        preset_name: Option<&str>,
+    ) -> Self {
+        let mut resolved = Self::fallback();
+
+        if let Some(role) = role {
+            resolved.temperature = Some(role.temperature as f64);
+            resolved.max_tokens = Some(role.max_tokens);
+        }
+
+        let selected = preset_name.or(api_config.default_preset.as_deref());
+        if let Some(name) = selected {
+            match api_config.generation_presets.iter().find(|p| p.name == name) {
+                Some(preset) => resolved = resolved.merged_with(preset),
+                None => eprintln!(
+                    "⚠️ API配置 '{}' 中不存在生成预设 '{}'，使用默认参数",
+                    api_config.profile, name
+                ),
+            }
+        }
+
+        resolved
+    }
+
+    /// 用另一个预设中已设置的参数覆盖当前预设
+    pub fn merged_with(mut self, other: &GenerationPreset) -> Self {
+        if !other.name.is_empty() {
+            self.name = other.name.clone();
+        }
+        self.temperature = other.temperature.or(self.temperature);
+        self.top_p = other.top_p.or(self.top_p);
+        self.top_k = other.top_k.or(self.top_k);
+        self.min_p = other.min_p.or(self.min_p);
+        self.frequency_penalty = other.frequency_penalty.or(self.frequency_penalty);
+        self.presence_penalty = other.presence_penalty.or(self.presence_penalty);
+        self.repetition_penalty = other.repetition_penalty.or(self.repetition_penalty);
+        self.seed = other.seed.or(self.seed);
+        self.max_tokens = other.max_tokens.or(self.max_tokens);
+        if !other.stop.is_empty() {
+            self.stop = other.stop.clone();
+        }
+        self
+    }
+
+    /// 将预设参数写入请求
+    pub fn apply_to(&self, request: &mut ChatCompletionRequest) {
+        request.temperature = self.temperature;
+        request.top_p = self.top_p;
+        request.top_k = self.top_k;
+        request.min_p = self.min_p;
+        request.frequency_penalty = self.frequency_penalty;
+        request.presence_penalty = self.presence_penalty;
+        request.repetition_penalty = self.repetition_penalty;
+        request.seed = self.seed;
+        request.max_tokens = self.max_tokens;
+        request.stop = if self.stop.is_empty() {
+            None
+        } else {
+            Some(StopSequence::Multiple(self.stop.clone()))
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_config_with_presets() -> ApiConfig {
+        serde_json::from_value(serde_json::json!({
+            "profile": "test",
+            "endpoint": "http://localhost",
+            "key": "",
+            "model": "m",
+            "default": true,
+            "enabled": true,
+            "generation_presets": [
+                { "name": "creative", "temperature": 1.1, "top_k": 40 },
+                { "name": "precise", "temperature": 0.2 }
+            ],
+            "default_preset": "creative"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_session_preset_overrides_default_preset() {
+        let config = api_config_with_presets();
+
+        let resolved = GenerationPreset::resolve(&config, None, None);
+        assert_eq!(resolved.name, "creative");
+        assert_eq!(resolved.temperature, Some(1.1));
+        assert_eq!(resolved.top_k, Some(40));
+        assert_eq!(resolved.max_tokens, Some(DEFAULT_MAX_TOKENS));
+
+        let resolved = GenerationPreset::resolve(&config, None, Some("precise"));
+        assert_eq!(resolved.temperature, Some(0.2));
+        assert_eq!(resolved.top_k, None);
+    }
+}
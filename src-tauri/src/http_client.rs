@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// API 配置的网络选项（代理、附加请求头、自定义 CA 与超时）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkOptions {
+    /// 代理地址（http://、https://、socks5:// 或 socks5h://）
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// 附加请求头（如 OpenRouter 的 HTTP-Referer、Azure 的 api-key；敏感请求头的值与 API 密钥一样加密保存）
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 自定义 CA 证书文件路径（PEM，可包含多个证书）
+    #[serde(default)]
+    pub ca_bundle: Option<String>,
+    /// 整个请求的超时时间（秒）
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// 建立连接的超时时间（秒）
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+}
+
+impl NetworkOptions {
+    /// 请求头是否携带凭据（按名称判断，如 api-key、Authorization、X-Token）
+    pub fn is_sensitive_header(name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        ["authorization", "cookie", "key", "token", "secret", "password"]
+            .iter()
+            .any(|marker| name.contains(marker))
+    }
+
+    /// 校验后的代理地址（空字符串视为未设置）
+    fn proxy_url(&self) -> Result<Option<&str>, String> {
+        let Some(proxy) = self.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()) else {
+            return Ok(None);
+        };
+
+        let scheme = proxy.split("://").next().unwrap_or_default().to_lowercase();
+        match scheme.as_str() {
+            "http" | "https" | "socks5" | "socks5h" => Ok(Some(proxy)),
+            _ => Err(format!("不支持的代理地址: {}", proxy)),
+        }
+    }
+
+    /// 读取自定义 CA 证书文件内容
+    fn ca_bundle_pem(&self) -> Result<Option<Vec<u8>>, String> {
+        match self.ca_bundle.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(path) => std::fs::read(path)
+                .map(Some)
+                .map_err(|e| format!("读取CA证书文件 {} 失败: {}", path, e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// 按 API 配置的网络选项创建 HTTP 客户端
+pub struct HttpClientFactory;
+
+impl HttpClientFactory {
+    /// 创建应用自身请求使用的客户端（聊天回退、文本补全、连接测试、模型列表）
+    pub fn build(options: &NetworkOptions) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(proxy) = options.proxy_url()? {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy).map_err(|e| format!("代理地址无效: {}", e))?,
+            );
+        }
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &options.headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|e| format!("请求头名称 '{}' 无效: {}", name, e))?;
+            let value = reqwest::header::HeaderValue::from_str(value.trim())
+                .map_err(|e| format!("请求头 '{}' 的值无效: {}", name, e))?;
+            headers.insert(name, value);
+        }
+        builder = builder.default_headers(headers);
+
+        if let Some(pem) = options.ca_bundle_pem()? {
+            for certificate in reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("解析CA证书失败: {}", e))?
+            {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(timeout) = options.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(timeout) = options.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(timeout));
+        }
+
+        builder.build().map_err(|e| format!("创建HTTP客户端失败: {}", e))
+    }
+
+    /// 创建 async-openai 使用的客户端（其依赖的 reqwest 版本与应用不同）
+    pub fn build_for_openai(options: &NetworkOptions) -> Result<reqwest_openai::Client, String> {
+        let mut builder = reqwest_openai::Client::builder();
+
+        if let Some(proxy) = options.proxy_url()? {
+            builder = builder.proxy(
+                reqwest_openai::Proxy::all(proxy).map_err(|e| format!("代理地址无效: {}", e))?,
+            );
+        }
+
+        let mut headers = reqwest_openai::header::HeaderMap::new();
+        for (name, value) in &options.headers {
+            let name = reqwest_openai::header::HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|e| format!("请求头名称 '{}' 无效: {}", name, e))?;
+            let value = reqwest_openai::header::HeaderValue::from_str(value.trim())
+                .map_err(|e| format!("请求头 '{}' 的值无效: {}", name, e))?;
+            headers.insert(name, value);
+        }
+        builder = builder.default_headers(headers);
+
+        if let Some(pem) = options.ca_bundle_pem()? {
+            for certificate in reqwest_openai::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("解析CA证书失败: {}", e))?
+            {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(timeout) = options.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(timeout) = options.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(timeout));
+        }
+
+        builder.build().map_err(|e| format!("创建HTTP客户端失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_client_validates_options() {
+        let mut options = NetworkOptions {
+            proxy: Some("http://127.0.0.1:8080".to_string()),
+            timeout_secs: Some(30),
+            ..Default::default()
+        };
+        options
+            .headers
+            .insert("HTTP-Referer".to_string(), "https://example.com".to_string());
+        assert!(HttpClientFactory::build(&options).is_ok());
+
+        options.proxy = Some("socks5h://127.0.0.1:1080".to_string());
+        assert!(HttpClientFactory::build(&options).is_ok());
+        assert!(HttpClientFactory::build_for_openai(&options).is_ok());
+
+        options.proxy = Some("ftp://127.0.0.1:21".to_string());
+        assert!(HttpClientFactory::build(&options).is_err());
+
+        options.proxy = None;
+        options.headers.insert("bad header".to_string(), "x".to_string());
+        assert!(HttpClientFactory::build(&options).is_err());
+
+        assert!(NetworkOptions::is_sensitive_header("api-key"));
+        assert!(NetworkOptions::is_sensitive_header("Authorization"));
+        assert!(!NetworkOptions::is_sensitive_header("HTTP-Referer"));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::ai_chat::{ChatMessage, MessageRole, StopSequence};
+use serde::{Deserialize, Serialize};
+
+/// 默认使用的指令模板
+pub const DEFAULT_INSTRUCT_TEMPLATE: &str = "chatml";
+
+/// 指令模板（用于文本补全模式，将对话渲染为单个 prompt）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructTemplate {
+    /// 模板标识
+    pub id: String,
+    /// 显示名称
+    pub name: String,
+    /// prompt 开头的 BOS 标记
+    pub bos: String,
+    pub system_prefix: String,
+    pub system_suffix: String,
+    pub user_prefix: String,
+    pub user_suffix: String,
+    pub assistant_prefix: String,
+    pub assistant_suffix: String,
+    /// 模板没有独立的 system 段时，将 system 内容并入第一条用户消息（如 Mistral）
+    pub system_as_user: bool,
+}
+
+/// 渲染完成的文本补全 prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedPrompt {
+    pub prompt: String,
+    pub stop: Vec<String>,
+}
+
+impl InstructTemplate {
+    /// 获取所有内置模板
+    pub fn builtin_templates() -> Vec<InstructTemplate> {
+        vec![
+            InstructTemplate {
+                id: "chatml".to_string(),
+                name: "ChatML".to_string(),
+                bos: String::new(),
+                system_prefix: "<|im_start|>system\n".to_string(),
+                system_suffix: "<|im_end|>\n".to_string(),
+                user_prefix: "<|im_start|>user\n".to_string(),
+                user_suffix: "<|im_end|>\n".to_string(),
+                assistant_prefix: "<|im_start|>assistant\n".to_string(),
+                assistant_suffix: "<|im_end|>\n".to_string(),
+                system_as_user: false,
+            },
+            InstructTemplate {
+                id: "alpaca".to_string(),
+                name: "Alpaca".to_string(),
+                bos: String::new(),
+                system_prefix: String::new(),
+                system_suffix: "\n\n".to_string(),
+                user_prefix: "### Instruction:\n".to_string(),
+                user_suffix: "\n\n".to_string(),
+                assistant_prefix: "### Response:\n".to_string(),
+                assistant_suffix: "\n\n".to_string(),
+                system_as_user: false,
+            },
+            InstructTemplate {
+                id: "llama3".to_string(),
+                name: "Llama-3".to_string(),
+                bos: "<|begin_of_text|>".to_string(),
+                system_prefix: "<|start_header_id|>system<|end_header_id|>\n\n".to_string(),
+                system_suffix: "<|eot_id|>".to_string(),
+                user_prefix: "<|start_header_id|>user<|end_header_id|>\n\n".to_string(),
+                user_suffix: "<|eot_id|>".to_string(),
+                assistant_prefix: "<|start_header_id|>assistant<|end_header_id|>\n\n".to_string(),
+                assistant_suffix: "<|eot_id|>".to_string(),
+                system_as_user: false,
+            },
+            InstructTemplate {
+                id: "mistral".to_string(),
+                name: "Mistral".to_string(),
+                bos: "<s>".to_string(),
+                system_prefix: String::new(),
+                system_suffix: String::new(),
+                user_prefix: "[INST] ".to_string(),
+                user_suffix: " [/INST]".to_string(),
+                assistant_prefix: String::new(),
+                assistant_suffix: "</s>".to_string(),
+                system_as_user: true,
+            },
+            InstructTemplate {
+                id: "vicuna".to_string(),
+                name: "Vicuna".to_string(),
+                bos: String::new(),
+                system_prefix: String::new(),
+                system_suffix: "\n\n".to_string(),
+                user_prefix: "USER: ".to_string(),
+                user_suffix: "\n".to_string(),
+                assistant_prefix: "ASSISTANT: ".to_string(),
+                assistant_suffix: "\n".to_string(),
+                system_as_user: false,
+            },
+        ]
+    }
+
+    /// 根据标识查找内置模板，未找到时回退到默认模板
+    pub fn get_or_default(id: Option<&str>) -> InstructTemplate {
+        let templates = Self::builtin_templates();
+        let target = id.unwrap_or(DEFAULT_INSTRUCT_TEMPLATE);
+
+        templates
+            .iter()
+            .find(|template| template.id.eq_ignore_ascii_case(target))
+            .or_else(|| templates.iter().find(|t| t.id == DEFAULT_INSTRUCT_TEMPLATE))
+            .cloned()
+            .expect("默认指令模板必须存在")
+    }
+
+    /// 从模板推导停止序列（轮次结束标记以及下一轮用户/系统前缀）
+    pub fn stop_sequences(&self) -> Vec<String> {
+        let mut stops: Vec<String> = Vec::new();
+
+        for candidate in [
+            &self.assistant_suffix,
+            &self.user_prefix,
+            &self.system_prefix,
+        ] {
+            let trimmed = candidate.trim();
+            if !trimmed.is_empty() && !stops.iter().any(|s| s == trimmed) {
+                stops.push(trimmed.to_string());
+            }
+        }
+
+        stops
+    }
+
+    /// 将聊天消息渲染为单个 prompt，并以 assistant 前缀结尾等待模型续写
+    pub fn render(&self, messages: &[ChatMessage], extra_stop: Option<&StopSequence>) -> RenderedPrompt {
+        let mut prompt = self.bos.clone();
+        let mut pending_system: Vec<&str> = Vec::new();
+
+        for message in messages {
+            match message.role {
+                MessageRole::System => {
+                    if message.content.is_empty() {
+                        continue;
+                    }
+                    if self.system_as_user {
+                        pending_system.push(&message.content);
+                    } else {
+                        prompt.push_str(&self.system_prefix);
+                        prompt.push_str(&message.content);
+                        prompt.push_str(&self.system_suffix);
+                    }
+                }
+                MessageRole::User | MessageRole::Tool => {
+                    let content = if message.role == MessageRole::Tool {
+                        format!("[Tool Response] {}", message.content)
+                    } else {
+                        message.content.clone()
+                    };
+
+                    prompt.push_str(&self.user_prefix);
+                    if !pending_system.is_empty() {
+                        prompt.push_str(&pending_system.join("\n\n"));
+                        prompt.push_str("\n\n");
+                        pending_system.clear();
+                    }
+                    prompt.push_str(&content);
+                    prompt.push_str(&self.user_suffix);
+                }
+                MessageRole::Assistant => {
+                    // 纯工具调用消息在文本补全模式下没有可渲染的内容
+                    if message.content.is_empty() {
+                        continue;
+                    }
+                    prompt.push_str(&self.assistant_prefix);
+                    prompt.push_str(&message.content);
+                    prompt.push_str(&self.assistant_suffix);
+                }
+            }
+        }
+
+        // 没有用户消息承载 system 内容时，单独作为一轮用户消息输出
+        if !pending_system.is_empty() {
+            prompt.push_str(&self.user_prefix);
+            prompt.push_str(&pending_system.join("\n\n"));
+            prompt.push_str(&self.user_suffix);
+        }
+
+        prompt.push_str(&self.assistant_prefix);
+
+        let mut stop = self.stop_sequences();
+        if let Some(extra) = extra_stop {
+            for sequence in extra.to_vec() {
+                if !sequence.is_empty() && !stop.contains(&sequence) {
+                    stop.push(sequence);
+                }
+            }
+        }
+
+        RenderedPrompt { prompt, stop }
+    }
+
+    /// 清理模型输出：去掉结尾残留的停止序列与空白
+    pub fn clean_completion(text: &str, stop: &[String]) -> String {
+        let mut result = text;
+        for sequence in stop {
+            if let Some(index) = result.find(sequence.as_str()) {
+                result = &result[..index];
+            }
+        }
+        result.trim().to_string()
+    }
+}
+
+// ====================== Tauri命令 ======================
+
+/// 获取所有内置指令模板
+#[tauri::command]
+pub async fn get_instruct_templates() -> Result<Vec<InstructTemplate>, String> {
+    Ok(InstructTemplate::builtin_templates())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: MessageRole, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            content: content.to_string(),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn test_chatml_render_ends_with_assistant_prefix() {
+        let template = InstructTemplate::get_or_default(Some("chatml"));
+        let rendered = template.render(
+            &[
+                message(MessageRole::System, "sys"),
+                message(MessageRole::User, "hi"),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            rendered.prompt,
+            "<|im_start|>system\nsys<|im_end|>\n<|im_start|>user\nhi<|im_end|>\n<|im_start|>assistant\n"
+        );
+        assert!(rendered.stop.contains(&"<|im_end|>".to_string()));
+    }
+
+    #[test]
+    fn test_mistral_folds_system_into_first_user_turn() {
+        let template = InstructTemplate::get_or_default(Some("mistral"));
+        let rendered = template.render(
+            &[
+                message(MessageRole::System, "sys"),
+                message(MessageRole::User, "hi"),
+            ],
+            Some(&StopSequence::Single("END".to_string())),
+        );
+
+        assert_eq!(rendered.prompt, "<s>[INST] sys\n\nhi [/INST]");
+        assert_eq!(rendered.stop, vec!["</s>", "[INST]", "END"]);
+    }
+}
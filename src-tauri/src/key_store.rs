@@ -0,0 +1,524 @@
+use crate::file_utils::FileUtils;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use once_cell::sync::{Lazy, OnceCell};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// 口令加密后的密钥前缀
+pub const SEALED_PREFIX: &str = "enc:v1:";
+/// 系统钥匙串中保存的密钥引用前缀
+pub const KEYRING_PREFIX: &str = "keyring:";
+/// 密钥口令环境变量
+pub const PASSPHRASE_ENV: &str = "CCC_KEY_PASSPHRASE";
+/// 密钥存储后端环境变量（`passphrase` 或 `keyring`）
+///
+/// 默认使用口令后端（未提供口令时使用本机口令文件，仅起混淆作用，见 [`KeyStore`]）；
+/// 系统钥匙串须设置为 `keyring` 显式启用。
+pub const BACKEND_ENV: &str = "CCC_KEY_BACKEND";
+
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const KEYRING_SERVICE: &str = "character-card-copilot";
+const VERIFIER_PLAINTEXT: &str = "character-card-copilot";
+
+/// 密钥存储后端
+///
+/// `seal` 返回写入配置文件的内容（密文或引用），`open` 根据该内容取回明文。
+pub trait KeyStoreBackend: Send + Sync {
+    /// 该后端写入配置文件的内容前缀
+    fn prefix(&self) -> &'static str;
+    fn seal(&self, profile: &str, plaintext: &str) -> Result<String, String>;
+    fn open(&self, profile: &str, sealed: &str) -> Result<String, String>;
+    /// 删除配置时清理后端中保存的密钥
+    fn remove(&self, _profile: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// 口令派生密钥后端（PBKDF2-HMAC-SHA256 + AES-256-GCM）
+pub struct PassphraseBackend {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl PassphraseBackend {
+    pub fn new(passphrase: &[u8], salt: &[u8], iterations: u32) -> Result<Self, String> {
+        let iterations = NonZeroU32::new(iterations).ok_or("PBKDF2 迭代次数不能为 0")?;
+        let mut derived = [0u8; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            passphrase,
+            &mut derived,
+        );
+
+        let unbound = UnboundKey::new(&AES_256_GCM, &derived)
+            .map_err(|_| "创建加密密钥失败".to_string())?;
+
+        Ok(Self {
+            key: LessSafeKey::new(unbound),
+            rng: SystemRandom::new(),
+        })
+    }
+}
+
+impl KeyStoreBackend for PassphraseBackend {
+    fn prefix(&self) -> &'static str {
+        SEALED_PREFIX
+    }
+
+    fn seal(&self, _profile: &str, plaintext: &str) -> Result<String, String> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce_bytes)
+            .map_err(|_| "生成随机数失败".to_string())?;
+
+        let mut buffer = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::empty(),
+                &mut buffer,
+            )
+            .map_err(|_| "加密API密钥失败".to_string())?;
+
+        let mut payload = nonce_bytes.to_vec();
+        payload.extend_from_slice(&buffer);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(payload)))
+    }
+
+    fn open(&self, _profile: &str, sealed: &str) -> Result<String, String> {
+        let encoded = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or("不是加密的API密钥")?;
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("API密钥密文格式错误: {}", e))?;
+
+        if payload.len() < NONCE_LEN {
+            return Err("API密钥密文长度错误".to_string());
+        }
+
+        let (nonce_bytes, ciphertext) = payload.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+            .map_err(|_| "API密钥密文格式错误".to_string())?;
+
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut buffer)
+            .map_err(|_| "解密API密钥失败（口令可能不正确）".to_string())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|e| format!("API密钥不是有效的UTF-8: {}", e))
+    }
+}
+
+/// 系统钥匙串后端（Linux 使用 libsecret 的 secret-tool，macOS 使用 security 命令）
+pub struct SystemKeyringBackend;
+
+impl SystemKeyringBackend {
+    /// 当前平台的钥匙串命令行工具是否可用
+    pub fn is_available() -> bool {
+        let program = if cfg!(target_os = "macos") {
+            "security"
+        } else if cfg!(target_os = "linux") {
+            "secret-tool"
+        } else {
+            return false;
+        };
+
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+            .unwrap_or(false)
+    }
+
+    fn run(command: &mut Command, stdin: Option<&str>) -> Result<String, String> {
+        command
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("无法调用系统钥匙串: {}", e))?;
+
+        if let Some(input) = stdin {
+            if let Some(mut pipe) = child.stdin.take() {
+                pipe.write_all(input.as_bytes())
+                    .map_err(|e| format!("写入系统钥匙串失败: {}", e))?;
+            }
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("系统钥匙串调用失败: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "系统钥匙串返回错误: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\r', '\n'])
+            .to_string())
+    }
+}
+
+impl KeyStoreBackend for SystemKeyringBackend {
+    fn prefix(&self) -> &'static str {
+        KEYRING_PREFIX
+    }
+
+    fn seal(&self, profile: &str, plaintext: &str) -> Result<String, String> {
+        if cfg!(target_os = "macos") {
+            // `-w` 放在最后且不带值时 security 从标准输入读取密钥（含确认输入），避免明文出现在进程参数中
+            let input = format!("{0}\n{0}\n", plaintext);
+            Self::run(
+                Command::new("security").args([
+                    "add-generic-password",
+                    "-U",
+                    "-s",
+                    KEYRING_SERVICE,
+                    "-a",
+                    profile,
+                    "-w",
+                ]),
+                Some(&input),
+            )?;
+        } else {
+            Self::run(
+                Command::new("secret-tool").args([
+                    "store",
+                    "--label",
+                    &format!("{} ({})", KEYRING_SERVICE, profile),
+                    "service",
+                    KEYRING_SERVICE,
+                    "account",
+                    profile,
+                ]),
+                Some(plaintext),
+            )?;
+        }
+
+        Ok(format!("{}{}", KEYRING_PREFIX, profile))
+    }
+
+    fn open(&self, _profile: &str, sealed: &str) -> Result<String, String> {
+        let account = sealed
+            .strip_prefix(KEYRING_PREFIX)
+            .ok_or("不是系统钥匙串引用")?;
+
+        if cfg!(target_os = "macos") {
+            Self::run(
+                Command::new("security").args([
+                    "find-generic-password",
+                    "-s",
+                    KEYRING_SERVICE,
+                    "-a",
+                    account,
+                    "-w",
+                ]),
+                None,
+            )
+        } else {
+            Self::run(
+                Command::new("secret-tool").args([
+                    "lookup",
+                    "service",
+                    KEYRING_SERVICE,
+                    "account",
+                    account,
+                ]),
+                None,
+            )
+        }
+    }
+
+    fn remove(&self, profile: &str) -> Result<(), String> {
+        if cfg!(target_os = "macos") {
+            Self::run(
+                Command::new("security").args([
+                    "delete-generic-password",
+                    "-s",
+                    KEYRING_SERVICE,
+                    "-a",
+                    profile,
+                ]),
+                None,
+            )?;
+        } else {
+            Self::run(
+                Command::new("secret-tool").args([
+                    "clear",
+                    "service",
+                    KEYRING_SERVICE,
+                    "account",
+                    profile,
+                ]),
+                None,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 口令后端的元数据（盐值、迭代次数与口令校验密文）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyStoreMetadata {
+    salt: String,
+    iterations: u32,
+    verifier: String,
+}
+
+static PASSPHRASE_BACKEND: OnceCell<Arc<PassphraseBackend>> = OnceCell::new();
+static KEYRING_BACKEND: OnceCell<Arc<SystemKeyringBackend>> = OnceCell::new();
+/// 已解密密钥的缓存（以存储内容为键），避免每次读取配置都解密或调用钥匙串
+static OPENED_KEYS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 未提供口令时保存在应用数据目录中的本机口令文件名
+const LOCAL_PASSPHRASE_FILE: &str = ".local_passphrase";
+
+/// API 密钥存储服务
+///
+/// 未设置 [`PASSPHRASE_ENV`] 且未启用系统钥匙串时，口令保存在 `api/.local_passphrase`
+/// （仅当前用户可读）。该口令与密文位于同一目录，只能防止密钥以明文出现在配置文件里，
+/// 无法防御能读取应用数据目录的人；需要真正的静态加密时请使用钥匙串或自行提供口令。
+pub struct KeyStore;
+
+impl KeyStore {
+    /// 获取（必要时初始化）口令后端
+    fn passphrase_backend(app_handle: &tauri::AppHandle) -> Result<Arc<PassphraseBackend>, String> {
+        PASSPHRASE_BACKEND
+            .get_or_try_init(|| Self::init_passphrase_backend(app_handle).map(Arc::new))
+            .cloned()
+    }
+
+    fn init_passphrase_backend(app_handle: &tauri::AppHandle) -> Result<PassphraseBackend, String> {
+        let api_dir = FileUtils::get_app_data_dir(app_handle)?.join("api");
+        FileUtils::ensure_dir_exists(&api_dir)?;
+
+        let passphrase = match Self::env_passphrase() {
+            Some(value) => value,
+            None => {
+                // 未提供口令时使用本机生成的随机口令（仅混淆，见 KeyStore 文档）
+                let passphrase_file = api_dir.join(LOCAL_PASSPHRASE_FILE);
+                if passphrase_file.exists() {
+                    std::fs::read_to_string(&passphrase_file)
+                        .map_err(|e| format!("读取本机密钥口令失败: {}", e))?
+                } else {
+                    let generated = STANDARD.encode(Self::random_bytes::<32>()?);
+                    Self::write_private_file(&passphrase_file, &generated)
+                        .map_err(|e| format!("保存本机密钥口令失败: {}", e))?;
+                    generated
+                }
+            }
+        };
+
+        let metadata_file = api_dir.join("keystore.json");
+        if metadata_file.exists() {
+            let metadata: KeyStoreMetadata = FileUtils::read_json_file(&metadata_file)?;
+            let salt = STANDARD
+                .decode(&metadata.salt)
+                .map_err(|e| format!("密钥存储元数据损坏: {}", e))?;
+            let backend = PassphraseBackend::new(passphrase.as_bytes(), &salt, metadata.iterations)?;
+
+            if backend.open("", &metadata.verifier).ok().as_deref() != Some(VERIFIER_PLAINTEXT) {
+                return Err(format!(
+                    "密钥口令不正确，无法解密API密钥（请检查 {} 环境变量）",
+                    PASSPHRASE_ENV
+                ));
+            }
+            return Ok(backend);
+        }
+
+        let salt = Self::random_bytes::<16>()?;
+        let backend = PassphraseBackend::new(passphrase.as_bytes(), &salt, PBKDF2_ITERATIONS)?;
+        let metadata = KeyStoreMetadata {
+            salt: STANDARD.encode(salt),
+            iterations: PBKDF2_ITERATIONS,
+            verifier: backend.seal("", VERIFIER_PLAINTEXT)?,
+        };
+        FileUtils::write_json_file(&metadata_file, &metadata)?;
+
+        Ok(backend)
+    }
+
+    fn env_passphrase() -> Option<String> {
+        std::env::var(PASSPHRASE_ENV).ok().filter(|value| !value.is_empty())
+    }
+
+    /// 创建仅当前用户可读写的文件
+    fn write_private_file(path: &Path, contents: &str) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)?.write_all(contents.as_bytes())
+    }
+
+    fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+        let mut bytes = [0u8; N];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| "生成随机数失败".to_string())?;
+        Ok(bytes)
+    }
+
+    /// 是否显式启用了系统钥匙串
+    fn keyring_enabled() -> bool {
+        std::env::var(BACKEND_ENV).as_deref() == Ok("keyring")
+    }
+
+    fn keyring_backend() -> Arc<SystemKeyringBackend> {
+        KEYRING_BACKEND
+            .get_or_init(|| Arc::new(SystemKeyringBackend))
+            .clone()
+    }
+
+    /// 当前用于写入的后端
+    fn active_backend(app_handle: &tauri::AppHandle) -> Result<Arc<dyn KeyStoreBackend>, String> {
+        if !Self::keyring_enabled() {
+            return Ok(Self::passphrase_backend(app_handle)?);
+        }
+        if !SystemKeyringBackend::is_available() {
+            return Err(format!(
+                "{}=keyring 已启用系统钥匙串，但找不到钥匙串命令行工具",
+                BACKEND_ENV
+            ));
+        }
+        Ok(Self::keyring_backend())
+    }
+
+    /// 根据存储内容的前缀选择能够解密它的后端
+    fn backend_for(
+        app_handle: &tauri::AppHandle,
+        stored: &str,
+    ) -> Result<Option<Arc<dyn KeyStoreBackend>>, String> {
+        if stored.starts_with(SEALED_PREFIX) {
+            Ok(Some(Self::passphrase_backend(app_handle)?))
+        } else if stored.starts_with(KEYRING_PREFIX) {
+            Ok(Some(Self::keyring_backend()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 加密密钥，返回写入配置文件的内容
+    pub fn seal(app_handle: &tauri::AppHandle, profile: &str, plaintext: &str) -> Result<String, String> {
+        if plaintext.is_empty() {
+            return Ok(String::new());
+        }
+
+        let sealed = Self::active_backend(app_handle)?.seal(profile, plaintext)?;
+
+        if let Ok(mut opened) = OPENED_KEYS.lock() {
+            opened.insert(sealed.clone(), plaintext.to_string());
+        }
+        Ok(sealed)
+    }
+
+    /// 解密配置文件中的密钥（旧版明文密钥原样返回，结果按存储内容缓存）
+    pub fn open(app_handle: &tauri::AppHandle, profile: &str, stored: &str) -> Result<String, String> {
+        let Some(backend) = Self::backend_for(app_handle, stored)? else {
+            return Ok(stored.to_string());
+        };
+
+        if let Some(plaintext) = OPENED_KEYS.lock().ok().and_then(|opened| opened.get(stored).cloned()) {
+            return Ok(plaintext);
+        }
+
+        let plaintext = backend.open(profile, stored)?;
+        if let Ok(mut opened) = OPENED_KEYS.lock() {
+            opened.insert(stored.to_string(), plaintext.clone());
+        }
+        Ok(plaintext)
+    }
+
+    /// 删除或重命名配置后清理后端中保存的密钥
+    pub fn remove(app_handle: &tauri::AppHandle, profile: &str, stored: &str) -> Result<(), String> {
+        if let Ok(mut opened) = OPENED_KEYS.lock() {
+            opened.remove(stored);
+        }
+        match Self::backend_for(app_handle, stored)? {
+            Some(backend) => backend.remove(profile),
+            None => Ok(()),
+        }
+    }
+
+    /// 存储内容是否需要迁移到当前后端（明文或其他后端的密钥）
+    pub fn needs_migration(app_handle: &tauri::AppHandle, stored: &str) -> Result<bool, String> {
+        if stored.is_empty() {
+            return Ok(false);
+        }
+        Ok(!stored.starts_with(Self::active_backend(app_handle)?.prefix()))
+    }
+
+    /// 返回给前端的掩码形式（保留前 3 位与后 4 位）
+    pub fn mask(key: &str) -> String {
+        let chars: Vec<char> = key.chars().collect();
+        if chars.len() <= 8 {
+            return "*".repeat(chars.len());
+        }
+
+        let head: String = chars[..3].iter().collect();
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}****{}", head, tail)
+    }
+
+    /// 判断前端传回的密钥是否只是已保存密钥的掩码
+    pub fn is_mask_of(candidate: &str, key: &str) -> bool {
+        !key.is_empty() && candidate == Self::mask(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_backend_round_trip() {
+        let backend = PassphraseBackend::new(b"secret", b"salt-salt-salt", 1000).unwrap();
+        let sealed = backend.seal("openai", "sk-test-123").unwrap();
+
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("sk-test-123"));
+        assert_eq!(backend.open("openai", &sealed).unwrap(), "sk-test-123");
+
+        let wrong = PassphraseBackend::new(b"other", b"salt-salt-salt", 1000).unwrap();
+        assert!(wrong.open("openai", &sealed).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_local_passphrase_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("ccc-passphrase-{}", uuid::Uuid::new_v4()));
+        KeyStore::write_private_file(&path, "secret").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(KeyStore::mask("sk-abcdefghijklmnop"), "sk-****mnop");
+        assert_eq!(KeyStore::mask("short"), "*****");
+        assert!(KeyStore::is_mask_of("sk-****mnop", "sk-abcdefghijklmnop"));
+        assert!(!KeyStore::is_mask_of("", ""));
+    }
+}
@@ -4,6 +4,9 @@
 mod file_utils;
 mod character_storage;
 mod api_config;
+mod api_failover;
+mod card_diff;
+mod change_set;
 mod ai_config;
 mod backend;
 mod ai_tools;
@@ -13,13 +16,29 @@ mod character_state;
 mod character_session;
 mod context_builder;
 mod events;
+mod generation_preset;
+mod http_client;
+mod instruct_template;
+mod key_store;
+mod mcp_client;
+mod mcp_server;
+mod model_registry;
 mod png_utils;
+mod token_breakdown;
 mod token_counter;
+mod tool_approval;
+mod tool_audit;
+mod tool_loop;
+mod tool_profiles;
 mod tools;
+mod user_commands;
+mod user_tools;
 mod command_system;
 
 use backend::infrastructure::tauri::{
     add_ai_role,
+    apply_change_set,
+    apply_proposed_change,
     check_token_limit,
     clear_chat_history,
     cleanup_expired_sessions,
@@ -34,6 +53,8 @@ use backend::infrastructure::tauri::{
     delete_api_config,
     delete_character,
     delete_chat_message,
+    discard_change_set,
+    discard_proposed_change,
     edit_chat_message,
     execute_tool_call,
     export_character_card,
@@ -47,23 +68,55 @@ use backend::infrastructure::tauri::{
     get_all_sessions,
     get_api_config_by_profile,
     get_available_tools,
+    get_card_token_breakdown,
+    get_change_set,
     get_character_by_uuid,
     get_default_api_config,
     get_last_chat_message,
+    get_mcp_server_info,
+    get_mcp_server_status,
+    get_mcp_servers,
+    get_model_capabilities,
+    get_pending_tool_approvals,
     get_recent_chat_messages,
+    get_session_enabled_tools,
     get_session_info,
+    get_tool_approval_config,
     get_tool_categories,
+    get_tool_settings,
     get_tools_by_category,
+    get_user_tool_status,
+    get_user_tools_dir,
     import_character_card,
     import_character_card_from_bytes,
+    list_model_capabilities,
+    list_tokenizers,
     load_character_session,
     load_chat_history,
+    query_tool_audit_log,
     regenerate_last_message,
+    reload_user_tools,
+    reconnect_mcp_server,
+    reload_tokenizers,
+    remove_mcp_server,
+    remove_model_capabilities,
+    respond_tool_approval,
+    reveal_api_key,
+    revert_tool_call,
     save_all_sessions,
     save_chat_message,
+    save_mcp_server,
     send_chat_message,
+    set_active_tool_profile,
     set_default_ai_role,
     set_default_api_config,
+    set_model_capabilities,
+    set_session_ai_role,
+    set_session_generation_preset,
+    set_session_proposal_mode,
+    set_session_tool_settings,
+    start_mcp_server,
+    stop_mcp_server,
     test_api_connection,
     toggle_api_config,
     truncate_to_token_limit,
@@ -73,11 +126,18 @@ use backend::infrastructure::tauri::{
     update_character,
     update_character_background_path,
     update_character_field,
+    update_tool_approval_config,
+    update_tool_settings,
     upload_background_image,
 };
 use character_state::{set_active_character, get_active_character, clear_active_character, has_active_character};
 use context_builder::build_context;
-use command_system::tauri_commands::{get_available_commands, search_commands, execute_command};
+use instruct_template::get_instruct_templates;
+use command_system::tauri_commands::{
+    get_available_commands, search_commands, execute_command, complete_command, get_user_commands_config,
+    save_user_commands_config, reload_user_commands, get_user_command_status, get_batch_commands,
+    run_batch_command, cancel_batch_command,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -85,11 +145,55 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
-        .setup(|_app| {
+        .setup(|app| {
+            let mcp_stdio = std::env::args().any(|arg| arg == mcp_server::MCP_STDIO_ARG);
+            // stdio 模式下作为 MCP 服务器被外部代理启动，不创建界面窗口（标准输出只用于 JSON-RPC）
+            if !mcp_stdio {
+                for window_config in &app.config().app.windows {
+                    tauri::WebviewWindowBuilder::from_config(app.handle(), window_config)?.build()?;
+                }
+            }
+            // 将旧版明文API密钥迁移到当前密钥存储后端
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match api_config::ApiConfigService::migrate_stored_keys(&app_handle) {
+                    Ok(0) => {}
+                    Ok(migrated) => eprintln!("🔐 已将 {} 个API密钥迁移为加密存储", migrated),
+                    Err(e) => eprintln!("迁移API密钥失败: {}", e),
+                }
+            });
             // 初始化命令系统
-            tauri::async_runtime::spawn(async {
-                command_system::tauri_commands::initialize_command_system().await;
+            tauri::async_runtime::spawn(command_system::tauri_commands::initialize_command_system(
+                app.handle().clone(),
+            ));
+            // 连接已配置的 MCP 服务器并注册其工具
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                mcp_client::McpService::connect_all(&app_handle).await;
             });
+            // 加载用户工具目录中的工具，目录变化时自动重新加载
+            tauri::async_runtime::spawn(user_tools::UserToolService::watch(app.handle().clone()));
+            // 以 MCP 服务器方式对外提供角色卡操作（stdio 或本地端口）
+            if mcp_stdio {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    mcp_server::McpServer::serve_stdio(app_handle.clone()).await;
+                    // 输入结束即客户端已断开，没有窗口时直接退出
+                    app_handle.exit(0);
+                });
+            }
+            if let Some(port) = std::env::var(mcp_server::MCP_PORT_ENV)
+                .ok()
+                .and_then(|port| port.trim().parse::<u16>().ok())
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    match mcp_server::McpServer::start_tcp(app_handle, port).await {
+                        Ok(info) => eprintln!("MCP 服务器已启动: {}（访问令牌: {}）", info.address, info.token),
+                        Err(e) => eprintln!("{}", e),
+                    }
+                });
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -116,6 +220,12 @@ pub fn run() {
             toggle_api_config,
             test_api_connection,
             fetch_models,
+            reveal_api_key,
+            // 模型能力命令
+            get_model_capabilities,
+            list_model_capabilities,
+            set_model_capabilities,
+            remove_model_capabilities,
             // AI配置命令
             get_ai_config,
             get_ai_role,
@@ -129,6 +239,32 @@ pub fn run() {
             get_tools_by_category,
             execute_tool_call,
             get_tool_categories,
+            get_tool_approval_config,
+            update_tool_approval_config,
+            respond_tool_approval,
+            get_pending_tool_approvals,
+            get_change_set,
+            apply_proposed_change,
+            discard_proposed_change,
+            apply_change_set,
+            discard_change_set,
+            get_user_tool_status,
+            reload_user_tools,
+            get_user_tools_dir,
+            get_tool_settings,
+            update_tool_settings,
+            set_active_tool_profile,
+            query_tool_audit_log,
+            revert_tool_call,
+            // MCP服务器命令
+            get_mcp_servers,
+            get_mcp_server_status,
+            save_mcp_server,
+            remove_mcp_server,
+            reconnect_mcp_server,
+            start_mcp_server,
+            stop_mcp_server,
+            get_mcp_server_info,
             // AI聊天命令
             create_chat_completion,
             create_streaming_chat_completion,
@@ -151,21 +287,38 @@ pub fn run() {
             get_all_sessions,
             save_all_sessions,
             cleanup_expired_sessions,
+            set_session_ai_role,
+            set_session_proposal_mode,
+            set_session_tool_settings,
+            get_session_enabled_tools,
+            set_session_generation_preset,
             delete_chat_message,
             edit_chat_message,
             regenerate_last_message,
             continue_chat,
             // 上下文构建命令
             build_context,
+            get_instruct_templates,
             // Token 计数命令
             count_tokens,
             count_tokens_batch,
             check_token_limit,
             truncate_to_token_limit,
+            get_card_token_breakdown,
+            list_tokenizers,
+            reload_tokenizers,
             // 命令系统
             get_available_commands,
             search_commands,
             execute_command,
+            complete_command,
+            get_user_commands_config,
+            save_user_commands_config,
+            reload_user_commands,
+            get_user_command_status,
+            get_batch_commands,
+            run_batch_command,
+            cancel_batch_command,
             // 通用命令
             generate_uuid
         ])
@@ -0,0 +1,700 @@
+use crate::ai_tools::ToolResult;
+use crate::file_utils::FileUtils;
+use crate::http_client::{HttpClientFactory, NetworkOptions};
+use crate::tools::{McpTool, ToolRegistry};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+/// 客户端声明的 MCP 协议版本
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+/// 单个 MCP 请求的超时时间
+const MCP_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// 注册到工具中心时 MCP 工具的分类
+pub const MCP_TOOL_CATEGORY: &str = "mcp";
+
+/// MCP 服务器连接方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpTransport {
+    /// 启动本地进程，通过标准输入输出交换换行分隔的 JSON-RPC 消息
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    /// Streamable HTTP：向同一地址 POST JSON-RPC 消息
+    Http {
+        url: String,
+        #[serde(default)]
+        network: NetworkOptions,
+    },
+}
+
+/// 用户配置的 MCP 服务器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// 服务器名称（作为工具名前缀，仅保留字母、数字、下划线和连字符）
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub transport: McpTransport,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// MCP 服务器连接状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub connected: bool,
+    /// 已注册到工具中心的工具名称
+    pub tools: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// MCP 服务器提供的工具定义（tools/list 结果）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema", default)]
+    pub input_schema: Value,
+}
+
+/// 解析 JSON-RPC 响应，返回 result 或错误信息
+fn parse_response(message: Value) -> Result<Value, String> {
+    if let Some(error) = message.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("未知错误");
+        return Err(format!("MCP 错误 {}: {}", code, text));
+    }
+    Ok(message.get("result").cloned().unwrap_or(Value::Null))
+}
+
+type PendingRequests = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// stdio 连接
+struct StdioConnection {
+    _child: Child,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: PendingRequests,
+    /// 进程已退出（由读取任务在持有 pending 锁时设置）
+    closed: Arc<AtomicBool>,
+}
+
+impl StdioConnection {
+    /// 启动服务器进程；进程退出后通知 McpService 将 `server` 标记为断开
+    fn spawn(
+        server: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("启动 MCP 服务器 {} 失败: {}", command, e))?;
+
+        let stdin = child.stdin.take().ok_or("无法获取 MCP 服务器标准输入")?;
+        let stdout = child.stdout.take().ok_or("无法获取 MCP 服务器标准输出")?;
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        // 读取响应并按请求 ID 分发；进程退出后让所有等待中的请求失败
+        let reader_pending = pending.clone();
+        let reader_closed = closed.clone();
+        let server = server.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                let Some(id) = message.get("id").and_then(Value::as_u64) else {
+                    continue;
+                };
+                if message.get("result").is_none() && message.get("error").is_none() {
+                    continue; // 服务器发起的请求，当前不支持
+                }
+                if let Some(sender) = reader_pending.lock().unwrap().remove(&id) {
+                    let _ = sender.send(parse_response(message));
+                }
+            }
+            {
+                let mut pending = reader_pending.lock().unwrap();
+                reader_closed.store(true, Ordering::SeqCst);
+                for (_, sender) in pending.drain() {
+                    let _ = sender.send(Err("MCP 服务器已断开".to_string()));
+                }
+            }
+            McpService::remove_closed(&server);
+        });
+
+        Ok(Self {
+            _child: child,
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            closed,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    async fn send(&self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("写入 MCP 服务器失败: {}", e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("写入 MCP 服务器失败: {}", e))
+    }
+
+    async fn request(&self, id: u64, message: Value) -> Result<Value, String> {
+        let (sender, receiver) = oneshot::channel();
+        {
+            // 与读取任务共用锁：进程退出后不再登记新请求，直接失败而不是等到超时
+            let mut pending = self.pending.lock().unwrap();
+            if self.is_closed() {
+                return Err("MCP 服务器已断开".to_string());
+            }
+            pending.insert(id, sender);
+        }
+
+        if let Err(e) = self.send(&message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(MCP_REQUEST_TIMEOUT, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("MCP 服务器已断开".to_string()),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err("MCP 请求超时".to_string())
+            }
+        }
+    }
+}
+
+/// Streamable HTTP 连接
+struct HttpConnection {
+    client: reqwest::Client,
+    url: String,
+    session_id: Mutex<Option<String>>,
+}
+
+impl HttpConnection {
+    fn new(url: &str, network: &NetworkOptions) -> Result<Self, String> {
+        let mut network = network.clone();
+        network.timeout_secs.get_or_insert(MCP_REQUEST_TIMEOUT.as_secs());
+        Ok(Self {
+            client: HttpClientFactory::build(&network)?,
+            url: url.to_string(),
+            session_id: Mutex::new(None),
+        })
+    }
+
+    async fn post(&self, message: &Value) -> Result<reqwest::Response, String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+        if let Some(session_id) = self.session_id.lock().unwrap().clone() {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("请求 MCP 服务器失败: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("MCP 服务器返回错误状态: {}", response.status()));
+        }
+
+        if let Some(session_id) = response
+            .headers()
+            .get("Mcp-Session-Id")
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.lock().unwrap() = Some(session_id.to_string());
+        }
+        Ok(response)
+    }
+
+    async fn send(&self, message: &Value) -> Result<(), String> {
+        self.post(message).await.map(|_| ())
+    }
+
+    async fn request(&self, id: u64, message: Value) -> Result<Value, String> {
+        let response = self.post(&message).await?;
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("读取 MCP 响应失败: {}", e))?;
+
+        if !is_event_stream {
+            let message: Value =
+                serde_json::from_str(&body).map_err(|e| format!("解析 MCP 响应失败: {}", e))?;
+            return parse_response(message);
+        }
+
+        Self::parse_event_stream(&body)
+            .into_iter()
+            .find(|message| message.get("id").and_then(Value::as_u64) == Some(id))
+            .ok_or_else(|| "MCP 响应中缺少对应的结果".to_string())
+            .and_then(parse_response)
+    }
+
+    /// 解析 SSE 响应体中的 JSON-RPC 消息
+    fn parse_event_stream(body: &str) -> Vec<Value> {
+        body.split("\n\n")
+            .filter_map(|event| {
+                let data: Vec<&str> = event
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(str::trim_start)
+                    .collect();
+                serde_json::from_str(&data.join("\n")).ok()
+            })
+            .collect()
+    }
+}
+
+enum McpConnection {
+    Stdio(StdioConnection),
+    Http(HttpConnection),
+}
+
+/// 与单个 MCP 服务器的会话
+pub struct McpClient {
+    connection: McpConnection,
+    next_id: AtomicU64,
+    tools: Vec<McpToolInfo>,
+}
+
+impl McpClient {
+    /// 建立连接、完成初始化握手并获取工具列表
+    async fn connect(config: &McpServerConfig) -> Result<Self, String> {
+        let connection = match &config.transport {
+            McpTransport::Stdio { command, args, env } => {
+                McpConnection::Stdio(StdioConnection::spawn(&config.name, command, args, env)?)
+            }
+            McpTransport::Http { url, network } => McpConnection::Http(HttpConnection::new(url, network)?),
+        };
+
+        let mut client = Self {
+            connection,
+            next_id: AtomicU64::new(1),
+            tools: Vec::new(),
+        };
+
+        client
+            .request(
+                "initialize",
+                serde_json::json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        client.notify("notifications/initialized").await?;
+
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let result = client.request("tools/list", params).await?;
+            let page: Vec<McpToolInfo> =
+                serde_json::from_value(result.get("tools").cloned().unwrap_or_default())
+                    .map_err(|e| format!("解析 MCP 工具列表失败: {}", e))?;
+            client.tools.extend(page);
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        match &self.connection {
+            McpConnection::Stdio(connection) => connection.request(id, message).await,
+            McpConnection::Http(connection) => connection.request(id, message).await,
+        }
+    }
+
+    /// 连接是否已失效（stdio 服务器进程已退出）
+    fn is_closed(&self) -> bool {
+        match &self.connection {
+            McpConnection::Stdio(connection) => connection.is_closed(),
+            McpConnection::Http(_) => false,
+        }
+    }
+
+    async fn notify(&self, method: &str) -> Result<(), String> {
+        let message = serde_json::json!({ "jsonrpc": "2.0", "method": method });
+        match &self.connection {
+            McpConnection::Stdio(connection) => connection.send(&message).await,
+            McpConnection::Http(connection) => connection.send(&message).await,
+        }
+    }
+
+    /// 调用服务器工具，返回 (内容, 是否为工具错误)
+    async fn call_tool(&self, tool_name: &str, arguments: &HashMap<String, Value>) -> Result<(Value, bool), String> {
+        let result = self
+            .request(
+                "tools/call",
+                serde_json::json!({ "name": tool_name, "arguments": arguments }),
+            )
+            .await?;
+
+        let is_error = result.get("isError").and_then(Value::as_bool).unwrap_or(false);
+        let content = result.get("content").cloned().unwrap_or(Value::Null);
+
+        // 纯文本内容合并为字符串，其余内容原样返回
+        let items = content.as_array().cloned().unwrap_or_default();
+        let texts: Vec<&str> = items
+            .iter()
+            .filter(|item| item["type"] == "text")
+            .filter_map(|item| item["text"].as_str())
+            .collect();
+        let data = if !items.is_empty() && texts.len() == items.len() {
+            Value::String(texts.join("\n"))
+        } else {
+            result.get("structuredContent").cloned().unwrap_or(content)
+        };
+
+        Ok((data, is_error))
+    }
+}
+
+/// 已连接的 MCP 服务器
+struct ConnectedServer {
+    client: Arc<McpClient>,
+    /// 已注册到工具中心的工具名称
+    registered_tools: Vec<String>,
+}
+
+static MCP_SERVERS: Lazy<RwLock<HashMap<String, ConnectedServer>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+static MCP_ERRORS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 注册到工具中心的名称：`{服务器}__{工具}`，仅保留函数名允许的字符
+fn registered_tool_name(server: &str, tool: &str) -> String {
+    let name: String = format!("{}__{}", server, tool)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    name.chars().take(64).collect()
+}
+
+/// MCP 客户端服务
+pub struct McpService;
+
+impl McpService {
+    fn get_config_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir.join("mcp_servers.json"))
+    }
+
+    /// 读取 MCP 服务器配置
+    pub fn get_servers(app_handle: &tauri::AppHandle) -> Result<Vec<McpServerConfig>, String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        if !config_file.exists() {
+            return Ok(Vec::new());
+        }
+        FileUtils::read_json_file(&config_file)
+    }
+
+    fn save_servers(app_handle: &tauri::AppHandle, servers: &[McpServerConfig]) -> Result<(), String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        FileUtils::write_json_file(&config_file, servers)
+    }
+
+    /// 新增或更新服务器配置，并按新配置重新连接
+    pub async fn upsert_server(
+        app_handle: &tauri::AppHandle,
+        config: McpServerConfig,
+    ) -> Result<McpServerStatus, String> {
+        let name = config.name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err("MCP 服务器名称只能包含字母、数字、下划线和连字符".to_string());
+        }
+
+        let mut servers = Self::get_servers(app_handle)?;
+        match servers.iter_mut().find(|server| server.name == config.name) {
+            Some(server) => *server = config.clone(),
+            None => servers.push(config.clone()),
+        }
+        Self::save_servers(app_handle, &servers)?;
+
+        Ok(Self::connect(&config).await)
+    }
+
+    /// 删除服务器配置并断开连接
+    pub fn remove_server(app_handle: &tauri::AppHandle, name: &str) -> Result<(), String> {
+        let mut servers = Self::get_servers(app_handle)?;
+        let before = servers.len();
+        servers.retain(|server| server.name != name);
+        if servers.len() == before {
+            return Err(format!("MCP 服务器 {} 不存在", name));
+        }
+        Self::save_servers(app_handle, &servers)?;
+        Self::disconnect(name);
+        MCP_ERRORS.lock().unwrap().remove(name);
+        Ok(())
+    }
+
+    /// 连接所有已启用的服务器（应用启动时调用）
+    pub async fn connect_all(app_handle: &tauri::AppHandle) {
+        let servers = match Self::get_servers(app_handle) {
+            Ok(servers) => servers,
+            Err(e) => {
+                eprintln!("读取 MCP 服务器配置失败: {}", e);
+                return;
+            }
+        };
+
+        let connections = servers.iter().filter(|server| server.enabled).map(Self::connect);
+        for status in futures::future::join_all(connections).await {
+            if let Some(error) = status.error {
+                eprintln!("连接 MCP 服务器 {} 失败: {}", status.name, error);
+            }
+        }
+    }
+
+    /// 按名称重新连接服务器
+    pub async fn reconnect(app_handle: &tauri::AppHandle, name: &str) -> Result<McpServerStatus, String> {
+        let config = Self::get_servers(app_handle)?
+            .into_iter()
+            .find(|server| server.name == name)
+            .ok_or_else(|| format!("MCP 服务器 {} 不存在", name))?;
+        Ok(Self::connect(&config).await)
+    }
+
+    /// （重新）连接服务器并将其工具注册到工具中心
+    async fn connect(config: &McpServerConfig) -> McpServerStatus {
+        Self::disconnect(&config.name);
+        MCP_ERRORS.lock().unwrap().remove(&config.name);
+        if !config.enabled {
+            return Self::status(&config.name);
+        }
+
+        let client = match McpClient::connect(config).await {
+            Ok(client) => Arc::new(client),
+            Err(e) => {
+                MCP_ERRORS.lock().unwrap().insert(config.name.clone(), e);
+                return Self::status(&config.name);
+            }
+        };
+
+        let mut registered_tools = Vec::new();
+        for tool in &client.tools {
+            let name = registered_tool_name(&config.name, &tool.name);
+            let mcp_tool = McpTool {
+                name: name.clone(),
+                server: config.name.clone(),
+                remote_name: tool.name.clone(),
+                description: tool.description.clone().unwrap_or_default(),
+                input_schema: tool.input_schema.clone(),
+            };
+            if ToolRegistry::register_dynamic_tool_global(Arc::new(mcp_tool)) {
+                registered_tools.push(name);
+            } else {
+                eprintln!("MCP 工具 {} 与已注册的工具重名，已跳过", name);
+            }
+        }
+
+        MCP_SERVERS.write().unwrap().insert(
+            config.name.clone(),
+            ConnectedServer {
+                client,
+                registered_tools,
+            },
+        );
+        Self::status(&config.name)
+    }
+
+    /// 断开服务器连接并注销其工具
+    fn disconnect(name: &str) {
+        if let Some(server) = MCP_SERVERS.write().unwrap().remove(name) {
+            for tool_name in &server.registered_tools {
+                ToolRegistry::unregister_tool_global(tool_name);
+            }
+        }
+    }
+
+    /// stdio 服务器进程退出后标记为断开并注销其工具（已被新连接替换时不处理）
+    fn remove_closed(name: &str) {
+        let removed = {
+            let mut servers = MCP_SERVERS.write().unwrap();
+            match servers.get(name) {
+                Some(server) if server.client.is_closed() => servers.remove(name),
+                _ => None,
+            }
+        };
+        if let Some(server) = removed {
+            for tool_name in &server.registered_tools {
+                ToolRegistry::unregister_tool_global(tool_name);
+            }
+            MCP_ERRORS
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), "MCP 服务器进程已退出".to_string());
+            eprintln!("MCP 服务器 {} 进程已退出，已注销其工具", name);
+        }
+    }
+
+    fn status(name: &str) -> McpServerStatus {
+        let servers = MCP_SERVERS.read().unwrap();
+        let server = servers.get(name);
+        McpServerStatus {
+            name: name.to_string(),
+            connected: server.is_some(),
+            tools: server.map(|server| server.registered_tools.clone()).unwrap_or_default(),
+            error: MCP_ERRORS.lock().unwrap().get(name).cloned(),
+        }
+    }
+
+    /// 所有已配置服务器的连接状态
+    pub fn list_status(app_handle: &tauri::AppHandle) -> Result<Vec<McpServerStatus>, String> {
+        Ok(Self::get_servers(app_handle)?
+            .iter()
+            .map(|server| Self::status(&server.name))
+            .collect())
+    }
+
+    /// 调用 MCP 服务器上的工具
+    pub async fn call_tool(server: &str, tool_name: &str, arguments: &HashMap<String, Value>) -> ToolResult {
+        let start_time = std::time::Instant::now();
+        let client = MCP_SERVERS
+            .read()
+            .unwrap()
+            .get(server)
+            .map(|server| server.client.clone());
+
+        let result = match client {
+            Some(client) => {
+                let result = client.call_tool(tool_name, arguments).await;
+                if client.is_closed() {
+                    Self::remove_closed(server);
+                }
+                result
+            }
+            None => Err(format!("MCP 服务器 {} 未连接", server)),
+        };
+
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        match result {
+            Ok((data, false)) => ToolResult {
+                success: true,
+                data: Some(data),
+                error: None,
+                execution_time_ms,
+            },
+            Ok((data, true)) => ToolResult {
+                success: false,
+                error: Some(data.as_str().map(str::to_string).unwrap_or_else(|| data.to_string())),
+                data: Some(data),
+                execution_time_ms,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                data: None,
+                error: Some(e),
+                execution_time_ms,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_stream_and_tool_names() {
+        let body = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"ok\":true}}\n\n";
+        let messages = HttpConnection::parse_event_stream(body);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(parse_response(messages[0].clone()).unwrap()["ok"], true);
+
+        let error = serde_json::json!({ "id": 1, "error": { "code": -32601, "message": "Method not found" } });
+        assert!(parse_response(error).unwrap_err().contains("-32601"));
+
+        assert_eq!(registered_tool_name("wiki", "search.pages"), "wiki__search_pages");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exited_server_fails_requests_immediately() {
+        let args = ["-c".to_string(), "exit 0".to_string()];
+        let connection = StdioConnection::spawn("gone", "sh", &args, &HashMap::new()).unwrap();
+        for _ in 0..100 {
+            if connection.is_closed() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(connection.is_closed());
+
+        let started = std::time::Instant::now();
+        let message = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+        assert!(connection.request(1, message).await.unwrap_err().contains("断开"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,780 @@
+use crate::ai_tools::{ToolCallRequest, ToolResult};
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::CharacterUpdateType;
+use crate::card_diff::{diff_cards, FieldChange};
+use crate::character_storage::{CharacterBook, CharacterData, CharacterStorage, TavernCardV2, WorldBookEntry};
+use crate::mcp_client::MCP_TOOL_CATEGORY;
+use crate::token_breakdown::CardTokenBreakdown;
+use crate::token_counter::{TokenCounter, TokenizerRegistry};
+use crate::tool_approval::{ApprovalDecision, ApprovalPolicy, ToolApprovalService};
+use crate::tool_audit::ToolAuditService;
+use crate::tools::{CharacterLock, ToolRegistry, TOOL_REGISTRY};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use once_cell::sync::Lazy;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// 服务端支持的 MCP 协议版本
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+/// 启动参数：以 stdio 方式运行 MCP 服务器
+pub const MCP_STDIO_ARG: &str = "--mcp-stdio";
+/// 环境变量：启动时在本地端口上运行 MCP 服务器
+pub const MCP_PORT_ENV: &str = "CCC_MCP_SERVER_PORT";
+/// 环境变量：本地端口服务器的访问令牌（未设置时每次启动随机生成）
+pub const MCP_TOKEN_ENV: &str = "CCC_MCP_SERVER_TOKEN";
+/// 会修改角色卡的存储操作（与内置修改类工具一样遵循审批策略）
+const MUTATING_STORE_TOOLS: &[&str] = &[
+    "update_character",
+    "delete_character",
+    "update_worldbook_entry",
+    "delete_worldbook_entry",
+];
+/// 角色卡资源 URI 前缀
+const CHARACTER_URI_PREFIX: &str = "character://";
+
+/// JSON-RPC 错误
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn method_not_found(method: &str) -> Self {
+        Self {
+            code: -32601,
+            message: format!("Method not found: {}", method),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: -32602,
+            message: message.into(),
+        }
+    }
+
+    fn unauthorized() -> Self {
+        Self {
+            code: -32001,
+            message: "Unauthorized: initialize 请求缺少正确的访问令牌（params._meta.token）".to_string(),
+        }
+    }
+}
+
+/// 本地端口服务器信息（在设置中展示，供外部代理配置连接）
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerInfo {
+    pub address: String,
+    /// 客户端须在 initialize 请求的 `params._meta.token` 中提供（兼容旧的 `params.token`）
+    pub token: String,
+}
+
+/// 正在运行的本地端口服务器
+struct RunningServer {
+    addr: SocketAddr,
+    token: String,
+    handle: JoinHandle<()>,
+}
+
+static TCP_SERVER: Lazy<Mutex<Option<RunningServer>>> = Lazy::new(|| Mutex::new(None));
+
+/// MCP 服务器 - 将角色卡存储与内置 AI 工具暴露给外部代理
+///
+/// 消息格式为换行分隔的 JSON-RPC 2.0（stdio 与本地端口相同）。
+/// 本地端口对本机所有进程可见，连接须先在 `initialize` 中提供访问令牌；
+/// stdio 由客户端自行启动，不需要令牌。
+pub struct McpServer;
+
+impl McpServer {
+    // ========== 传输 ==========
+
+    /// 通过标准输入输出提供服务（直到输入结束）
+    pub async fn serve_stdio(app_handle: tauri::AppHandle) {
+        Self::serve_stream(app_handle, tokio::io::stdin(), tokio::io::stdout(), None).await;
+    }
+
+    /// 在本机端口上启动服务器（端口为 0 时自动分配），返回监听地址与访问令牌
+    pub async fn start_tcp(app_handle: tauri::AppHandle, port: u16) -> Result<McpServerInfo, String> {
+        Self::stop_tcp();
+
+        let token = match std::env::var(MCP_TOKEN_ENV) {
+            Ok(token) if !token.trim().is_empty() => token.trim().to_string(),
+            _ => Self::generate_token()?,
+        };
+
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("MCP 服务器监听端口 {} 失败: {}", port, e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("获取 MCP 服务器地址失败: {}", e))?;
+
+        let connection_token = token.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let app_handle = app_handle.clone();
+                let token = connection_token.clone();
+                tokio::spawn(async move {
+                    let (reader, writer) = stream.into_split();
+                    Self::serve_stream(app_handle, reader, writer, Some(token)).await;
+                });
+            }
+        });
+
+        let info = McpServerInfo {
+            address: addr.to_string(),
+            token: token.clone(),
+        };
+        *TCP_SERVER.lock().unwrap() = Some(RunningServer { addr, token, handle });
+        Ok(info)
+    }
+
+    /// 停止本地端口服务器
+    pub fn stop_tcp() -> bool {
+        match TCP_SERVER.lock().unwrap().take() {
+            Some(server) => {
+                server.handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 本地端口服务器的监听地址与访问令牌
+    pub fn tcp_info() -> Option<McpServerInfo> {
+        TCP_SERVER.lock().unwrap().as_ref().map(|server| McpServerInfo {
+            address: server.addr.to_string(),
+            token: server.token.clone(),
+        })
+    }
+
+    fn generate_token() -> Result<String, String> {
+        let mut bytes = [0u8; 24];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| "生成 MCP 访问令牌失败".to_string())?;
+        Ok(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    /// 连接的第一条请求须为携带正确令牌的 initialize
+    ///
+    /// 令牌放在 MCP 预留的 `params._meta.token` 中，标准客户端无需改动协议即可携带；
+    /// 旧版客户端使用的 `params.token` 仍然接受
+    fn token_matches(message: &Value, expected: &str) -> bool {
+        if message.get("method").and_then(Value::as_str) != Some("initialize") {
+            return false;
+        }
+        let params = message.get("params");
+        let provided = params
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("token"))
+            .or_else(|| params.and_then(|params| params.get("token")))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        // 逐字节比较全部内容，避免按耗时猜测令牌
+        provided.len() == expected.len()
+            && provided
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    async fn serve_stream<R, W>(app_handle: tauri::AppHandle, reader: R, mut writer: W, token: Option<String>)
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut authorized = token.is_none();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(message) if !authorized => {
+                    if Self::token_matches(&message, token.as_deref().unwrap_or_default()) {
+                        authorized = true;
+                        Self::handle_message(&app_handle, message).await
+                    } else {
+                        // 未授权的连接返回错误后立即断开
+                        let id = message.get("id").cloned().unwrap_or(Value::Null);
+                        let response = Self::error_response(id, RpcError::unauthorized());
+                        let _ = Self::write_message(&mut writer, &response).await;
+                        break;
+                    }
+                }
+                Ok(message) => Self::handle_message(&app_handle, message).await,
+                Err(e) => Some(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": -32700, "message": format!("Parse error: {}", e) },
+                })),
+            };
+
+            if let Some(response) = response {
+                if Self::write_message(&mut writer, &response).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn write_message<W>(writer: &mut W, message: &Value) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut line = message.to_string();
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await
+    }
+
+    fn error_response(id: Value, error: RpcError) -> Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        })
+    }
+
+    // ========== 协议 ==========
+
+    /// 处理一条 JSON-RPC 消息；通知消息没有响应
+    pub async fn handle_message(app_handle: &tauri::AppHandle, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let response = match Self::dispatch(app_handle, method, params).await {
+            Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => Self::error_response(id, error),
+        };
+        Some(response)
+    }
+
+    async fn dispatch(app_handle: &tauri::AppHandle, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(serde_json::json!({
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": { "tools": {}, "resources": {} },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(serde_json::json!({})),
+            "tools/list" => Ok(serde_json::json!({ "tools": Self::tool_definitions() })),
+            "tools/call" => {
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("缺少工具名称"))?;
+                let arguments = params
+                    .get("arguments")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+
+                // 工具执行错误作为工具结果返回（isError），而不是协议错误
+                let (text, is_error) = match Self::call_tool(app_handle, name, arguments).await {
+                    Ok(value) => (Self::to_text(&value), false),
+                    Err(e) => (e, true),
+                };
+                Ok(serde_json::json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": is_error,
+                }))
+            }
+            "resources/list" => {
+                let resources: Vec<Value> = CharacterStorage::get_all_characters(app_handle)
+                    .map_err(RpcError::invalid_params)?
+                    .iter()
+                    .map(|character| {
+                        serde_json::json!({
+                            "uri": format!("{}{}", CHARACTER_URI_PREFIX, character.uuid),
+                            "name": character.card.data.name,
+                            "mimeType": "application/json",
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::json!({ "resources": resources }))
+            }
+            "resources/read" => {
+                let uri = params
+                    .get("uri")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("缺少资源 URI"))?;
+                let uuid = uri
+                    .strip_prefix(CHARACTER_URI_PREFIX)
+                    .ok_or_else(|| RpcError::invalid_params(format!("不支持的资源: {}", uri)))?;
+                let character = Self::get_character(app_handle, uuid).map_err(RpcError::invalid_params)?;
+                Ok(serde_json::json!({
+                    "contents": [{
+                        "uri": uri,
+                        "mimeType": "application/json",
+                        "text": Self::to_text(&serde_json::to_value(&character.card).unwrap_or_default()),
+                    }],
+                }))
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn to_text(value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        }
+    }
+
+    // ========== 工具 ==========
+
+    fn schema(properties: Value, required: &[&str]) -> Value {
+        serde_json::json!({ "type": "object", "properties": properties, "required": required })
+    }
+
+    /// 工具列表：存储操作 + 内置 AI 工具（不转发 MCP 客户端注册的外部工具）
+    fn tool_definitions() -> Vec<Value> {
+        let uuid = serde_json::json!({ "type": "string", "description": "角色 UUID" });
+        let model = serde_json::json!({ "type": "string", "description": "按该模型选择分词器（可选）" });
+        let entry_id = serde_json::json!({ "type": "integer", "description": "世界书条目 ID" });
+
+        let mut tools = vec![
+            serde_json::json!({
+                "name": "list_characters",
+                "description": "列出所有角色卡（UUID、名称、标签）",
+                "inputSchema": Self::schema(serde_json::json!({}), &[]),
+            }),
+            serde_json::json!({
+                "name": "get_character",
+                "description": "获取角色卡完整数据",
+                "inputSchema": Self::schema(serde_json::json!({ "uuid": uuid }), &["uuid"]),
+            }),
+            serde_json::json!({
+                "name": "create_character",
+                "description": "创建新角色卡，返回新角色数据",
+                "inputSchema": Self::schema(serde_json::json!({ "name": { "type": "string" } }), &["name"]),
+            }),
+            serde_json::json!({
+                "name": "update_character",
+                "description": "更新角色卡字段（fields 中的键为 TavernCardV2 data 字段，如 description、tags）",
+                "inputSchema": Self::schema(
+                    serde_json::json!({ "uuid": uuid, "fields": { "type": "object" } }),
+                    &["uuid", "fields"],
+                ),
+            }),
+            serde_json::json!({
+                "name": "delete_character",
+                "description": "删除角色卡",
+                "inputSchema": Self::schema(serde_json::json!({ "uuid": uuid }), &["uuid"]),
+            }),
+            serde_json::json!({
+                "name": "list_worldbook_entries",
+                "description": "列出角色的世界书条目",
+                "inputSchema": Self::schema(serde_json::json!({ "uuid": uuid }), &["uuid"]),
+            }),
+            serde_json::json!({
+                "name": "update_worldbook_entry",
+                "description": "更新世界书条目字段（fields 中的键为条目字段，如 keys、content、enabled）",
+                "inputSchema": Self::schema(
+                    serde_json::json!({ "uuid": uuid, "entry_id": entry_id, "fields": { "type": "object" } }),
+                    &["uuid", "entry_id", "fields"],
+                ),
+            }),
+            serde_json::json!({
+                "name": "delete_worldbook_entry",
+                "description": "删除世界书条目",
+                "inputSchema": Self::schema(
+                    serde_json::json!({ "uuid": uuid, "entry_id": entry_id }),
+                    &["uuid", "entry_id"],
+                ),
+            }),
+            serde_json::json!({
+                "name": "count_tokens",
+                "description": "统计文本的 Token 数量",
+                "inputSchema": Self::schema(
+                    serde_json::json!({ "text": { "type": "string" }, "model": model }),
+                    &["text"],
+                ),
+            }),
+            serde_json::json!({
+                "name": "get_card_token_breakdown",
+                "description": "按字段统计角色卡的 Token 数量（常驻/条件触发）",
+                "inputSchema": Self::schema(serde_json::json!({ "uuid": uuid, "model": model }), &["uuid"]),
+            }),
+        ];
+
+        // 内置 AI 工具（如 edit_character、create_world_book_entry），额外需要角色 UUID
+        let registry = TOOL_REGISTRY.read().unwrap();
+        for tool in registry.tools.values() {
+            if !tool.enabled() || tool.category() == MCP_TOOL_CATEGORY {
+                continue;
+            }
+            let mut schema = tool.input_schema();
+            if !schema.is_object() {
+                schema = Self::schema(serde_json::json!({}), &[]);
+            }
+            if tool.modifies_character() {
+                schema["properties"]["uuid"] = uuid.clone();
+                match schema["required"].as_array_mut() {
+                    Some(required) => required.push(Value::from("uuid")),
+                    None => schema["required"] = serde_json::json!(["uuid"]),
+                }
+            }
+            tools.push(serde_json::json!({
+                "name": tool.name(),
+                "description": tool.description(),
+                "inputSchema": schema,
+            }));
+        }
+
+        tools
+    }
+
+    async fn call_tool(
+        app_handle: &tauri::AppHandle,
+        name: &str,
+        mut arguments: Map<String, Value>,
+    ) -> Result<Value, String> {
+        let str_arg = |arguments: &Map<String, Value>, key: &str| -> Result<String, String> {
+            arguments
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("缺少参数: {}", key))
+        };
+        let entry_id_arg = |arguments: &Map<String, Value>| -> Result<i32, String> {
+            arguments
+                .get("entry_id")
+                .and_then(Value::as_i64)
+                .map(|id| id as i32)
+                .ok_or_else(|| "缺少参数: entry_id".to_string())
+        };
+        let fields_arg = |arguments: &Map<String, Value>| -> Result<Map<String, Value>, String> {
+            arguments
+                .get("fields")
+                .and_then(Value::as_object)
+                .cloned()
+                .ok_or_else(|| "缺少参数: fields".to_string())
+        };
+
+        if MUTATING_STORE_TOOLS.contains(&name) {
+            let uuid = str_arg(&arguments, "uuid")?;
+            arguments = Self::check_approval(
+                app_handle,
+                &uuid,
+                name,
+                arguments,
+                Err("由外部 MCP 客户端发起，无法预览修改".to_string()),
+            )
+            .await?;
+        }
+
+        match name {
+            "list_characters" => {
+                let characters = CharacterStorage::get_all_characters(app_handle)?;
+                Ok(Value::Array(
+                    characters
+                        .iter()
+                        .map(|character| {
+                            serde_json::json!({
+                                "uuid": character.uuid,
+                                "name": character.card.data.name,
+                                "tags": character.card.data.tags,
+                            })
+                        })
+                        .collect(),
+                ))
+            }
+            "get_character" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let character = Self::get_character(app_handle, &uuid)?;
+                serde_json::to_value(&character.card).map_err(|e| e.to_string())
+            }
+            "create_character" => {
+                let name = str_arg(&arguments, "name")?;
+                let character = CharacterStorage::create_character(app_handle, &name)?;
+                Ok(serde_json::json!({ "uuid": character.uuid, "name": character.card.data.name }))
+            }
+            "update_character" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let fields = fields_arg(&arguments)?;
+                Self::modify_card(app_handle, name, &uuid, &arguments, CharacterUpdateType::FullData, |card| {
+                    let mut data = serde_json::to_value(&card.data).map_err(|e| e.to_string())?;
+                    for (key, value) in fields {
+                        if data.get(&key).is_none() {
+                            return Err(format!("不支持的字段: {}", key));
+                        }
+                        data[key] = value;
+                    }
+                    card.data = serde_json::from_value(data).map_err(|e| format!("字段格式错误: {}", e))?;
+                    Ok(Value::from("角色卡已更新"))
+                })
+                .await
+            }
+            "delete_character" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let _character_guard = CharacterLock::acquire(&uuid).await;
+                Self::get_character(app_handle, &uuid)?;
+                CharacterStorage::delete_character(app_handle, &uuid)?;
+                Ok(Value::from("角色卡已删除"))
+            }
+            "list_worldbook_entries" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let character = Self::get_character(app_handle, &uuid)?;
+                let entries = character
+                    .card
+                    .data
+                    .character_book
+                    .map(|book| book.entries)
+                    .unwrap_or_default();
+                serde_json::to_value(entries).map_err(|e| e.to_string())
+            }
+            "update_worldbook_entry" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let entry_id = entry_id_arg(&arguments)?;
+                let fields = fields_arg(&arguments)?;
+                Self::modify_card(app_handle, name, &uuid, &arguments, CharacterUpdateType::Worldbook, |card| {
+                    let entry = Self::find_entry(card.data.character_book.as_mut(), entry_id)?;
+                    let mut value = serde_json::to_value(&*entry).map_err(|e| e.to_string())?;
+                    for (key, field) in fields {
+                        if key == "id" {
+                            continue;
+                        }
+                        value[key] = field;
+                    }
+                    *entry = serde_json::from_value::<WorldBookEntry>(value)
+                        .map_err(|e| format!("字段格式错误: {}", e))?;
+                    serde_json::to_value(&*entry).map_err(|e| e.to_string())
+                })
+                .await
+            }
+            "delete_worldbook_entry" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let entry_id = entry_id_arg(&arguments)?;
+                Self::modify_card(app_handle, name, &uuid, &arguments, CharacterUpdateType::Worldbook, |card| {
+                    Self::find_entry(card.data.character_book.as_mut(), entry_id)?;
+                    if let Some(book) = card.data.character_book.as_mut() {
+                        book.entries.retain(|entry| entry.id != Some(entry_id));
+                    }
+                    Ok(Value::from("世界书条目已删除"))
+                })
+                .await
+            }
+            "count_tokens" => {
+                let text = str_arg(&arguments, "text")?;
+                let counter = Self::resolve_counter(app_handle, arguments.get("model"))?;
+                serde_json::to_value(counter.count_tokens(&text)).map_err(|e| e.to_string())
+            }
+            "get_card_token_breakdown" => {
+                let uuid = str_arg(&arguments, "uuid")?;
+                let character = Self::get_character(app_handle, &uuid)?;
+                let counter = Self::resolve_counter(app_handle, arguments.get("model"))?;
+                serde_json::to_value(CardTokenBreakdown::build(&counter, &character)).map_err(|e| e.to_string())
+            }
+            _ => {
+                let modifies_character = {
+                    let registry = TOOL_REGISTRY.read().unwrap();
+                    match registry.tools.get(name) {
+                        Some(tool) if tool.category() != MCP_TOOL_CATEGORY => tool.modifies_character(),
+                        _ => return Err(format!("Unknown tool: {}", name)),
+                    }
+                };
+
+                let character_uuid = arguments
+                    .remove("uuid")
+                    .and_then(|uuid| uuid.as_str().map(str::to_string));
+                let mut request = ToolCallRequest {
+                    tool_name: name.to_string(),
+                    parameters: arguments.into_iter().collect(),
+                    character_uuid,
+                    context: None,
+                    origin: None,
+                };
+
+                if modifies_character {
+                    let preview = ToolRegistry::preview_tool_call_global(app_handle, &request)
+                        .map(Option::unwrap_or_default);
+                    let parameters = std::mem::take(&mut request.parameters).into_iter().collect();
+                    request.parameters = Self::check_approval(
+                        app_handle,
+                        request.character_uuid.as_deref().unwrap_or_default(),
+                        name,
+                        parameters,
+                        preview,
+                    )
+                    .await?
+                    .into_iter()
+                    .collect();
+                }
+                let result = ToolRegistry::execute_tool_call_global(app_handle, &request).await;
+                if result.success {
+                    Ok(result.data.unwrap_or(Value::Null))
+                } else {
+                    Err(result.error.unwrap_or_else(|| "工具执行失败".to_string()))
+                }
+            }
+        }
+    }
+
+    /// 修改类调用按工具审批策略处理，返回（可能经用户修改的）参数
+    async fn check_approval(
+        app_handle: &tauri::AppHandle,
+        uuid: &str,
+        name: &str,
+        arguments: Map<String, Value>,
+        preview: Result<Vec<FieldChange>, String>,
+    ) -> Result<Map<String, Value>, String> {
+        let config = ToolApprovalService::get_config(app_handle).unwrap_or_default();
+        match config.policy_for(name, true) {
+            ApprovalPolicy::Auto => Ok(arguments),
+            ApprovalPolicy::Deny => Err(format!("工具 {} 已被审批策略禁用", name)),
+            ApprovalPolicy::Ask => {
+                let parameters: HashMap<String, Value> = arguments.into_iter().collect();
+                let decision = ToolApprovalService::request_approval(
+                    app_handle,
+                    uuid,
+                    name,
+                    &parameters,
+                    preview,
+                    Duration::from_secs(config.timeout_secs.max(1)),
+                )
+                .await;
+
+                match decision {
+                    ApprovalDecision::Approve => Ok(parameters.into_iter().collect()),
+                    ApprovalDecision::Edit { arguments } => Ok(arguments.into_iter().collect()),
+                    ApprovalDecision::Reject { reason } => Err(match reason {
+                        Some(reason) => format!("用户拒绝了工具调用 {}: {}", name, reason),
+                        None => format!("用户拒绝了工具调用 {}", name),
+                    }),
+                }
+            }
+        }
+    }
+
+    fn get_character(app_handle: &tauri::AppHandle, uuid: &str) -> Result<CharacterData, String> {
+        CharacterStorage::get_character_by_uuid(app_handle, uuid)?
+            .ok_or_else(|| format!("角色 {} 不存在", uuid))
+    }
+
+    fn find_entry(book: Option<&mut CharacterBook>, entry_id: i32) -> Result<&mut WorldBookEntry, String> {
+        book.and_then(|book| book.entries.iter_mut().find(|entry| entry.id == Some(entry_id)))
+            .ok_or_else(|| format!("世界书条目 {} 不存在", entry_id))
+    }
+
+    fn resolve_counter(app_handle: &tauri::AppHandle, model: Option<&Value>) -> Result<Arc<TokenCounter>, String> {
+        match model.and_then(Value::as_str).map(str::trim).filter(|m| !m.is_empty()) {
+            Some(model) => TokenizerRegistry::for_model(app_handle, model),
+            None => TokenizerRegistry::for_active_model(app_handle),
+        }
+    }
+
+    /// 在角色锁内读取、修改并保存角色卡，记入工具审计日志后通知前端刷新
+    async fn modify_card<F>(
+        app_handle: &tauri::AppHandle,
+        tool_name: &str,
+        uuid: &str,
+        arguments: &Map<String, Value>,
+        update_type: CharacterUpdateType,
+        modify: F,
+    ) -> Result<Value, String>
+    where
+        F: FnOnce(&mut TavernCardV2) -> Result<Value, String>,
+    {
+        let start_time = std::time::Instant::now();
+        let _character_guard = CharacterLock::acquire(uuid).await;
+
+        let before = Self::get_character(app_handle, uuid)?.card;
+        let mut card = before.clone();
+        let outcome = modify(&mut card)
+            .and_then(|result| CharacterStorage::update_character(app_handle, uuid, &card).map(|_| result));
+
+        let request = ToolCallRequest {
+            tool_name: tool_name.to_string(),
+            parameters: arguments.clone().into_iter().collect(),
+            character_uuid: Some(uuid.to_string()),
+            context: None,
+            origin: None,
+        };
+        let result = ToolResult {
+            success: outcome.is_ok(),
+            data: outcome.as_ref().ok().cloned(),
+            error: outcome.as_ref().err().cloned(),
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+        };
+        let changes = if outcome.is_ok() { diff_cards(&before, &card) } else { Vec::new() };
+        ToolAuditService::record_changes(app_handle, &request, &result, changes);
+
+        let result = outcome?;
+        if let Ok(character_data) = Self::get_character(app_handle, uuid) {
+            if let Err(e) = EventBus::character_updated(app_handle, uuid, &character_data, update_type) {
+                eprintln!("发送角色更新事件失败: {}", e);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tools_require_character_uuid() {
+        let tools = McpServer::tool_definitions();
+        let edit = tools
+            .iter()
+            .find(|tool| tool["name"] == "edit_character")
+            .expect("edit_character should be exposed");
+
+        assert!(edit["inputSchema"]["properties"]["uuid"].is_object());
+        assert!(edit["inputSchema"]["required"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("uuid")));
+        assert!(tools.iter().any(|tool| tool["name"] == "list_characters"));
+    }
+
+    #[test]
+    fn test_token_checked_on_initialize() {
+        let initialize = |token: &str| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "token": token },
+            })
+        };
+
+        let initialize_meta = |token: &str| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": MCP_PROTOCOL_VERSION, "_meta": { "token": token } },
+            })
+        };
+
+        assert!(McpServer::token_matches(&initialize("secret"), "secret"));
+        assert!(McpServer::token_matches(&initialize_meta("secret"), "secret"));
+        assert!(!McpServer::token_matches(&initialize_meta("secreT"), "secret"));
+        assert!(!McpServer::token_matches(&initialize("secreT"), "secret"));
+        assert!(!McpServer::token_matches(&initialize(""), "secret"));
+        assert!(!McpServer::token_matches(
+            &serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": { "token": "secret" } }),
+            "secret"
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+use super::api_config::ModelInfo;
+use super::file_utils::FileUtils;
+use super::token_counter::TokenizerRegistry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// 未知模型使用的默认上下文窗口
+pub const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+/// 上下文预算占模型窗口的比例（为输出与计数误差预留空间）
+pub const CONTEXT_BUDGET_RATIO: f64 = 0.8;
+
+/// 模型能力信息
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// 模型标识
+    pub model: String,
+    /// 上下文窗口（Token）
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    /// 最大输出 Token 数
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+    /// 是否支持工具调用
+    #[serde(default)]
+    pub supports_tools: Option<bool>,
+    /// 是否支持图片输入
+    #[serde(default)]
+    pub supports_vision: Option<bool>,
+    /// 分词器标识（如 cl100k_base、o200k_base，或 hf:名称 表示本地 tokenizer.json）
+    #[serde(default)]
+    pub tokenizer: Option<String>,
+}
+
+impl ModelCapabilities {
+    /// 用另一份信息中已设置的字段覆盖当前信息
+    fn merged_with(mut self, other: &ModelCapabilities) -> Self {
+        self.context_window = other.context_window.or(self.context_window);
+        self.max_output_tokens = other.max_output_tokens.or(self.max_output_tokens);
+        self.supports_tools = other.supports_tools.or(self.supports_tools);
+        self.supports_vision = other.supports_vision.or(self.supports_vision);
+        self.tokenizer = other.tokenizer.clone().or(self.tokenizer);
+        self
+    }
+
+    /// 实际使用的上下文窗口
+    pub fn context_window(&self) -> usize {
+        self.context_window.unwrap_or(DEFAULT_CONTEXT_WINDOW)
+    }
+
+    /// 上下文构建可使用的 Token 预算
+    ///
+    /// 取窗口按比例预留后的大小，已知最大输出时不超过窗口减去最大输出，保证提示词与回复之和不超出窗口
+    pub fn context_budget(&self) -> usize {
+        let window = self.context_window();
+        let by_ratio = (window as f64 * CONTEXT_BUDGET_RATIO) as usize;
+        match self.max_output_tokens {
+            Some(max_output) => by_ratio.min(window.saturating_sub(max_output as usize)),
+            None => by_ratio,
+        }
+    }
+
+    /// 根据模型名称推断的内置能力（仅覆盖常见模型系列）
+    pub fn builtin(model: &str) -> ModelCapabilities {
+        let name = model.to_lowercase();
+        let name = name.rsplit('/').next().unwrap_or(&name);
+
+        let (context_window, max_output_tokens, tokenizer) = if name.starts_with("gpt-4.1") {
+            (Some(1_047_576), Some(32_768), Some("o200k_base"))
+        } else if name.starts_with("gpt-4o") || name.starts_with("chatgpt-4o") {
+            (Some(128_000), Some(16_384), Some("o200k_base"))
+        } else if name.starts_with("o1") || name.starts_with("o3") || name.starts_with("o4") {
+            (Some(200_000), Some(100_000), Some("o200k_base"))
+        } else if name.starts_with("gpt-4-turbo") {
+            (Some(128_000), Some(4_096), Some("cl100k_base"))
+        } else if name.starts_with("gpt-4") {
+            (Some(8_192), Some(4_096), Some("cl100k_base"))
+        } else if name.starts_with("gpt-3.5") {
+            (Some(16_385), Some(4_096), Some("cl100k_base"))
+        } else if name.starts_with("claude") {
+            (Some(200_000), None, None)
+        } else if name.starts_with("gemini") {
+            (Some(1_048_576), None, None)
+        } else if name.starts_with("deepseek") {
+            (Some(64_000), Some(8_192), None)
+        } else if name.contains("llama-3") || name.contains("llama3") {
+            (Some(128_000), None, Some("hf:llama3"))
+        } else if name.starts_with("qwen") {
+            (Some(32_768), None, Some("hf:qwen2"))
+        } else if name.starts_with("mistral") || name.starts_with("mixtral") {
+            (Some(32_768), None, Some("hf:mistral"))
+        } else {
+            (None, None, None)
+        };
+
+        ModelCapabilities {
+            model: model.to_string(),
+            context_window,
+            max_output_tokens,
+            supports_tools: None,
+            supports_vision: None,
+            tokenizer: tokenizer.map(str::to_string),
+        }
+    }
+}
+
+impl From<&ModelInfo> for ModelCapabilities {
+    fn from(info: &ModelInfo) -> Self {
+        ModelCapabilities {
+            model: info.id.clone(),
+            context_window: info.context_length.map(|length| length as usize),
+            max_output_tokens: info.max_output_tokens.map(|tokens| tokens as u32),
+            supports_tools: info.supports_tools,
+            supports_vision: info.supports_vision,
+            tokenizer: None,
+        }
+    }
+}
+
+/// 能力注册表文件内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelRegistryData {
+    /// 从模型列表接口获取的能力
+    #[serde(default)]
+    pub discovered: HashMap<String, ModelCapabilities>,
+    /// 用户手动覆盖的能力（优先级最高）
+    #[serde(default)]
+    pub overrides: HashMap<String, ModelCapabilities>,
+}
+
+/// 模型能力注册表服务
+pub struct ModelRegistry;
+
+impl ModelRegistry {
+    fn get_registry_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        let api_dir = app_data_dir.join("api");
+        FileUtils::ensure_dir_exists(&api_dir)?;
+        Ok(api_dir.join("model_capabilities.json"))
+    }
+
+    fn read(app_handle: &tauri::AppHandle) -> Result<ModelRegistryData, String> {
+        let registry_file = Self::get_registry_file(app_handle)?;
+        if !registry_file.exists() {
+            return Ok(ModelRegistryData::default());
+        }
+        FileUtils::read_json_file(&registry_file)
+    }
+
+    fn write(app_handle: &tauri::AppHandle, data: &ModelRegistryData) -> Result<(), String> {
+        let registry_file = Self::get_registry_file(app_handle)?;
+        FileUtils::write_json_file(&registry_file, data)
+    }
+
+    /// 记录模型列表接口返回的能力信息
+    pub fn record_discovered(app_handle: &tauri::AppHandle, models: &[ModelInfo]) -> Result<(), String> {
+        let mut data = Self::read(app_handle)?;
+        for info in models {
+            data.discovered
+                .insert(info.id.clone(), ModelCapabilities::from(info));
+        }
+        Self::write(app_handle, &data)
+    }
+
+    /// 获取模型能力（内置推断 → 接口获取 → 用户覆盖，逐层覆盖）
+    pub fn get(app_handle: &tauri::AppHandle, model: &str) -> Result<ModelCapabilities, String> {
+        let data = Self::read(app_handle)?;
+        Ok(Self::resolve(&data, model))
+    }
+
+    fn resolve(data: &ModelRegistryData, model: &str) -> ModelCapabilities {
+        let mut capabilities = ModelCapabilities::builtin(model);
+        if let Some(discovered) = data.discovered.get(model) {
+            capabilities = capabilities.merged_with(discovered);
+        }
+        if let Some(overrides) = data.overrides.get(model) {
+            capabilities = capabilities.merged_with(overrides);
+        }
+        capabilities
+    }
+
+    /// 列出所有已知模型的能力
+    pub fn list(app_handle: &tauri::AppHandle) -> Result<Vec<ModelCapabilities>, String> {
+        let data = Self::read(app_handle)?;
+        let mut models: Vec<&String> = data.discovered.keys().chain(data.overrides.keys()).collect();
+        models.sort();
+        models.dedup();
+
+        Ok(models
+            .into_iter()
+            .map(|model| Self::resolve(&data, model))
+            .collect())
+    }
+
+    /// 设置用户覆盖
+    pub fn set_override(app_handle: &tauri::AppHandle, capabilities: ModelCapabilities) -> Result<ModelCapabilities, String> {
+        if capabilities.model.trim().is_empty() {
+            return Err("模型标识不能为空".to_string());
+        }
+
+        let mut data = Self::read(app_handle)?;
+        let model = capabilities.model.clone();
+        data.overrides.insert(model.clone(), capabilities);
+        Self::write(app_handle, &data)?;
+        TokenizerRegistry::clear_cache();
+        Ok(Self::resolve(&data, &model))
+    }
+
+    /// 移除用户覆盖
+    pub fn remove_override(app_handle: &tauri::AppHandle, model: &str) -> Result<(), String> {
+        let mut data = Self::read(app_handle)?;
+        if data.overrides.remove(model).is_none() {
+            return Err(format!("模型 '{}' 没有自定义能力配置", model));
+        }
+        Self::write(app_handle, &data)?;
+        TokenizerRegistry::clear_cache();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_layers_overrides_on_discovered() {
+        let mut data = ModelRegistryData::default();
+        data.discovered.insert(
+            "openai/gpt-4o".to_string(),
+            ModelCapabilities {
+                model: "openai/gpt-4o".to_string(),
+                supports_tools: Some(true),
+                ..Default::default()
+            },
+        );
+        data.overrides.insert(
+            "openai/gpt-4o".to_string(),
+            ModelCapabilities {
+                model: "openai/gpt-4o".to_string(),
+                context_window: Some(32_000),
+                ..Default::default()
+            },
+        );
+
+        let resolved = ModelRegistry::resolve(&data, "openai/gpt-4o");
+        assert_eq!(resolved.context_window, Some(32_000));
+        assert_eq!(resolved.max_output_tokens, Some(16_384));
+        assert_eq!(resolved.supports_tools, Some(true));
+        assert_eq!(resolved.tokenizer.as_deref(), Some("o200k_base"));
+        assert_eq!(resolved.context_budget(), 32_000 - 16_384);
+
+        let unknown = ModelRegistry::resolve(&data, "my-local-model");
+        assert_eq!(unknown.context_budget(), 102_400);
+
+        let reasoning = ModelRegistry::resolve(&data, "o3-mini");
+        assert_eq!(reasoning.context_budget(), 200_000 - 100_000);
+        let small_output = ModelRegistry::resolve(&data, "gpt-4o-mini");
+        assert_eq!(small_output.context_budget(), 102_400);
+        let oversized = ModelCapabilities {
+            context_window: Some(8_000),
+            max_output_tokens: Some(10_000),
+            ..Default::default()
+        };
+        assert_eq!(oversized.context_budget(), 0);
+    }
+}
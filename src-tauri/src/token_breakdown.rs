@@ -0,0 +1,196 @@
+use crate::character_storage::CharacterData;
+use crate::token_counter::TokenCounter;
+use serde::{Deserialize, Serialize};
+
+/// 字段的 Token 类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenCategory {
+    /// 每轮都会进入上下文（核心设定、系统提示、常驻世界书条目）
+    Permanent,
+    /// 视情况进入上下文（开场白、示例对话、关键词触发的世界书条目）
+    Conditional,
+    /// 不会发送给模型（作者备注、已禁用的世界书条目）
+    Excluded,
+}
+
+/// 单个字段的 Token 统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldTokenBreakdown {
+    /// 字段路径（如 description、alternate_greetings[0]、character_book.entries[2]）
+    pub path: String,
+    /// 显示名称（世界书条目的名称或首个关键词）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub tokens: usize,
+    pub category: TokenCategory,
+}
+
+/// 角色卡 Token 分布报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardTokenBreakdown {
+    /// 计数使用的分词器
+    pub tokenizer: String,
+    pub fields: Vec<FieldTokenBreakdown>,
+    pub permanent_tokens: usize,
+    pub conditional_tokens: usize,
+    pub total_tokens: usize,
+}
+
+impl CardTokenBreakdown {
+    /// 统计角色卡各字段的 Token 数量（优先使用随角色卡保存的计数缓存）
+    pub fn build(counter: &TokenCounter, character: &CharacterData) -> Self {
+        let card_data = &character.card.data;
+        let fields = card_data.text_fields();
+
+        let counts: Vec<usize> = fields
+            .iter()
+            .map(|(path, text)| {
+                character
+                    .token_counts
+                    .as_ref()
+                    .and_then(|cache| cache.cached(counter.name(), path, text))
+                    .unwrap_or_else(|| counter.count(text))
+            })
+            .collect();
+
+        let entries = card_data
+            .character_book
+            .as_ref()
+            .map(|book| book.entries.as_slice())
+            .unwrap_or_default();
+
+        let mut breakdown = Self {
+            tokenizer: counter.name().to_string(),
+            fields: Vec::with_capacity(fields.len()),
+            permanent_tokens: 0,
+            conditional_tokens: 0,
+            total_tokens: 0,
+        };
+
+        for ((path, _), count) in fields.iter().zip(counts) {
+            let entry = path
+                .strip_prefix("character_book.entries[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| entries.get(index));
+
+            let (category, label) = match entry {
+                Some(entry) => {
+                    let category = if !entry.enabled {
+                        TokenCategory::Excluded
+                    } else if entry.constant.unwrap_or(false) {
+                        TokenCategory::Permanent
+                    } else {
+                        TokenCategory::Conditional
+                    };
+                    let label = entry
+                        .name
+                        .clone()
+                        .or_else(|| entry.comment.clone())
+                        .filter(|label| !label.is_empty())
+                        .or_else(|| entry.keys.first().cloned());
+                    (category, label)
+                }
+                None => (Self::field_category(path), None),
+            };
+
+            match category {
+                TokenCategory::Permanent => breakdown.permanent_tokens += count,
+                TokenCategory::Conditional => breakdown.conditional_tokens += count,
+                TokenCategory::Excluded => {}
+            }
+            breakdown.total_tokens += count;
+
+            breakdown.fields.push(FieldTokenBreakdown {
+                path: path.clone(),
+                label,
+                tokens: count,
+                category,
+            });
+        }
+
+        breakdown
+    }
+
+    /// 角色卡字段（非世界书条目）的类别
+    fn field_category(path: &str) -> TokenCategory {
+        match path {
+            "name" | "description" | "personality" | "scenario" | "system_prompt"
+            | "post_history_instructions" => TokenCategory::Permanent,
+            "creator_notes" => TokenCategory::Excluded,
+            _ => TokenCategory::Conditional,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support;
+    use crate::token_counter::CardTokenCounts;
+
+    #[test]
+    fn test_breakdown_splits_permanent_and_conditional() {
+        let mut card = test_support::card(
+            "A curious girl who follows rabbits.",
+            Some(serde_json::json!([
+                { "keys": ["Wonderland"], "content": "A strange land.", "enabled": true, "insertion_order": 0, "constant": true },
+                { "keys": ["Rabbit"], "content": "Always late.", "enabled": true, "insertion_order": 1 },
+                { "keys": ["Queen"], "content": "Off with their heads.", "enabled": false, "insertion_order": 2 }
+            ])),
+        );
+        card.data.first_mes = "Hello there!".to_string();
+        card.data.creator_notes = "Made for testing.".to_string();
+        card.data.alternate_greetings = vec!["Hi!".to_string()];
+        let character = test_support::character("u", card);
+
+        let counter = TokenCounter::new().unwrap();
+        let report = CardTokenBreakdown::build(&counter, &character);
+        let tokens = |text: &str| counter.count(text);
+
+        assert_eq!(report.tokenizer, "cl100k_base");
+        assert_eq!(
+            report.permanent_tokens,
+            tokens("Alice") + tokens("A curious girl who follows rabbits.") + tokens("A strange land.")
+        );
+        assert_eq!(
+            report.conditional_tokens,
+            tokens("Hello there!") + tokens("Hi!") + tokens("Always late.")
+        );
+
+        let queen = report
+            .fields
+            .iter()
+            .find(|field| field.path == "character_book.entries[2]")
+            .unwrap();
+        assert_eq!(queen.category, TokenCategory::Excluded);
+        assert_eq!(queen.label.as_deref(), Some("Queen"));
+    }
+
+    #[test]
+    fn test_breakdown_reuses_matching_cached_counts() {
+        let counter = TokenCounter::new().unwrap();
+        let mut character = test_support::character("u", test_support::card("A curious girl.", None));
+        let mut cache = CardTokenCounts::default();
+        cache.refresh(&counter, &character.card.data.text_fields());
+        cache.fields.get_mut("name").unwrap().tokens = 99;
+        character.token_counts = Some(cache);
+
+        let tokens = |report: &CardTokenBreakdown, path: &str| {
+            report.fields.iter().find(|field| field.path == path).unwrap().tokens
+        };
+
+        let report = CardTokenBreakdown::build(&counter, &character);
+        assert_eq!(tokens(&report, "name"), 99);
+
+        character.card.data.name = "Alice Liddell".to_string();
+        let report = CardTokenBreakdown::build(&counter, &character);
+        assert_eq!(tokens(&report, "name"), counter.count("Alice Liddell"));
+
+        character.token_counts.as_mut().unwrap().tokenizer = "o200k_base".to_string();
+        character.card.data.name = "Alice".to_string();
+        let report = CardTokenBreakdown::build(&counter, &character);
+        assert_eq!(tokens(&report, "name"), counter.count("Alice"));
+    }
+}
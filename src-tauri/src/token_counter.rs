@@ -1,7 +1,29 @@
+use crate::api_config::ApiConfigService;
+use crate::file_utils::FileUtils;
+use crate::model_registry::ModelRegistry;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use tiktoken_rs::{cl100k_base, CoreBPE};
+use ring::digest;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tiktoken_rs::{cl100k_base, o200k_base, p50k_base, r50k_base, CoreBPE, Rank};
+
+/// 默认分词器
+pub const DEFAULT_TOKENIZER: &str = "cl100k_base";
+
+/// 本地 HuggingFace 分词器标识前缀（如 `hf:llama3` 对应 tokenizers/llama3.json）
+pub const HF_TOKENIZER_PREFIX: &str = "hf:";
+
+/// GPT-2 字节级 BPE 的默认预分词正则
+const BYTE_LEVEL_PATTERN: &str =
+    "'s|'t|'re|'ve|'m|'ll|'d| ?\\p{L}+| ?\\p{N}+| ?[^\\s\\p{L}\\p{N}]+|\\s+(?!\\S)|\\s+";
+
+/// SentencePiece 词表的近似预分词正则（空格并入后一个片段）
+const SENTENCE_PIECE_PATTERN: &str = " ?[^\\s]+|\\s+";
+
+/// 每个分词器最多缓存的计数条数（超出后清空重建）
+const MAX_CACHED_COUNTS: usize = 50_000;
 
 /// Token 计数结果
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,28 +31,170 @@ pub struct TokenCountResult {
     pub text: String,
     pub token_count: usize,
     pub char_count: usize,
+    /// 计数使用的分词器
+    #[serde(default)]
+    pub tokenizer: String,
 }
 
 /// Token 计数服务
 pub struct TokenCounter {
+    /// 分词器标识
+    name: String,
     encoding: CoreBPE,
 }
 
 impl TokenCounter {
-    /// 创建新的 Token 计数器实例
+    /// 创建新的 Token 计数器实例（cl100k_base）
     pub fn new() -> Result<Self, String> {
-        let encoding = cl100k_base().map_err(|e| format!("Failed to load tokenizer: {}", e))?;
-        Ok(Self { encoding })
+        Self::from_encoding(DEFAULT_TOKENIZER)
     }
 
-    /// 计算单个文本的 Token 数量
-    pub fn count_tokens(&self, text: &str) -> TokenCountResult {
+    /// 分词器标识
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 按 tiktoken 内置编码创建计数器
+    pub fn from_encoding(name: &str) -> Result<Self, String> {
+        let encoding = match name {
+            "cl100k_base" => cl100k_base(),
+            "o200k_base" => o200k_base(),
+            "p50k_base" => p50k_base(),
+            "r50k_base" => r50k_base(),
+            _ => return Err(format!("未知的分词器: {}", name)),
+        }
+        .map_err(|e| format!("Failed to load tokenizer: {}", e))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            encoding,
+        })
+    }
+
+    /// 从 HuggingFace tokenizer.json 创建计数器（仅支持 BPE 模型）
+    pub fn from_hf_tokenizer_file(name: &str, path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("读取分词器文件 {} 失败: {}", path.display(), e))?;
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("解析分词器文件 {} 失败: {}", path.display(), e))?;
+        Self::from_hf_tokenizer_json(name, &json)
+    }
+
+    /// 将 HuggingFace BPE 词表转换为 tiktoken 编码（合并顺序作为优先级）
+    pub fn from_hf_tokenizer_json(name: &str, json: &serde_json::Value) -> Result<Self, String> {
+        let model = json.get("model").ok_or("分词器文件缺少 model 字段")?;
+        let model_type = model.get("type").and_then(|t| t.as_str()).unwrap_or("BPE");
+        if model_type != "BPE" {
+            return Err(format!("暂不支持 {} 类型的分词器", model_type));
+        }
+
+        let vocab = model
+            .get("vocab")
+            .and_then(|v| v.as_object())
+            .ok_or("分词器文件缺少词表")?;
+
+        let byte_level = ["pre_tokenizer", "decoder"].iter().any(|key| {
+            json.get(*key)
+                .map(|value| value.to_string().contains("\"ByteLevel\""))
+                .unwrap_or(false)
+        });
+        let byte_decoder = byte_level.then(byte_level_decoder);
+
+        // 合并规则产生的 Token 按合并顺序排列，其余基础 Token 排在前面
+        let mut merge_ranks: HashMap<String, usize> = HashMap::new();
+        if let Some(merges) = model.get("merges").and_then(|m| m.as_array()) {
+            for (index, merge) in merges.iter().enumerate() {
+                let merged = match merge {
+                    serde_json::Value::String(pair) => pair.replacen(' ', "", 1),
+                    serde_json::Value::Array(parts) => parts
+                        .iter()
+                        .filter_map(|part| part.as_str())
+                        .collect::<String>(),
+                    _ => continue,
+                };
+                merge_ranks.entry(merged).or_insert(index);
+            }
+        }
+
+        let mut entries: Vec<(bool, u64, &String)> = vocab
+            .iter()
+            .filter_map(|(token, id)| {
+                let id = id.as_u64()?;
+                Some(match merge_ranks.get(token) {
+                    Some(index) => (true, *index as u64, token),
+                    None => (false, id, token),
+                })
+            })
+            .collect();
+        entries.sort();
+
+        let mut encoder: HashMap<Vec<u8>, Rank> = HashMap::new();
+        for (_, _, token) in entries {
+            let Some(bytes) = decode_vocab_token(token, byte_decoder.as_ref()) else {
+                continue;
+            };
+            let rank = encoder.len() as Rank;
+            encoder.entry(bytes).or_insert(rank);
+        }
+
+        // tiktoken 要求所有单字节都可编码
+        for byte in 0..=255u8 {
+            let rank = encoder.len() as Rank;
+            encoder.entry(vec![byte]).or_insert(rank);
+        }
+
+        let pattern = find_split_pattern(json.get("pre_tokenizer").unwrap_or(&serde_json::Value::Null))
+            .unwrap_or_else(|| {
+                if byte_level {
+                    BYTE_LEVEL_PATTERN.to_string()
+                } else {
+                    SENTENCE_PIECE_PATTERN.to_string()
+                }
+            });
+
+        let encoding = CoreBPE::new(encoder.into_iter().collect(), Default::default(), &pattern)
+            .map_err(|e| format!("构建分词器 {} 失败: {}", name, e))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            encoding,
+        })
+    }
+
+    /// 计算 Token 数量（按内容哈希缓存，不同分词器互不影响）
+    pub fn count(&self, text: &str) -> usize {
+        let hash = content_hash(text);
+
+        if let Some(count) = TOKEN_COUNT_CACHE
+            .read()
+            .unwrap()
+            .get(&self.name)
+            .and_then(|counts| counts.get(&hash))
+        {
+            return *count;
+        }
+
         let allowed_special = HashSet::new(); // 不允许任何特殊token
         let (tokens, _token_count) = self.encoding.encode(text, &allowed_special);
+        let count = tokens.len();
+
+        let mut cache = TOKEN_COUNT_CACHE.write().unwrap();
+        let counts = cache.entry(self.name.clone()).or_default();
+        if counts.len() >= MAX_CACHED_COUNTS {
+            counts.clear();
+        }
+        counts.insert(hash, count);
+
+        count
+    }
+
+    /// 计算单个文本的 Token 数量
+    pub fn count_tokens(&self, text: &str) -> TokenCountResult {
         TokenCountResult {
             text: text.to_string(),
-            token_count: tokens.len(),
+            token_count: self.count(text),
             char_count: text.chars().count(),
+            tokenizer: self.name.clone(),
         }
     }
 
@@ -44,9 +208,7 @@ impl TokenCounter {
 
     /// 检查文本是否超出 Token 限制
     pub fn is_within_limit(&self, text: &str, limit: usize) -> bool {
-        let allowed_special = HashSet::new();
-        let (tokens, _token_count) = self.encoding.encode(text, &allowed_special);
-        tokens.len() <= limit
+        self.count(text) <= limit
     }
 
     /// 截断文本以符合 Token 限制
@@ -72,11 +234,306 @@ impl Default for TokenCounter {
     }
 }
 
+/// 文本内容哈希（SHA-256）
+pub fn content_hash(text: &str) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(digest::digest(&digest::SHA256, text.as_bytes()).as_ref());
+    hash
+}
+
+/// 单个字段的 Token 计数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldTokenCount {
+    /// 字段内容哈希（十六进制）
+    pub hash: String,
+    pub tokens: usize,
+}
+
+/// 角色卡各字段的 Token 计数（随角色卡保存，内容或分词器变化时重新计数）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CardTokenCounts {
+    /// 计数使用的分词器
+    pub tokenizer: String,
+    /// 字段路径 → 计数（如 description、alternate_greetings[0]、character_book.entries[2]）
+    pub fields: BTreeMap<String, FieldTokenCount>,
+}
+
+/// 字段内容哈希的十六进制形式
+fn content_hash_hex(text: &str) -> String {
+    content_hash(text).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl CardTokenCounts {
+    /// 缓存的字段计数（分词器与字段内容均未变化时）
+    pub fn cached(&self, tokenizer: &str, path: &str, text: &str) -> Option<usize> {
+        if self.tokenizer != tokenizer {
+            return None;
+        }
+        self.fields
+            .get(path)
+            .filter(|cached| cached.hash == content_hash_hex(text))
+            .map(|cached| cached.tokens)
+    }
+
+    /// 按当前字段内容刷新计数，仅重新计算有变化的字段；返回是否有更新
+    pub fn refresh(&mut self, counter: &TokenCounter, fields: &[(String, &str)]) -> bool {
+        let tokenizer_changed = self.tokenizer != counter.name;
+        if tokenizer_changed {
+            self.tokenizer = counter.name.clone();
+        }
+
+        let mut fields_map = BTreeMap::new();
+        let mut changed = tokenizer_changed || fields.len() != self.fields.len();
+
+        for (path, text) in fields {
+            let hash = content_hash_hex(text);
+
+            let count = match self.fields.get(path) {
+                Some(cached) if !tokenizer_changed && cached.hash == hash => cached.clone(),
+                _ => {
+                    changed = true;
+                    FieldTokenCount {
+                        hash,
+                        tokens: counter.count(text),
+                    }
+                }
+            };
+            fields_map.insert(path.clone(), count);
+        }
+
+        self.fields = fields_map;
+        changed
+    }
+}
+
+/// GPT-2 字节级 BPE 中 可见字符 → 原始字节 的映射
+fn byte_level_decoder() -> HashMap<char, u8> {
+    let mut bytes: Vec<u8> = (b'!'..=b'~').chain(0xA1..=0xAC).chain(0xAE..=0xFF).collect();
+    let mut chars: Vec<u32> = bytes.iter().map(|byte| *byte as u32).collect();
+
+    let mut extra = 0;
+    for byte in 0..=255u8 {
+        if !bytes.contains(&byte) {
+            bytes.push(byte);
+            chars.push(256 + extra);
+            extra += 1;
+        }
+    }
+
+    chars
+        .into_iter()
+        .filter_map(char::from_u32)
+        .zip(bytes)
+        .collect()
+}
+
+/// 还原词表 Token 对应的原始字节
+fn decode_vocab_token(token: &str, byte_decoder: Option<&HashMap<char, u8>>) -> Option<Vec<u8>> {
+    if let Some(decoder) = byte_decoder {
+        return token.chars().map(|c| decoder.get(&c).copied()).collect();
+    }
+
+    // SentencePiece：<0xNN> 为字节回退，▁ 表示空格
+    if let Some(hex) = token.strip_prefix("<0x").and_then(|t| t.strip_suffix('>')) {
+        return u8::from_str_radix(hex, 16).ok().map(|byte| vec![byte]);
+    }
+    Some(token.replace('\u{2581}', " ").into_bytes())
+}
+
+/// 查找预分词器中的 Split 正则
+fn find_split_pattern(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.get("type").and_then(|t| t.as_str()) == Some("Split") {
+                if let Some(regex) = map
+                    .get("pattern")
+                    .and_then(|p| p.get("Regex"))
+                    .and_then(|r| r.as_str())
+                {
+                    return Some(regex.to_string());
+                }
+            }
+            map.values().find_map(find_split_pattern)
+        }
+        serde_json::Value::Array(items) => items.iter().find_map(find_split_pattern),
+        _ => None,
+    }
+}
+
 /// 全局 Token 计数器实例
-static TOKEN_COUNTER: Lazy<TokenCounter> =
-    Lazy::new(|| TokenCounter::new().expect("Failed to initialize TokenCounter"));
+static TOKEN_COUNTER: Lazy<Arc<TokenCounter>> =
+    Lazy::new(|| Arc::new(TokenCounter::new().expect("Failed to initialize TokenCounter")));
+
+/// 内容哈希 → Token 数
+type CountCache = HashMap<[u8; 32], usize>;
+
+/// Token 计数缓存（按分词器标识分组）
+static TOKEN_COUNT_CACHE: Lazy<RwLock<HashMap<String, CountCache>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 已加载的分词器（按标识缓存）
+static TOKENIZER_CACHE: Lazy<RwLock<HashMap<String, Arc<TokenCounter>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
 
 /// 获取全局 Token 计数器实例
 pub fn get_token_counter() -> &'static TokenCounter {
     &TOKEN_COUNTER
 }
+
+/// 分词器注册表 - 按模型选择分词器
+pub struct TokenizerRegistry;
+
+impl TokenizerRegistry {
+    /// 本地分词器目录（存放 HuggingFace tokenizer.json）
+    fn get_tokenizers_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        let tokenizers_dir = app_data_dir.join("tokenizers");
+        FileUtils::ensure_dir_exists(&tokenizers_dir)?;
+        Ok(tokenizers_dir)
+    }
+
+    /// 解析本地分词器文件路径：tokenizers/{name}.json 或 tokenizers/{name}/tokenizer.json
+    fn resolve_hf_path(app_handle: &tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
+        let tokenizers_dir = Self::get_tokenizers_dir(app_handle)?;
+        [
+            tokenizers_dir.join(format!("{}.json", name)),
+            tokenizers_dir.join(name).join("tokenizer.json"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("未找到本地分词器 '{}'", name))
+    }
+
+    fn load(app_handle: &tauri::AppHandle, tokenizer: &str) -> Result<TokenCounter, String> {
+        match tokenizer.strip_prefix(HF_TOKENIZER_PREFIX) {
+            Some(name) => {
+                let path = Self::resolve_hf_path(app_handle, name)?;
+                TokenCounter::from_hf_tokenizer_file(tokenizer, &path)
+            }
+            None => TokenCounter::from_encoding(tokenizer),
+        }
+    }
+
+    /// 获取指定分词器（未指定或加载失败时使用 cl100k_base）
+    pub fn get(app_handle: &tauri::AppHandle, tokenizer: Option<&str>) -> Arc<TokenCounter> {
+        let Some(tokenizer) = tokenizer.map(str::trim).filter(|t| !t.is_empty()) else {
+            return TOKEN_COUNTER.clone();
+        };
+
+        if let Some(counter) = TOKENIZER_CACHE.read().unwrap().get(tokenizer) {
+            return counter.clone();
+        }
+
+        let counter = match Self::load(app_handle, tokenizer) {
+            Ok(counter) => Arc::new(counter),
+            Err(e) => {
+                eprintln!("加载分词器 {} 失败，使用 {}: {}", tokenizer, DEFAULT_TOKENIZER, e);
+                TOKEN_COUNTER.clone()
+            }
+        };
+
+        TOKENIZER_CACHE
+            .write()
+            .unwrap()
+            .insert(tokenizer.to_string(), counter.clone());
+        counter
+    }
+
+    /// 获取模型对应的分词器
+    pub fn for_model(app_handle: &tauri::AppHandle, model: &str) -> Result<Arc<TokenCounter>, String> {
+        let capabilities = ModelRegistry::get(app_handle, model)?;
+        Ok(Self::get(app_handle, capabilities.tokenizer.as_deref()))
+    }
+
+    /// 获取当前默认 API 配置模型的分词器
+    pub fn for_active_model(app_handle: &tauri::AppHandle) -> Result<Arc<TokenCounter>, String> {
+        match ApiConfigService::get_default_model(app_handle)? {
+            Some(model) => Self::for_model(app_handle, &model),
+            None => Ok(TOKEN_COUNTER.clone()),
+        }
+    }
+
+    /// 列出可用分词器（内置编码与本地 HuggingFace 分词器）
+    pub fn list(app_handle: &tauri::AppHandle) -> Result<Vec<String>, String> {
+        let mut tokenizers: Vec<String> = ["cl100k_base", "o200k_base", "p50k_base", "r50k_base"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let tokenizers_dir = Self::get_tokenizers_dir(app_handle)?;
+        let entries = std::fs::read_dir(&tokenizers_dir)
+            .map_err(|e| format!("读取分词器目录失败: {}", e))?;
+
+        let mut local: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.is_dir() && path.join("tokenizer.json").is_file() {
+                    path.file_name()?.to_str().map(str::to_string)
+                } else if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                    path.file_stem()?.to_str().map(str::to_string)
+                } else {
+                    None
+                }
+            })
+            .map(|name| format!("{}{}", HF_TOKENIZER_PREFIX, name))
+            .collect();
+        local.sort();
+        tokenizers.extend(local);
+
+        Ok(tokenizers)
+    }
+
+    /// 清空已加载的分词器及其计数缓存（本地文件或模型能力变更后调用）
+    pub fn clear_cache() {
+        TOKENIZER_CACHE.write().unwrap().clear();
+        TOKEN_COUNT_CACHE.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hf_byte_level_bpe_follows_merges() {
+        let json = serde_json::json!({
+            "pre_tokenizer": { "type": "ByteLevel", "add_prefix_space": false },
+            "model": {
+                "type": "BPE",
+                "vocab": { "a": 0, "b": 1, "\u{0120}": 2, "ab": 3, "\u{0120}a": 4, "\u{0120}ab": 5 },
+                "merges": ["a b", "\u{0120} a", ["\u{0120}a", "b"]]
+            }
+        });
+
+        let counter = TokenCounter::from_hf_tokenizer_json("hf:test", &json).unwrap();
+        let result = counter.count_tokens("ab ab");
+        assert_eq!(result.tokenizer, "hf:test");
+        assert_eq!(result.token_count, 2);
+        assert_eq!(counter.count_tokens("abc").token_count, 2);
+        assert_eq!(counter.truncate_to_limit("ab ab ab", 2), "ab ab");
+    }
+
+    #[test]
+    fn test_card_counts_refresh_only_changed_fields() {
+        let counter = TokenCounter::new().unwrap();
+        let mut counts = CardTokenCounts::default();
+
+        let fields = vec![
+            ("name".to_string(), "Alice"),
+            ("description".to_string(), "A curious girl."),
+        ];
+        assert!(counts.refresh(&counter, &fields));
+        assert_eq!(counts.tokenizer, DEFAULT_TOKENIZER);
+        assert!(!counts.refresh(&counter, &fields));
+
+        let description = counts.fields["description"].clone();
+        let fields = vec![
+            ("name".to_string(), "Alice Liddell"),
+            ("description".to_string(), "A curious girl."),
+        ];
+        assert!(counts.refresh(&counter, &fields));
+        assert_eq!(counts.fields["description"], description);
+        assert_eq!(counts.fields["name"].tokens, counter.count("Alice Liddell"));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::ToolApprovalRequestedPayload;
+use crate::card_diff::FieldChange;
+use crate::file_utils::FileUtils;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// 单个工具的审批策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalPolicy {
+    /// 自动执行
+    Auto,
+    /// 执行前等待用户确认
+    Ask,
+    /// 禁止执行
+    Deny,
+}
+
+/// 工具调用审批配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolApprovalConfig {
+    /// 是否启用审批模式（关闭时所有工具自动执行）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 未单独配置的修改类工具使用的策略
+    #[serde(default = "default_policy")]
+    pub default_policy: ApprovalPolicy,
+    /// 只读工具（不修改角色卡）是否自动执行
+    #[serde(default = "default_true")]
+    pub auto_approve_read_only: bool,
+    /// 按工具名称单独配置的策略
+    #[serde(default)]
+    pub tools: HashMap<String, ApprovalPolicy>,
+    /// 等待用户确认的超时时间（秒），超时视为拒绝
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_policy() -> ApprovalPolicy {
+    ApprovalPolicy::Ask
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+impl Default for ToolApprovalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_policy: default_policy(),
+            auto_approve_read_only: true,
+            tools: HashMap::new(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl ToolApprovalConfig {
+    /// 工具调用适用的审批策略
+    pub fn policy_for(&self, tool_name: &str, modifies_character: bool) -> ApprovalPolicy {
+        if !self.enabled {
+            return ApprovalPolicy::Auto;
+        }
+        if let Some(policy) = self.tools.get(tool_name) {
+            return *policy;
+        }
+        if !modifies_character && self.auto_approve_read_only {
+            return ApprovalPolicy::Auto;
+        }
+        self.default_policy
+    }
+}
+
+/// 用户对审批请求的回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum ApprovalDecision {
+    /// 按原参数执行
+    Approve,
+    /// 拒绝执行（原因会告知模型）
+    Reject {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// 使用用户修改后的参数执行
+    Edit { arguments: HashMap<String, Value> },
+}
+
+/// 等待回复的审批请求
+struct PendingApproval {
+    payload: ToolApprovalRequestedPayload,
+    sender: oneshot::Sender<ApprovalDecision>,
+}
+
+static PENDING_APPROVALS: Lazy<Mutex<HashMap<String, PendingApproval>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 工具调用审批服务
+pub struct ToolApprovalService;
+
+impl ToolApprovalService {
+    fn get_config_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir.join("tool_approval.json"))
+    }
+
+    /// 读取审批配置
+    pub fn get_config(app_handle: &tauri::AppHandle) -> Result<ToolApprovalConfig, String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        if !config_file.exists() {
+            return Ok(ToolApprovalConfig::default());
+        }
+        FileUtils::read_json_file(&config_file)
+    }
+
+    /// 保存审批配置
+    pub fn save_config(app_handle: &tauri::AppHandle, config: &ToolApprovalConfig) -> Result<(), String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        FileUtils::write_json_file(&config_file, config)
+    }
+
+    /// 发出审批请求并等待用户回复（超时视为拒绝）
+    pub async fn request_approval(
+        app_handle: &tauri::AppHandle,
+        character_uuid: &str,
+        tool_name: &str,
+        arguments: &HashMap<String, Value>,
+        preview: Result<Vec<FieldChange>, String>,
+        timeout: Duration,
+    ) -> ApprovalDecision {
+        let approval_id = FileUtils::generate_uuid();
+        let (changes, preview_error) = match preview {
+            Ok(changes) => (changes, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+
+        let payload = ToolApprovalRequestedPayload {
+            uuid: character_uuid.to_string(),
+            approval_id: approval_id.clone(),
+            tool_name: tool_name.to_string(),
+            arguments: serde_json::to_value(arguments).unwrap_or(Value::Null),
+            changes,
+            preview_error,
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        let (sender, receiver) = oneshot::channel();
+        PENDING_APPROVALS.lock().unwrap().insert(
+            approval_id.clone(),
+            PendingApproval {
+                payload: payload.clone(),
+                sender,
+            },
+        );
+
+        if let Err(e) = EventBus::tool_approval_requested(app_handle, &payload) {
+            eprintln!("{}", e);
+        }
+
+        let decision = match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(decision)) => decision,
+            Ok(Err(_)) => ApprovalDecision::Reject {
+                reason: Some("审批请求已取消".to_string()),
+            },
+            Err(_) => ApprovalDecision::Reject {
+                reason: Some("等待用户确认超时".to_string()),
+            },
+        };
+
+        PENDING_APPROVALS.lock().unwrap().remove(&approval_id);
+        decision
+    }
+
+    /// 回复审批请求
+    pub fn respond(approval_id: &str, decision: ApprovalDecision) -> Result<(), String> {
+        let pending = PENDING_APPROVALS
+            .lock()
+            .unwrap()
+            .remove(approval_id)
+            .ok_or_else(|| format!("审批请求 {} 不存在或已过期", approval_id))?;
+
+        pending
+            .sender
+            .send(decision)
+            .map_err(|_| format!("审批请求 {} 已结束", approval_id))
+    }
+
+    /// 列出等待回复的审批请求（前端重新加载后恢复显示）
+    pub fn list_pending() -> Vec<ToolApprovalRequestedPayload> {
+        let mut pending: Vec<ToolApprovalRequestedPayload> = PENDING_APPROVALS
+            .lock()
+            .unwrap()
+            .values()
+            .map(|pending| pending.payload.clone())
+            .collect();
+        pending.sort_by_key(|payload| payload.timestamp);
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_resolution() {
+        let mut config = ToolApprovalConfig::default();
+        assert_eq!(config.policy_for("edit_character", true), ApprovalPolicy::Auto);
+
+        config.enabled = true;
+        config.tools.insert("create_world_book_entry".to_string(), ApprovalPolicy::Deny);
+        assert_eq!(config.policy_for("edit_character", true), ApprovalPolicy::Ask);
+        assert_eq!(config.policy_for("create_world_book_entry", true), ApprovalPolicy::Deny);
+        assert_eq!(config.policy_for("lookup", false), ApprovalPolicy::Auto);
+
+        config.auto_approve_read_only = false;
+        assert_eq!(config.policy_for("lookup", false), ApprovalPolicy::Ask);
+
+        let decision: ApprovalDecision =
+            serde_json::from_str(r#"{"decision":"edit","arguments":{"name":"Bob"}}"#).unwrap();
+        assert!(matches!(decision, ApprovalDecision::Edit { arguments } if arguments["name"] == "Bob"));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::ai_tools::{ToolCallRequest, ToolResult};
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::CharacterUpdateType;
+use crate::card_diff::{diff_cards, FieldChange};
+use crate::character_storage::{CharacterStorage, TavernCardV2};
+use crate::file_utils::FileUtils;
+use crate::tools::CharacterLock;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 工具调用审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAuditRecord {
+    pub id: String,
+    pub timestamp: i64,
+    pub tool_name: String,
+    /// 发起调用的会话（外部调用为空）
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub arguments: HashMap<String, Value>,
+    pub success: bool,
+    #[serde(default)]
+    pub result: Option<Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    /// 调用修改的角色卡字段（修改前后的值）
+    #[serde(default)]
+    pub changes: Vec<FieldChange>,
+    /// 撤销记录对应的原调用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<String>,
+}
+
+/// 审计日志查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolAuditQuery {
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    /// 起始时间（含）
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// 截止时间（含）
+    #[serde(default)]
+    pub until: Option<i64>,
+    /// 只返回修改了角色卡的调用
+    #[serde(default)]
+    pub changes_only: bool,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl ToolAuditQuery {
+    fn matches(&self, record: &ToolAuditRecord) -> bool {
+        self.tool_name.as_ref().is_none_or(|name| &record.tool_name == name)
+            && self.session.as_ref().is_none_or(|session| record.session.as_ref() == Some(session))
+            && self.success.is_none_or(|success| record.success == success)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+            && (!self.changes_only || !record.changes.is_empty())
+    }
+}
+
+/// 串行化日志追加，避免并发调用交错写入
+static AUDIT_WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 工具调用审计日志服务（每个角色一份只追加的 JSONL 文件）
+pub struct ToolAuditService;
+
+impl ToolAuditService {
+    fn get_audit_file(app_handle: &tauri::AppHandle, uuid: &str) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir
+            .join("character-cards")
+            .join(uuid)
+            .join("tool_audit.jsonl"))
+    }
+
+    fn append(app_handle: &tauri::AppHandle, uuid: &str, record: &ToolAuditRecord) -> Result<(), String> {
+        let audit_file = Self::get_audit_file(app_handle, uuid)?;
+        let line = serde_json::to_string(record).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+
+        let _write_guard = AUDIT_WRITE_LOCK.lock().unwrap();
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&audit_file)
+            .map_err(|e| format!("打开审计日志失败: {}", e))?
+            .write_all((line + "\n").as_bytes())
+            .map_err(|e| format!("写入审计日志失败: {}", e))
+    }
+
+    fn load(app_handle: &tauri::AppHandle, uuid: &str) -> Result<Vec<ToolAuditRecord>, String> {
+        let audit_file = Self::get_audit_file(app_handle, uuid)?;
+        if !audit_file.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&audit_file).map_err(|e| format!("读取审计日志失败: {}", e))?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    eprintln!("跳过无法解析的审计记录: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// 修改类工具执行前的角色卡快照（用于计算修改前后的值）
+    pub fn snapshot(app_handle: &tauri::AppHandle, request: &ToolCallRequest) -> Option<TavernCardV2> {
+        let uuid = request.character_uuid.as_deref()?;
+        CharacterStorage::get_character_by_uuid(app_handle, uuid)
+            .ok()
+            .flatten()
+            .map(|data| data.card)
+    }
+
+    /// 记录一次工具调用（应在持有角色锁时调用，确保修改前后的值只来自本次调用）
+    pub fn record(
+        app_handle: &tauri::AppHandle,
+        request: &ToolCallRequest,
+        result: &ToolResult,
+        before: Option<&TavernCardV2>,
+    ) {
+        let changes = before
+            .zip(Self::snapshot(app_handle, request))
+            .map(|(before, after)| diff_cards(before, &after))
+            .unwrap_or_default();
+        Self::record_changes(app_handle, request, result, changes);
+    }
+
+    /// 记录已知修改内容的调用（应用提议修改、本地 MCP 服务器的编辑等不经过工具中心执行的修改）
+    pub fn record_changes(
+        app_handle: &tauri::AppHandle,
+        request: &ToolCallRequest,
+        result: &ToolResult,
+        changes: Vec<FieldChange>,
+    ) {
+        let Some(uuid) = request.character_uuid.as_deref() else {
+            return;
+        };
+
+        let origin = request.origin.clone().unwrap_or_default();
+        let record = ToolAuditRecord {
+            id: FileUtils::generate_uuid(),
+            timestamp: chrono::Utc::now().timestamp(),
+            tool_name: request.tool_name.clone(),
+            session: origin.session,
+            model: origin.model,
+            arguments: request.parameters.clone(),
+            success: result.success,
+            result: result.data.clone(),
+            error: result.error.clone(),
+            duration_ms: result.execution_time_ms,
+            changes,
+            reverts: None,
+        };
+
+        if let Err(e) = Self::append(app_handle, uuid, &record) {
+            eprintln!("{}", e);
+        }
+    }
+
+    /// 查询审计日志（按时间倒序）
+    pub fn query(
+        app_handle: &tauri::AppHandle,
+        uuid: &str,
+        query: &ToolAuditQuery,
+    ) -> Result<Vec<ToolAuditRecord>, String> {
+        Ok(Self::load(app_handle, uuid)?
+            .into_iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// 撤销单次工具调用：将其修改的字段恢复为调用前的值
+    ///
+    /// 字段在之后又被修改时视为冲突，`force` 为 false 时不撤销
+    pub async fn revert(
+        app_handle: &tauri::AppHandle,
+        uuid: &str,
+        record_id: &str,
+        force: bool,
+    ) -> Result<ToolAuditRecord, String> {
+        let _character_guard = CharacterLock::acquire(uuid).await;
+
+        let records = Self::load(app_handle, uuid)?;
+        let record = records
+            .iter()
+            .find(|record| record.id == record_id)
+            .ok_or_else(|| format!("审计记录 {} 不存在", record_id))?;
+        if record.changes.is_empty() {
+            return Err("该工具调用没有修改角色卡".to_string());
+        }
+        if records.iter().any(|r| r.reverts.as_deref() == Some(record_id)) {
+            return Err("该工具调用已撤销".to_string());
+        }
+
+        let start_time = std::time::Instant::now();
+        let before = CharacterStorage::get_character_by_uuid(app_handle, uuid)?
+            .ok_or_else(|| "角色不存在".to_string())?
+            .card;
+        let mut card = before.clone();
+        revert_changes(&mut card, &record.changes, force)?;
+
+        CharacterStorage::update_character(app_handle, uuid, &card)?;
+        if let Some(character_data) = CharacterStorage::get_character_by_uuid(app_handle, uuid)? {
+            if let Err(e) =
+                EventBus::character_updated(app_handle, uuid, &character_data, CharacterUpdateType::FullData)
+            {
+                eprintln!("发送角色更新事件失败: {}", e);
+            }
+        }
+
+        let revert_record = ToolAuditRecord {
+            id: FileUtils::generate_uuid(),
+            timestamp: chrono::Utc::now().timestamp(),
+            tool_name: record.tool_name.clone(),
+            session: None,
+            model: None,
+            arguments: HashMap::new(),
+            success: true,
+            result: None,
+            error: None,
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            changes: diff_cards(&before, &card),
+            reverts: Some(record_id.to_string()),
+        };
+        Self::append(app_handle, uuid, &revert_record)?;
+        Ok(revert_record)
+    }
+}
+
+/// 按字段路径将修改恢复为修改前的值（倒序处理，保证世界书条目下标有效）
+fn revert_changes(card: &mut TavernCardV2, changes: &[FieldChange], force: bool) -> Result<(), String> {
+    let mut data = serde_json::to_value(&card.data).map_err(|e| e.to_string())?;
+
+    if !force {
+        let conflicts: Vec<&str> = changes
+            .iter()
+            .filter(|change| get_path(&data, &change.path) != change.after.as_ref())
+            .map(|change| change.path.as_str())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(format!("以下字段在此后已被修改: {}", conflicts.join(", ")));
+        }
+    }
+
+    for change in changes.iter().rev() {
+        set_path(&mut data, &change.path, change.before.clone(), change.after.is_none())?;
+    }
+    card.data = serde_json::from_value(data).map_err(|e| format!("恢复后的角色卡无效: {}", e))?;
+    Ok(())
+}
+
+/// 解析 `character_book.entries[3]` 形式的条目路径
+fn entry_index(path: &str) -> Option<usize> {
+    path.strip_prefix("character_book.entries[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+fn get_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    let value = match entry_index(path) {
+        Some(index) => data.get("character_book")?.get("entries")?.get(index),
+        None => match path.strip_prefix("character_book.") {
+            Some(key) => data.get("character_book")?.get(key),
+            None => data.get(path),
+        },
+    };
+    value.filter(|value| !value.is_null())
+}
+
+/// 设置字段；`insert` 表示条目在调用中被删除，需要插回原位置
+fn set_path(data: &mut Value, path: &str, value: Option<Value>, insert: bool) -> Result<(), String> {
+    let missing = || format!("字段 {} 不存在", path);
+
+    if let Some(index) = entry_index(path) {
+        let entries = data
+            .get_mut("character_book")
+            .and_then(|book| book.get_mut("entries"))
+            .and_then(Value::as_array_mut)
+            .ok_or_else(missing)?;
+        return match value {
+            Some(entry) if insert => {
+                entries.insert(index.min(entries.len()), entry);
+                Ok(())
+            }
+            Some(entry) => {
+                *entries.get_mut(index).ok_or_else(missing)? = entry;
+                Ok(())
+            }
+            None if index < entries.len() => {
+                entries.remove(index);
+                Ok(())
+            }
+            None => Err(missing()),
+        };
+    }
+
+    let (target, key) = match path.strip_prefix("character_book.") {
+        Some(key) => (data.get_mut("character_book").ok_or_else(missing)?, key),
+        None => (data, path),
+    };
+    let target = target.as_object_mut().ok_or_else(missing)?;
+    target.insert(key.to_string(), value.unwrap_or(Value::Null));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support::card;
+
+    #[test]
+    fn test_revert_restores_fields_and_detects_conflicts() {
+        let entry = serde_json::json!({ "keys": ["k"], "content": "c", "enabled": true, "insertion_order": 1 });
+        let before = card("old", Some(serde_json::json!([])));
+        let after = card("new", Some(serde_json::json!([entry.clone(), entry])));
+        let changes = diff_cards(&before, &after);
+
+        let mut reverted = after.clone();
+        revert_changes(&mut reverted, &changes, false).unwrap();
+        assert!(diff_cards(&before, &reverted).is_empty());
+
+        let mut edited = card("edited later", Some(serde_json::json!([])));
+        assert!(revert_changes(&mut edited, &changes, false)
+            .unwrap_err()
+            .contains("description"));
+        let mut later = after.clone();
+        later.data.description = "edited later".to_string();
+        revert_changes(&mut later, &changes, true).unwrap();
+        assert_eq!(later.data.description, "old");
+    }
+}
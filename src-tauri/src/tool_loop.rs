@@ -0,0 +1,255 @@
+use crate::ai_config::AIRole;
+use crate::ai_tools::ToolResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 默认的工具调用循环轮数上限
+pub const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 5;
+/// 默认允许完全相同的工具调用（名称与参数均相同）执行的次数
+pub const DEFAULT_MAX_IDENTICAL_CALLS: u32 = 2;
+
+/// 工具调用循环策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolLoopPolicy {
+    /// 单次回复最多请求模型的轮数（包含工具调用后的续轮）
+    pub max_iterations: u32,
+    /// 相同调用允许执行的次数，超出后不再执行并返回 repeated_call 错误
+    pub max_identical_calls: u32,
+}
+
+impl Default for ToolLoopPolicy {
+    fn default() -> Self {
+        Self {
+            max_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            max_identical_calls: DEFAULT_MAX_IDENTICAL_CALLS,
+        }
+    }
+}
+
+impl ToolLoopPolicy {
+    /// 按 AI 角色配置的轮数上限创建策略
+    pub fn for_role(role: Option<&AIRole>) -> Self {
+        let mut policy = Self::default();
+        if let Some(role) = role {
+            policy.max_iterations = role.max_tool_iterations.max(1);
+        }
+        policy
+    }
+}
+
+/// 工具错误类别（模型可据此决定如何修正）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// 参数不是合法的 JSON 对象
+    InvalidArguments,
+    /// 工具不存在
+    UnknownTool,
+    /// 工具执行失败
+    ExecutionFailed,
+    /// 重复的相同调用，已跳过执行
+    RepeatedCall,
+    /// 用户拒绝或审批策略禁止执行
+    Rejected,
+}
+
+/// 结构化的工具错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallError {
+    pub kind: ToolErrorKind,
+    pub message: String,
+    /// 给模型的修正建议
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl ToolCallError {
+    pub fn invalid_arguments(err: impl std::fmt::Display) -> Self {
+        Self {
+            kind: ToolErrorKind::InvalidArguments,
+            message: format!("Invalid tool arguments: {}", err),
+            hint: Some("Pass the arguments as a single JSON object matching the tool schema.".to_string()),
+        }
+    }
+
+    pub fn unknown_tool(tool_name: &str) -> Self {
+        Self {
+            kind: ToolErrorKind::UnknownTool,
+            message: format!("Unknown tool: {}", tool_name),
+            hint: Some("Only call tools listed in the tool definitions.".to_string()),
+        }
+    }
+
+    pub fn execution_failed(message: impl Into<String>) -> Self {
+        Self {
+            kind: ToolErrorKind::ExecutionFailed,
+            message: message.into(),
+            hint: Some("Check the parameters against the error and retry with corrected values, or explain the problem to the user.".to_string()),
+        }
+    }
+
+    pub fn repeated_call(tool_name: &str, times: u32) -> Self {
+        Self {
+            kind: ToolErrorKind::RepeatedCall,
+            message: format!("Identical call to {} was already made {} time(s) and was not executed again", tool_name, times),
+            hint: Some("Use the earlier result instead of repeating the call, or change the arguments.".to_string()),
+        }
+    }
+
+    pub fn rejected(tool_name: &str, reason: Option<&str>) -> Self {
+        let message = match reason {
+            Some(reason) if !reason.trim().is_empty() => {
+                format!("Call to {} was rejected by the user: {}", tool_name, reason.trim())
+            }
+            _ => format!("Call to {} was rejected by the user", tool_name),
+        };
+        Self {
+            kind: ToolErrorKind::Rejected,
+            message,
+            hint: Some("Do not retry the same change; ask the user how to proceed or adjust the change according to the reason.".to_string()),
+        }
+    }
+}
+
+/// 单个工具调用的执行结果（作为 tool 消息发送回模型）
+#[derive(Debug, Clone)]
+pub struct ToolCallOutcome {
+    pub data: Option<Value>,
+    pub error: Option<ToolCallError>,
+    pub execution_time_ms: u64,
+}
+
+impl ToolCallOutcome {
+    pub fn failed(error: ToolCallError) -> Self {
+        Self {
+            data: None,
+            error: Some(error),
+            execution_time_ms: 0,
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// tool 消息内容
+    pub fn to_content(&self) -> String {
+        let content = match &self.error {
+            None => serde_json::json!({
+                "success": true,
+                "data": self.data,
+                "execution_time_ms": self.execution_time_ms,
+            }),
+            Some(error) => serde_json::json!({
+                "success": false,
+                "error": error,
+                "data": self.data,
+                "execution_time_ms": self.execution_time_ms,
+            }),
+        };
+        content.to_string()
+    }
+}
+
+impl From<ToolResult> for ToolCallOutcome {
+    fn from(result: ToolResult) -> Self {
+        let error = (!result.success).then(|| {
+            ToolCallError::execution_failed(
+                result
+                    .error
+                    .unwrap_or_else(|| "Tool execution failed".to_string()),
+            )
+        });
+
+        Self {
+            data: result.data,
+            error,
+            execution_time_ms: result.execution_time_ms,
+        }
+    }
+}
+
+/// 重复调用检测（同一次回复内，名称与参数完全相同的调用）
+#[derive(Debug, Default)]
+pub struct RepeatedCallGuard {
+    max_identical_calls: u32,
+    counts: HashMap<String, u32>,
+}
+
+impl RepeatedCallGuard {
+    pub fn new(max_identical_calls: u32) -> Self {
+        Self {
+            max_identical_calls: max_identical_calls.max(1),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// 登记一次调用；超过允许次数时返回此前已执行的次数
+    pub fn register(&mut self, tool_name: &str, arguments: &str) -> Result<(), u32> {
+        let count = self
+            .counts
+            .entry(Self::signature(tool_name, arguments))
+            .or_insert(0);
+
+        if *count >= self.max_identical_calls {
+            return Err(*count);
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// 调用签名（参数按键排序，忽略格式差异）
+    fn signature(tool_name: &str, arguments: &str) -> String {
+        let arguments = serde_json::from_str::<Value>(arguments)
+            .map(|value| canonical_json(&value))
+            .unwrap_or_else(|_| arguments.trim().to_string());
+        format!("{}:{}", tool_name, arguments)
+    }
+}
+
+/// 键按字典序排列的 JSON 文本
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let body = entries
+                .into_iter()
+                .map(|(key, value)| format!("{}:{}", Value::String(key.clone()), canonical_json(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{{{}}}", body)
+        }
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(canonical_json).collect::<Vec<_>>().join(",")
+        ),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_blocks_identical_calls_regardless_of_key_order() {
+        let mut guard = RepeatedCallGuard::new(2);
+
+        assert_eq!(guard.register("edit_character", r#"{"field":"name","value":"A"}"#), Ok(()));
+        assert_eq!(guard.register("edit_character", r#"{ "value": "A", "field": "name" }"#), Ok(()));
+        assert_eq!(guard.register("edit_character", r#"{"field":"name","value":"A"}"#), Err(2));
+        assert_eq!(guard.register("edit_character", r#"{"field":"name","value":"B"}"#), Ok(()));
+    }
+
+    #[test]
+    fn test_outcome_content_carries_structured_error() {
+        let outcome = ToolCallOutcome::failed(ToolCallError::unknown_tool("fly"));
+        let content: Value = serde_json::from_str(&outcome.to_content()).unwrap();
+
+        assert_eq!(content["success"], false);
+        assert_eq!(content["error"]["kind"], "unknown_tool");
+        assert!(content["error"]["hint"].is_string());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::ai_config::AIConfigService;
+use crate::backend::domain::SessionSettings;
+use crate::file_utils::FileUtils;
+use crate::tools::AIToolTrait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// 一层工具启用规则（未列出的分类与工具沿用上一层的结果）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolSelection {
+    /// 只允许不修改角色卡的工具
+    #[serde(default)]
+    pub read_only: bool,
+    /// 按分类启用或停用
+    #[serde(default)]
+    pub categories: HashMap<String, bool>,
+    /// 按工具名称启用或停用（优先于分类）
+    #[serde(default)]
+    pub tools: HashMap<String, bool>,
+}
+
+/// 可切换的工具档案（如“起草”与“仅审阅”）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(flatten)]
+    pub selection: ToolSelection,
+}
+
+/// 工具启用设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSettings {
+    /// 全局使用的档案（会话可单独指定）
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<ToolProfile>,
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        Self {
+            active_profile: Some("drafting".to_string()),
+            profiles: vec![
+                ToolProfile {
+                    name: "drafting".to_string(),
+                    description: "起草：启用全部工具".to_string(),
+                    selection: ToolSelection::default(),
+                },
+                ToolProfile {
+                    name: "review_only".to_string(),
+                    description: "仅审阅：只启用不修改角色卡的工具".to_string(),
+                    selection: ToolSelection {
+                        read_only: true,
+                        ..ToolSelection::default()
+                    },
+                },
+            ],
+        }
+    }
+}
+
+impl ToolSettings {
+    pub fn profile(&self, name: &str) -> Option<&ToolProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+}
+
+/// 按“档案 → AI 角色 → 会话”顺序叠加的工具过滤器
+#[derive(Debug, Clone, Default)]
+pub struct ToolFilter {
+    layers: Vec<ToolSelection>,
+}
+
+impl ToolFilter {
+    /// 工具是否启用（任一层设为只读时修改类工具始终停用）
+    pub fn allows(&self, tool: &dyn AIToolTrait) -> bool {
+        self.allows_tool(tool.name(), tool.category(), tool.modifies_character())
+    }
+
+    fn allows_tool(&self, name: &str, category: &str, modifies_character: bool) -> bool {
+        let mut enabled = true;
+        for layer in &self.layers {
+            if layer.read_only && modifies_character {
+                return false;
+            }
+            if let Some(tool_enabled) = layer.tools.get(name) {
+                enabled = *tool_enabled;
+            } else if let Some(category_enabled) = layer.categories.get(category) {
+                enabled = *category_enabled;
+            }
+        }
+        enabled
+    }
+}
+
+/// 工具启用设置服务
+pub struct ToolSettingsService;
+
+impl ToolSettingsService {
+    fn get_config_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir.join("tool_settings.json"))
+    }
+
+    /// 读取工具启用设置
+    pub fn get_settings(app_handle: &tauri::AppHandle) -> Result<ToolSettings, String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        if !config_file.exists() {
+            return Ok(ToolSettings::default());
+        }
+        FileUtils::read_json_file(&config_file)
+    }
+
+    /// 保存工具启用设置
+    pub fn save_settings(app_handle: &tauri::AppHandle, settings: &ToolSettings) -> Result<(), String> {
+        for (index, profile) in settings.profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                return Err("工具档案名称不能为空".to_string());
+            }
+            if settings.profiles[..index].iter().any(|p| p.name == profile.name) {
+                return Err(format!("工具档案 {} 重复", profile.name));
+            }
+        }
+        if let Some(active) = &settings.active_profile {
+            if settings.profile(active).is_none() {
+                return Err(format!("工具档案 {} 不存在", active));
+            }
+        }
+
+        let config_file = Self::get_config_file(app_handle)?;
+        FileUtils::write_json_file(&config_file, settings)
+    }
+
+    /// 切换全局使用的档案
+    pub fn set_active_profile(
+        app_handle: &tauri::AppHandle,
+        name: Option<String>,
+    ) -> Result<ToolSettings, String> {
+        let mut settings = Self::get_settings(app_handle)?;
+        settings.active_profile = name;
+        Self::save_settings(app_handle, &settings)?;
+        Ok(settings)
+    }
+
+    /// 会话适用的工具过滤器
+    pub fn filter_for(
+        app_handle: &tauri::AppHandle,
+        session_settings: &SessionSettings,
+    ) -> Result<ToolFilter, String> {
+        let settings = Self::get_settings(app_handle)?;
+        let role = AIConfigService::resolve_role(app_handle, session_settings.ai_role.as_deref())?;
+        let profile_name = session_settings
+            .tool_profile
+            .as_deref()
+            .or(settings.active_profile.as_deref());
+
+        let mut layers = Vec::new();
+        if let Some(name) = profile_name {
+            match settings.profile(name) {
+                Some(profile) => layers.push(profile.selection.clone()),
+                None => eprintln!("⚠️ 工具档案 '{}' 不存在，已忽略", name),
+            }
+        }
+        if let Some((_, role)) = role {
+            layers.push(role.tools);
+        }
+        layers.push(session_settings.tools.clone());
+
+        Ok(ToolFilter { layers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_later_layers_override_and_read_only_wins() {
+        let profile = ToolSelection {
+            categories: HashMap::from([("mcp".to_string(), false)]),
+            ..ToolSelection::default()
+        };
+        let role = ToolSelection {
+            tools: HashMap::from([("wiki__search".to_string(), true)]),
+            ..ToolSelection::default()
+        };
+        let filter = ToolFilter {
+            layers: vec![profile, role],
+        };
+        assert!(filter.allows_tool("wiki__search", "mcp", false));
+        assert!(!filter.allows_tool("wiki__fetch", "mcp", false));
+        assert!(filter.allows_tool("edit_character", "character", true));
+
+        let session = ToolSelection {
+            read_only: true,
+            tools: HashMap::from([("edit_character".to_string(), true)]),
+            ..ToolSelection::default()
+        };
+        let filter = ToolFilter { layers: vec![session] };
+        assert!(!filter.allows_tool("edit_character", "character", true));
+        assert!(filter.allows_tool("count_tokens", "user", false));
+    }
+}
@@ -1,8 +1,16 @@
 pub mod traits;
 pub mod registry;
+pub mod character_lock;
 pub mod character_editor;
 pub mod world_book_creator;
+pub mod mcp_tool;
+pub mod schema;
+pub mod user_tool;
 
 pub use traits::*;
 pub use registry::*;
+pub use character_lock::*;
+pub use mcp_tool::McpTool;
+pub use schema::ToolSchema;
+pub use user_tool::{UserTool, UserToolAction, UserToolManifest};
 
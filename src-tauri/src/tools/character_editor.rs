@@ -1,18 +1,112 @@
-use super::AIToolTrait;
+use super::{AIToolTrait, ToolSchema};
 use crate::ai_tools::{ToolCallRequest, ToolResult};
-use crate::ai_chat::{ChatTool, ToolFunction, ToolParameters, ToolParameter as ChatToolParameter};
 use crate::backend::application::event_bus::EventBus;
 use crate::backend::domain::CharacterUpdateType;
-use crate::character_storage::CharacterStorage;
+use crate::character_storage::{CharacterStorage, TavernCardV2};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
 use tauri::AppHandle;
 
 const ALTERNATE_GREETING_MARKER: &str = "<START_ALT>";
 
+/// 角色编辑工具参数（至少提供一个字段）
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EditCharacterParams {
+    /// 角色名称
+    pub name: Option<String>,
+    /// 角色描述
+    pub description: Option<String>,
+    /// 性格特点
+    pub personality: Option<String>,
+    /// 场景设定
+    pub scenario: Option<String>,
+    /// 开场白
+    pub first_mes: Option<String>,
+    /// 对话示例
+    pub mes_example: Option<String>,
+    /// 创作者笔记
+    pub creator_notes: Option<String>,
+    /// 系统提示词
+    pub system_prompt: Option<String>,
+    /// 历史后指令
+    pub post_history_instructions: Option<String>,
+    /// 备用问候语，使用 <START_ALT> 标记每段开头
+    pub alternate_greetings: Option<String>,
+    /// 标签，多个标签用逗号分隔
+    pub tags: Option<String>,
+    /// 创作者
+    pub creator: Option<String>,
+    /// 角色版本
+    pub character_version: Option<String>,
+}
+
 /// 角色编辑工具
 pub struct EditCharacterTool;
 
+impl EditCharacterTool {
+    /// 将参数中的字段写入角色卡，返回 (字段名, 字段说明) 列表
+    fn apply_fields(
+        tavern_card: &mut TavernCardV2,
+        params: EditCharacterParams,
+    ) -> Vec<(&'static str, &'static str)> {
+        let mut updated_fields = Vec::new();
+        let data = &mut tavern_card.data;
+
+        let text_fields = [
+            (params.name, &mut data.name, "name", "角色名称"),
+            (params.description, &mut data.description, "description", "角色描述"),
+            (params.personality, &mut data.personality, "personality", "性格特点"),
+            (params.scenario, &mut data.scenario, "scenario", "场景设定"),
+            (params.first_mes, &mut data.first_mes, "first_mes", "开场白"),
+            (params.mes_example, &mut data.mes_example, "mes_example", "对话示例"),
+            (params.creator_notes, &mut data.creator_notes, "creator_notes", "创作者笔记"),
+            (params.system_prompt, &mut data.system_prompt, "system_prompt", "系统提示词"),
+            (
+                params.post_history_instructions,
+                &mut data.post_history_instructions,
+                "post_history_instructions",
+                "历史后指令",
+            ),
+            (params.creator, &mut data.creator, "creator", "创作者"),
+            (
+                params.character_version,
+                &mut data.character_version,
+                "character_version",
+                "角色版本",
+            ),
+        ];
+        for (value, field, field_name, label) in text_fields {
+            if let Some(value) = value {
+                *field = value;
+                updated_fields.push((field_name, label));
+            }
+        }
+
+        if let Some(greetings) = params.alternate_greetings {
+            data.alternate_greetings = greetings
+                .split(ALTERNATE_GREETING_MARKER)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            updated_fields.push(("alternate_greetings", "备用问候语"));
+        }
+
+        if let Some(tags) = params.tags {
+            data.tags = tags
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            updated_fields.push(("tags", "标签"));
+        }
+
+        updated_fields
+    }
+}
+
 #[async_trait]
 impl AIToolTrait for EditCharacterTool {
     fn name(&self) -> &'static str {
@@ -20,16 +114,51 @@ impl AIToolTrait for EditCharacterTool {
     }
 
     fn description(&self) -> &'static str {
-        "直接编辑角色卡字段。使用方法：将要更新的字段作为参数传入，例如要更新description字段，就直接传入description参数。不需要指定角色名称，系统会自动使用当前角色。支持的参数：name, description, personality, scenario, first_mes, mes_example, creator_notes, system_prompt, post_history_instructions, alternate_greetings(使用<START_ALT>标记每段), tags(逗号分隔), creator, character_version"
+        "直接编辑角色卡字段。使用方法：将要更新的字段作为参数传入，例如要更新description字段，就直接传入description参数，至少提供一个字段。不需要指定角色名称，系统会自动使用当前角色。alternate_greetings 使用<START_ALT>标记每段，tags 用逗号分隔"
     }
 
     fn category(&self) -> &'static str {
         "character"
     }
 
+    fn input_schema(&self) -> Value {
+        let mut schema = ToolSchema::of::<EditCharacterParams>();
+        schema["minProperties"] = Value::from(1);
+        schema
+    }
+
+    fn apply_to_card(
+        &self,
+        card: &mut TavernCardV2,
+        request: &ToolCallRequest,
+    ) -> Option<Result<(), String>> {
+        let params = match ToolSchema::parse::<EditCharacterParams>(&request.parameters) {
+            Ok(params) => params,
+            Err(e) => return Some(Err(e)),
+        };
+        let updated_fields = Self::apply_fields(card, params);
+        Some(if updated_fields.is_empty() {
+            Err("没有提供有效的字段参数".to_string())
+        } else {
+            Ok(())
+        })
+    }
+
     async fn execute(&self, app_handle: &AppHandle, request: &ToolCallRequest) -> ToolResult {
         let start_time = std::time::Instant::now();
 
+        let params = match ToolSchema::parse::<EditCharacterParams>(&request.parameters) {
+            Ok(params) => params,
+            Err(e) => {
+                return ToolResult {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                };
+            }
+        };
+
         // 获取角色UUID
         let character_uuid = match &request.character_uuid {
             Some(uuid) => uuid.clone(),
@@ -66,84 +195,7 @@ impl AIToolTrait for EditCharacterTool {
             };
 
         let mut tavern_card = character_data.card;
-        let mut updated_fields = Vec::new();
-
-        // 遍历所有参数，更新对应的字段（忽略提示字段）
-        for (field_name, field_value) in &request.parameters {
-            // 忽略提示字段
-            if field_name == "at_least_one_field" {
-                continue;
-            }
-
-            if let Some(value_str) = field_value.as_str() {
-                match field_name.as_str() {
-                    "name" => {
-                        tavern_card.data.name = value_str.to_string();
-                        updated_fields.push(("name", "角色名称"));
-                    }
-                    "description" => {
-                        tavern_card.data.description = value_str.to_string();
-                        updated_fields.push(("description", "角色描述"));
-                    }
-                    "personality" => {
-                        tavern_card.data.personality = value_str.to_string();
-                        updated_fields.push(("personality", "性格特点"));
-                    }
-                    "scenario" => {
-                        tavern_card.data.scenario = value_str.to_string();
-                        updated_fields.push(("scenario", "场景设定"));
-                    }
-                    "first_mes" => {
-                        tavern_card.data.first_mes = value_str.to_string();
-                        updated_fields.push(("first_mes", "开场白"));
-                    }
-                    "mes_example" => {
-                        tavern_card.data.mes_example = value_str.to_string();
-                        updated_fields.push(("mes_example", "对话示例"));
-                    }
-                    "creator_notes" => {
-                        tavern_card.data.creator_notes = value_str.to_string();
-                        updated_fields.push(("creator_notes", "创作者笔记"));
-                    }
-                    "system_prompt" => {
-                        tavern_card.data.system_prompt = value_str.to_string();
-                        updated_fields.push(("system_prompt", "系统提示词"));
-                    }
-                    "post_history_instructions" => {
-                        tavern_card.data.post_history_instructions = value_str.to_string();
-                        updated_fields.push(("post_history_instructions", "历史后指令"));
-                    }
-                    "alternate_greetings" => {
-                        tavern_card.data.alternate_greetings = value_str
-                            .split(ALTERNATE_GREETING_MARKER)
-                            .map(|s| s.trim().to_string())
-                            .filter(|s| !s.is_empty())
-                            .collect();
-                        updated_fields.push(("alternate_greetings", "备用问候语"));
-                    }
-                    "tags" => {
-                        tavern_card.data.tags = value_str
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .filter(|s| !s.is_empty())
-                            .collect();
-                        updated_fields.push(("tags", "标签"));
-                    }
-                    "creator" => {
-                        tavern_card.data.creator = value_str.to_string();
-                        updated_fields.push(("creator", "创作者"));
-                    }
-                    "character_version" => {
-                        tavern_card.data.character_version = value_str.to_string();
-                        updated_fields.push(("character_version", "角色版本"));
-                    }
-                    _ => {
-                        // 忽略未知字段，但记录警告
-                        eprintln!("警告: 未知字段名 '{}' 被忽略", field_name);
-                    }
-                }
-            }
-        }
+        let updated_fields = Self::apply_fields(&mut tavern_card, params);
 
         // 检查是否有字段被更新
         if updated_fields.is_empty() {
@@ -212,190 +264,4 @@ impl AIToolTrait for EditCharacterTool {
             },
         }
     }
-
-    fn to_chat_tool(&self) -> ChatTool {
-        let mut properties = HashMap::new();
-
-        // 添加所有参数到 properties
-        properties.insert(
-            "at_least_one_field".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("必须提供至少一个要编辑的字段（如description, personality等）".to_string()),
-                enum_values: Some(vec!["edit_character".to_string()]),
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "name".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("角色名称".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "description".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("角色描述".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "personality".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("性格特点".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "scenario".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("场景设定".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "first_mes".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("开场白".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "mes_example".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("对话示例".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "creator_notes".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("创作者笔记".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "system_prompt".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("系统提示词".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "post_history_instructions".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("历史后指令".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "alternate_greetings".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("备用问候语，使用 <START_ALT> 标记每段开头".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "tags".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("标签，多个标签用逗号分隔".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "creator".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("创作者".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        properties.insert(
-            "character_version".to_string(),
-            ChatToolParameter {
-                param_type: "string".to_string(),
-                description: Some("角色版本".to_string()),
-                enum_values: None,
-                items: None,
-                properties: None,
-                required: None,
-            },
-        );
-
-        ChatTool {
-            tool_type: "function".to_string(),
-            function: ToolFunction {
-                name: self.name().to_string(),
-                description: Some(self.description().to_string()),
-                parameters: Some(ToolParameters {
-                    param_type: "object".to_string(),
-                    properties,
-                    required: Some(vec!["at_least_one_field".to_string()]),
-                }),
-            },
-        }
-    }
 }
@@ -0,0 +1,55 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+/// 每个角色的写锁
+static CHARACTER_LOCKS: Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 角色级锁 - 串行化修改同一角色卡的工具调用，避免并发读改写互相覆盖
+pub struct CharacterLock;
+
+impl CharacterLock {
+    /// 获取角色写锁，持有期间其他修改该角色的调用会等待
+    pub async fn acquire(character_uuid: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = CHARACTER_LOCKS.lock().unwrap();
+            // 顺便清理无人持有的锁
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks
+                .entry(character_uuid.to_string())
+                .or_default()
+                .clone()
+        };
+
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_character_calls_are_serialized() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let tasks = (0..3).map(|index| {
+            let log = log.clone();
+            tokio::spawn(async move {
+                let _guard = CharacterLock::acquire("same-character").await;
+                log.lock().unwrap().push(format!("start{}", index));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push(format!("end{}", index));
+            })
+        });
+        futures::future::join_all(tasks).await;
+
+        let log = log.lock().unwrap();
+        for pair in log.chunks(2) {
+            assert_eq!(pair[0].replace("start", ""), pair[1].replace("end", ""));
+        }
+    }
+}
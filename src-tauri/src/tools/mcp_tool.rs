@@ -0,0 +1,51 @@
+use super::{AIToolTrait, ToolSchema};
+use crate::ai_chat::ChatTool;
+use crate::ai_tools::{ToolCallRequest, ToolResult};
+use crate::mcp_client::{McpService, MCP_TOOL_CATEGORY};
+use async_trait::async_trait;
+use serde_json::Value;
+use tauri::AppHandle;
+
+/// MCP 服务器提供的工具（调用转发到对应服务器）
+pub struct McpTool {
+    /// 注册到工具中心的名称（`{服务器}__{工具}`）
+    pub name: String,
+    pub server: String,
+    /// 服务器上的工具名称
+    pub remote_name: String,
+    pub description: String,
+    /// 工具参数的 JSON Schema
+    pub input_schema: Value,
+}
+
+#[async_trait]
+impl AIToolTrait for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn category(&self) -> &'static str {
+        MCP_TOOL_CATEGORY
+    }
+
+    fn modifies_character(&self) -> bool {
+        false
+    }
+
+    fn input_schema(&self) -> Value {
+        self.input_schema.clone()
+    }
+
+    async fn execute(&self, _app_handle: &AppHandle, request: &ToolCallRequest) -> ToolResult {
+        McpService::call_tool(&self.server, &self.remote_name, &request.parameters).await
+    }
+
+    fn to_chat_tool(&self) -> ChatTool {
+        let description = format!("[MCP: {}] {}", self.server, self.description);
+        ToolSchema::chat_tool(&self.name, &description, &self.input_schema)
+    }
+}
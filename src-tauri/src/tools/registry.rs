@@ -1,6 +1,9 @@
-use super::AIToolTrait;
+use super::{AIToolTrait, CharacterLock, ToolSchema};
 use crate::ai_tools::{ToolCallRequest, ToolResult};
 use crate::ai_chat::ChatTool;
+use crate::card_diff::{diff_cards, FieldChange};
+use crate::tool_audit::ToolAuditService;
+use crate::character_storage::{CharacterStorage, TavernCardV2};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::AppHandle;
@@ -53,7 +56,39 @@ impl ToolRegistry {
 
         // 锁已释放，可以安全地执行异步调用
         if let Some(tool) = tool_opt {
-            tool.execute(app_handle, request).await
+            if !tool.enabled() {
+                return ToolResult {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Tool is disabled: {}", tool_name)),
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                };
+            }
+
+            if let Err(errors) = ToolSchema::validate(&tool.input_schema(), &request.parameters) {
+                return ToolResult {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Invalid arguments: {}", errors.join("; "))),
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                };
+            }
+
+            // 修改角色卡的调用按角色串行执行，其余调用可并发
+            let _character_guard = match &request.character_uuid {
+                Some(uuid) if tool.modifies_character() => Some(CharacterLock::acquire(uuid).await),
+                _ => None,
+            };
+
+            // 持锁期间记录执行前后的角色卡，审计日志中的修改只来自本次调用
+            let before = if tool.modifies_character() {
+                ToolAuditService::snapshot(app_handle, request)
+            } else {
+                None
+            };
+            let result = tool.execute(app_handle, request).await;
+            ToolAuditService::record(app_handle, request, &result, before.as_ref());
+            result
         } else {
             ToolResult {
                 success: false,
@@ -64,6 +99,66 @@ impl ToolRegistry {
         }
     }
 
+    /// 预览工具调用对角色卡的修改（在角色卡副本上执行，不落盘）
+    ///
+    /// 工具不修改角色卡或不支持预览时返回 Ok(None)
+    pub fn preview_tool_call_global(
+        app_handle: &AppHandle,
+        request: &ToolCallRequest,
+    ) -> Result<Option<Vec<FieldChange>>, String> {
+        let tool = {
+            let registry = TOOL_REGISTRY.read().unwrap();
+            registry.tools.get(&request.tool_name).cloned()
+        }
+        .ok_or_else(|| format!("Unknown tool: {}", request.tool_name))?;
+
+        if !tool.modifies_character() {
+            return Ok(None);
+        }
+        let character_uuid = request
+            .character_uuid
+            .as_deref()
+            .ok_or_else(|| "缺少角色UUID".to_string())?;
+        let character_data = CharacterStorage::get_character_by_uuid(app_handle, character_uuid)?
+            .ok_or_else(|| "角色不存在".to_string())?;
+
+        let mut preview = character_data.card.clone();
+        match tool.apply_to_card(&mut preview, request) {
+            None => Ok(None),
+            Some(result) => {
+                result?;
+                Ok(Some(diff_cards(&character_data.card, &preview)))
+            }
+        }
+    }
+
+    /// 将工具调用应用到给定角色卡（不落盘）
+    pub fn apply_to_card_global(
+        card: &mut TavernCardV2,
+        request: &ToolCallRequest,
+    ) -> Result<(), String> {
+        let tool = {
+            let registry = TOOL_REGISTRY.read().unwrap();
+            registry.tools.get(&request.tool_name).cloned()
+        }
+        .ok_or_else(|| format!("Unknown tool: {}", request.tool_name))?;
+
+        tool.apply_to_card(card, request)
+            .unwrap_or_else(|| Err(format!("工具 {} 不支持预览修改", request.tool_name)))
+    }
+
+    /// 获取通过过滤条件的可用工具
+    pub fn get_available_tools_where(
+        &self,
+        predicate: impl Fn(&dyn AIToolTrait) -> bool,
+    ) -> Vec<ChatTool> {
+        self.tools
+            .values()
+            .filter(|tool| tool.enabled() && predicate(tool.as_ref()))
+            .map(|tool| tool.to_chat_tool())
+            .collect()
+    }
+
     /// 获取工具分类
     pub fn get_tool_categories(&self) -> Vec<&'static str> {
         let mut categories: std::collections::HashSet<&'static str> =
@@ -91,6 +186,23 @@ impl ToolRegistry {
         registry.get_available_tools()
     }
 
+    /// 获取通过过滤条件的可用工具（静态方法）
+    pub fn get_available_tools_where_global(
+        predicate: impl Fn(&dyn AIToolTrait) -> bool,
+    ) -> Vec<ChatTool> {
+        let registry = TOOL_REGISTRY.read().unwrap();
+        registry.get_available_tools_where(predicate)
+    }
+
+    /// 工具是否已注册、已启用且通过过滤条件
+    pub fn tool_matches_global(tool_name: &str, predicate: impl Fn(&dyn AIToolTrait) -> bool) -> bool {
+        let registry = TOOL_REGISTRY.read().unwrap();
+        registry
+            .tools
+            .get(tool_name)
+            .is_some_and(|tool| tool.enabled() && predicate(tool.as_ref()))
+    }
+
     /// 获取工具分类（静态方法）
     pub fn get_tool_categories_global() -> Vec<&'static str> {
         let registry = TOOL_REGISTRY.read().unwrap();
@@ -102,6 +214,45 @@ impl ToolRegistry {
         let registry = TOOL_REGISTRY.read().unwrap();
         registry.get_tools_by_category(category)
     }
+
+    /// 注册运行时发现的工具（如 MCP 工具），与已注册工具重名时返回 false
+    pub fn register_dynamic_tool_global(tool: Arc<dyn AIToolTrait + Send + Sync>) -> bool {
+        let mut registry = TOOL_REGISTRY.write().unwrap();
+        let name = tool.name().to_string();
+        if registry.tools.contains_key(&name) {
+            return false;
+        }
+        registry.tools.insert(name, tool);
+        true
+    }
+
+    /// 注销工具
+    pub fn unregister_tool_global(tool_name: &str) {
+        TOOL_REGISTRY.write().unwrap().tools.remove(tool_name);
+    }
+
+    /// 按工具的参数 Schema 校验参数，返回全部校验错误（未注册的工具视为错误）
+    pub fn validate_arguments_global(
+        tool_name: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), Vec<String>> {
+        let schema = {
+            let registry = TOOL_REGISTRY.read().unwrap();
+            registry.tools.get(tool_name).map(|tool| tool.input_schema())
+        }
+        .ok_or_else(|| vec![format!("Unknown tool: {}", tool_name)])?;
+        ToolSchema::validate(&schema, parameters)
+    }
+
+    /// 工具是否会修改角色卡（未注册或已停用的工具返回 None）
+    pub fn modifies_character_global(tool_name: &str) -> Option<bool> {
+        let registry = TOOL_REGISTRY.read().unwrap();
+        registry
+            .tools
+            .get(tool_name)
+            .filter(|tool| tool.enabled())
+            .map(|tool| tool.modifies_character())
+    }
 }
 
 // 全局工具注册中心实例
@@ -0,0 +1,333 @@
+use crate::ai_chat::{ChatTool, ToolFunction, ToolParameter as ChatToolParameter, ToolParameters};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// 基于 JSON Schema 的工具参数定义与校验
+pub struct ToolSchema;
+
+impl ToolSchema {
+    /// 由参数结构体生成 JSON Schema（子结构内联，Option 字段即非必填）
+    ///
+    /// Option 字段的类型不含 null，校验时非必填字段的 null 值视为未填写（与 serde 的行为一致）
+    pub fn of<T: JsonSchema>() -> Value {
+        let generator = SchemaSettings::draft07()
+            .with(|settings| {
+                settings.inline_subschemas = true;
+                settings.option_add_null_type = false;
+                settings.meta_schema = None;
+            })
+            .into_generator();
+
+        let mut schema = serde_json::to_value(generator.into_root_schema_for::<T>()).unwrap_or_default();
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove("title");
+            schema.remove("definitions");
+        }
+        schema
+    }
+
+    /// 由 JSON Schema 生成 ChatTool 定义
+    pub fn chat_tool(name: &str, description: &str, schema: &Value) -> ChatTool {
+        ChatTool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                parameters: Some(ToolParameters {
+                    param_type: "object".to_string(),
+                    properties: Self::properties(schema).unwrap_or_default(),
+                    required: Self::required(schema),
+                }),
+            },
+        }
+    }
+
+    /// 将 JSON Schema 转换为工具参数定义
+    ///
+    /// 工具参数定义无法表示联合类型：仅含一个非 null 分支的 anyOf/oneOf 按该分支转换，
+    /// 其余 anyOf/oneOf 与缺少 type 的 schema 按字符串处理（校验仍按原 schema 进行）
+    fn to_parameter(schema: &Value) -> ChatToolParameter {
+        if let Some(variant) = Self::single_variant(schema) {
+            let mut parameter = Self::to_parameter(variant);
+            if let Some(description) = schema.get("description").and_then(Value::as_str) {
+                parameter.description = Some(description.to_string());
+            }
+            return parameter;
+        }
+
+        ChatToolParameter {
+            param_type: Self::types(schema)
+                .into_iter()
+                .find(|param_type| *param_type != "null")
+                .unwrap_or("string")
+                .to_string(),
+            description: schema
+                .get("description")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            enum_values: schema.get("enum").and_then(Value::as_array).map(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+                    .collect()
+            }),
+            items: schema
+                .get("items")
+                .map(|items| Box::new(Self::to_parameter(items))),
+            properties: Self::properties(schema),
+            required: Self::required(schema),
+        }
+    }
+
+    /// 没有 type 的 anyOf/oneOf 中唯一的非 null 分支
+    fn single_variant(schema: &Value) -> Option<&Value> {
+        if schema.get("type").is_some() {
+            return None;
+        }
+        let variants = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(Value::as_array)?;
+        let mut non_null = variants.iter().filter(|variant| Self::types(variant) != ["null"]);
+        match (non_null.next(), non_null.next()) {
+            (Some(variant), None) => Some(variant),
+            _ => None,
+        }
+    }
+
+    fn properties(schema: &Value) -> Option<HashMap<String, ChatToolParameter>> {
+        schema.get("properties").and_then(Value::as_object).map(|properties| {
+            properties
+                .iter()
+                .map(|(name, schema)| (name.clone(), Self::to_parameter(schema)))
+                .collect()
+        })
+    }
+
+    fn required(schema: &Value) -> Option<Vec<String>> {
+        schema.get("required").and_then(Value::as_array).map(|required| {
+            required
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+    }
+
+    fn types(schema: &Value) -> Vec<&str> {
+        match schema.get("type") {
+            Some(Value::String(value_type)) => vec![value_type.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 按 JSON Schema 校验工具参数，返回所有错误（供模型修正）
+    pub fn validate(schema: &Value, arguments: &HashMap<String, Value>) -> Result<(), Vec<String>> {
+        let arguments = Value::Object(arguments.clone().into_iter().collect());
+        let mut errors = Vec::new();
+        Self::validate_value(schema, &arguments, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 将参数反序列化为参数结构体（逐字段错误由 validate 给出）
+    pub fn parse<T: DeserializeOwned>(arguments: &HashMap<String, Value>) -> Result<T, String> {
+        let arguments: Map<String, Value> = arguments.clone().into_iter().collect();
+        serde_json::from_value(Value::Object(arguments)).map_err(|e| format!("Invalid tool arguments: {}", e))
+    }
+
+    fn validate_value(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        let location = if path.is_empty() { "arguments" } else { path };
+
+        let types = Self::types(schema);
+        if !types.is_empty() && !types.iter().any(|expected| Self::matches_type(expected, value)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                location,
+                types.join(" or "),
+                Self::type_name(value)
+            ));
+            return;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                errors.push(format!("{}: must be one of [{}]", location, allowed.join(", ")));
+            }
+        }
+
+        match value {
+            Value::Object(map) => Self::validate_object(schema, map, path, errors),
+            Value::Array(items) => {
+                if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                    if (items.len() as u64) < min {
+                        errors.push(format!("{}: must contain at least {} item(s)", location, min));
+                    }
+                }
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        Self::validate_value(item_schema, item, &format!("{}[{}]", path, index), errors);
+                    }
+                }
+            }
+            Value::String(text) => {
+                if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                    if (text.chars().count() as u64) < min {
+                        errors.push(format!("{}: must be at least {} character(s)", location, min));
+                    }
+                }
+            }
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                    if number < min {
+                        errors.push(format!("{}: must be >= {}", location, min));
+                    }
+                }
+                if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                    if number > max {
+                        errors.push(format!("{}: must be <= {}", location, max));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_object(schema: &Value, map: &Map<String, Value>, path: &str, errors: &mut Vec<String>) {
+        let location = if path.is_empty() { "arguments" } else { path };
+        let field_path = |key: &str| {
+            if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", path, key)
+            }
+        };
+
+        let required = Self::required(schema).unwrap_or_default();
+        for required in &required {
+            if !map.contains_key(required) {
+                errors.push(format!("{}: missing required field", field_path(required)));
+            }
+        }
+
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (map.len() as u64) < min {
+                errors.push(format!("{}: must contain at least {} field(s)", location, min));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, value) in map {
+            match properties.and_then(|properties| properties.get(key)) {
+                // 模型常对可选字段传 null，serde 将其视为 None
+                Some(_) if value.is_null() && !required.contains(key) => {}
+                Some(property_schema) => Self::validate_value(property_schema, value, &field_path(key), errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => errors.push(format!("{}: unknown field", field_path(key))),
+                    Some(additional @ Value::Object(_)) => {
+                        Self::validate_value(additional, value, &field_path(key), errors)
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn matches_type(expected: &str, value: &Value) -> bool {
+        match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        }
+    }
+
+    fn type_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(number) if number.is_f64() => "number",
+            Value::Number(_) => "integer",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Params {
+        /// 关键词
+        #[schemars(length(min = 1))]
+        keys: Vec<String>,
+        #[schemars(range(min = 0, max = 100))]
+        probability: u8,
+        enabled: Option<bool>,
+    }
+
+    #[test]
+    fn test_schema_validation_reports_each_field() {
+        let schema = ToolSchema::of::<Params>();
+        let tool = ToolSchema::chat_tool("t", "d", &schema);
+        let parameters = tool.function.parameters.unwrap();
+        assert_eq!(parameters.properties["keys"].param_type, "array");
+        assert_eq!(parameters.properties["keys"].description.as_deref(), Some("关键词"));
+        assert_eq!(parameters.required, Some(vec!["keys".to_string(), "probability".to_string()]));
+
+        let arguments: HashMap<String, Value> = serde_json::from_value(serde_json::json!({
+            "keys": [], "probability": 150, "enabled": "yes", "depth": 3
+        }))
+        .unwrap();
+        let errors = ToolSchema::validate(&schema, &arguments).unwrap_err();
+        assert!(errors.contains(&"keys: must contain at least 1 item(s)".to_string()));
+        assert!(errors.contains(&"probability: must be <= 100".to_string()));
+        assert!(errors.contains(&"enabled: expected boolean, got string".to_string()));
+        assert!(errors.contains(&"depth: unknown field".to_string()));
+
+        let arguments: HashMap<String, Value> =
+            serde_json::from_value(serde_json::json!({ "keys": ["a"], "probability": 50 })).unwrap();
+        assert!(ToolSchema::validate(&schema, &arguments).is_ok());
+        assert!(ToolSchema::parse::<Params>(&arguments).is_ok());
+
+        let arguments: HashMap<String, Value> =
+            serde_json::from_value(serde_json::json!({ "keys": ["a"], "probability": null, "enabled": null }))
+                .unwrap();
+        let errors = ToolSchema::validate(&schema, &arguments).unwrap_err();
+        assert_eq!(errors, vec!["probability: expected integer, got null".to_string()]);
+    }
+
+    #[test]
+    fn test_single_variant_unions_convert_to_that_variant() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "mode": { "description": "模式", "anyOf": [{ "type": "null" }, { "type": "integer" }] },
+                "value": { "oneOf": [{ "type": "integer" }, { "type": "string" }] }
+            }
+        });
+        let properties = ToolSchema::chat_tool("t", "d", &schema).function.parameters.unwrap().properties;
+        assert_eq!(properties["mode"].param_type, "integer");
+        assert_eq!(properties["mode"].description.as_deref(), Some("模式"));
+        assert_eq!(properties["value"].param_type, "string");
+    }
+}
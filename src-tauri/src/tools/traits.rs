@@ -2,15 +2,18 @@ use async_trait::async_trait;
 use tauri::AppHandle;
 use crate::ai_tools::{ToolResult, ToolCallRequest};
 use crate::ai_chat::ChatTool;
+use crate::character_storage::TavernCardV2;
+use super::ToolSchema;
+use serde_json::Value;
 
 /// AI工具特征
 #[async_trait]
 pub trait AIToolTrait {
     /// 工具名称
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// 工具描述
-    fn description(&self) -> &'static str;
+    fn description(&self) -> &str;
 
     /// 工具分类
     fn category(&self) -> &'static str;
@@ -18,9 +21,22 @@ pub trait AIToolTrait {
     /// 工具是否启用
     fn enabled(&self) -> bool { true }
 
+    /// 工具是否会修改角色卡（修改同一角色的调用会按角色串行执行）
+    fn modifies_character(&self) -> bool { true }
+
+    /// 将调用应用到角色卡副本（不落盘），用于审批时的修改预览；不修改角色卡的工具返回 None
+    fn apply_to_card(&self, _card: &mut TavernCardV2, _request: &ToolCallRequest) -> Option<Result<(), String>> {
+        None
+    }
+
+    /// 工具参数的 JSON Schema，调用前按此校验参数
+    fn input_schema(&self) -> Value;
+
     /// 执行工具调用
     async fn execute(&self, app_handle: &AppHandle, request: &ToolCallRequest) -> ToolResult;
 
     /// 将工具转换为 OpenAI ChatTool 格式
-    fn to_chat_tool(&self) -> ChatTool;
+    fn to_chat_tool(&self) -> ChatTool {
+        ToolSchema::chat_tool(self.name(), self.description(), &self.input_schema())
+    }
 }
\ No newline at end of file
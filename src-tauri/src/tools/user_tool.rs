@@ -0,0 +1,340 @@
+use super::world_book_creator::{CreateWorldBookEntryParams, CreateWorldBookEntryTool};
+use super::AIToolTrait;
+use crate::ai_tools::{ToolCallRequest, ToolResult};
+use crate::backend::application::event_bus::EventBus;
+use crate::backend::domain::CharacterUpdateType;
+use crate::character_storage::{CharacterStorage, TavernCardV2};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// 注册到工具中心时用户工具的分类
+pub const USER_TOOL_CATEGORY: &str = "user";
+
+/// 脚本引擎（限制运算量与数据规模，不提供文件、网络等外部访问）
+static SCRIPT_ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut engine = Engine::new();
+    engine.set_max_operations(1_000_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1_000_000);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.disable_symbol("eval");
+    engine.on_print(|text| eprintln!("[用户工具脚本] {}", text));
+    engine.on_debug(|text, _, pos| eprintln!("[用户工具脚本] {:?} {}", pos, text));
+    engine
+});
+
+/// 用户工具的动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum UserToolAction {
+    /// 按模板修改角色卡字段并追加世界书条目
+    ///
+    /// 模板中的 `{{参数名}}` 替换为调用参数，`{{card.字段}}` 替换为当前字段值；
+    /// 整个字符串只有一个占位符时保留原值类型（如数组）
+    Template {
+        #[serde(default)]
+        fields: Map<String, Value>,
+        #[serde(default)]
+        world_book_entries: Vec<Value>,
+    },
+    /// 运行 Rhai 脚本：可读取 `args`、读写 `card`（角色卡 data），最后一个表达式的值作为结果
+    Script {
+        #[serde(default)]
+        source: Option<String>,
+        /// 相对工具目录的脚本文件
+        #[serde(default)]
+        file: Option<String>,
+    },
+}
+
+/// 用户工具清单（工具目录中的一个 JSON 文件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserToolManifest {
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub modifies_character: bool,
+    /// 参数的 JSON Schema
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    pub action: UserToolAction,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_input_schema() -> Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+/// 由清单声明的用户工具
+pub struct UserTool {
+    pub manifest: UserToolManifest,
+    /// 预编译的脚本（仅脚本动作）
+    script: Option<AST>,
+}
+
+impl UserTool {
+    /// 由清单创建工具，脚本动作在此编译（`script_source` 为读取后的脚本内容）
+    pub fn new(manifest: UserToolManifest, script_source: Option<&str>) -> Result<Self, String> {
+        let script = match script_source {
+            Some(source) => Some(
+                SCRIPT_ENGINE
+                    .compile(source)
+                    .map_err(|e| format!("脚本编译失败: {}", e))?,
+            ),
+            None => None,
+        };
+        Ok(Self { manifest, script })
+    }
+
+    /// 在角色卡上执行动作，返回给模型的结果
+    fn run(&self, card: &mut TavernCardV2, arguments: &HashMap<String, Value>) -> Result<Value, String> {
+        match &self.manifest.action {
+            UserToolAction::Template {
+                fields,
+                world_book_entries,
+            } => Self::run_template(card, arguments, fields, world_book_entries),
+            UserToolAction::Script { .. } => self.run_script(card, arguments),
+        }
+    }
+
+    fn run_template(
+        card: &mut TavernCardV2,
+        arguments: &HashMap<String, Value>,
+        fields: &Map<String, Value>,
+        world_book_entries: &[Value],
+    ) -> Result<Value, String> {
+        let current = serde_json::to_value(&card.data).map_err(|e| e.to_string())?;
+        let mut data = current.clone();
+        for (field, template) in fields {
+            if field == "character_book" || current.get(field).is_none() {
+                return Err(format!("不支持修改字段: {}", field));
+            }
+            data[field] = render(template, arguments, &current);
+        }
+        card.data = serde_json::from_value(data).map_err(|e| format!("字段值类型错误: {}", e))?;
+
+        let mut entry_ids = Vec::new();
+        for template in world_book_entries {
+            let entry = render(template, arguments, &current);
+            let params: CreateWorldBookEntryParams = serde_json::from_value(entry)
+                .map_err(|e| format!("世界书条目模板无效: {}", e))?;
+            entry_ids.push(CreateWorldBookEntryTool::add_entry(card, params)?.id);
+        }
+
+        Ok(serde_json::json!({
+            "updated_fields": fields.keys().collect::<Vec<_>>(),
+            "created_entries": entry_ids,
+        }))
+    }
+
+    fn run_script(&self, card: &mut TavernCardV2, arguments: &HashMap<String, Value>) -> Result<Value, String> {
+        let ast = self.script.as_ref().ok_or_else(|| "脚本未加载".to_string())?;
+
+        let mut scope = Scope::new();
+        scope.push(
+            "args",
+            rhai::serde::to_dynamic(arguments).map_err(|e| e.to_string())?,
+        );
+        scope.push(
+            "card",
+            rhai::serde::to_dynamic(&card.data).map_err(|e| e.to_string())?,
+        );
+
+        let result: Dynamic = SCRIPT_ENGINE
+            .eval_ast_with_scope(&mut scope, ast)
+            .map_err(|e| format!("脚本执行失败: {}", e))?;
+
+        if self.manifest.modifies_character {
+            let data = scope
+                .get_value::<Dynamic>("card")
+                .ok_or_else(|| "脚本移除了 card 变量".to_string())?;
+            card.data = rhai::serde::from_dynamic(&data).map_err(|e| format!("脚本返回的角色卡无效: {}", e))?;
+        }
+
+        rhai::serde::from_dynamic::<Value>(&result).map_err(|e| format!("脚本结果无法序列化: {}", e))
+    }
+}
+
+/// 渲染模板值（字符串中的占位符被替换，对象与数组递归处理）
+fn render(template: &Value, arguments: &HashMap<String, Value>, card: &Value) -> Value {
+    match template {
+        Value::String(text) => render_string(text, arguments, card),
+        Value::Array(items) => Value::Array(items.iter().map(|item| render(item, arguments, card)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render(value, arguments, card)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// 渲染字符串模板（`{{参数名}}` 替换为参数，`{{card.字段}}` 替换为角色卡字段）
+pub(crate) fn render_string(text: &str, arguments: &HashMap<String, Value>, card: &Value) -> Value {
+    let lookup = |name: &str| -> Value {
+        let name = name.trim();
+        match name.strip_prefix("card.") {
+            Some(field) => card.get(field).cloned().unwrap_or_default(),
+            None => arguments.get(name).cloned().unwrap_or_default(),
+        }
+    };
+
+    // 整个字符串就是一个占位符时保留原值类型
+    if let Some(name) = text.strip_prefix("{{").and_then(|rest| rest.strip_suffix("}}")) {
+        if !name.contains("{{") && !name.contains("}}") {
+            return lookup(name);
+        }
+    }
+
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match lookup(&rest[start + 2..start + end]) {
+            Value::Null => {}
+            Value::String(value) => rendered.push_str(&value),
+            value => rendered.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Value::String(rendered)
+}
+
+#[async_trait]
+impl AIToolTrait for UserTool {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn description(&self) -> &str {
+        &self.manifest.description
+    }
+
+    fn category(&self) -> &'static str {
+        USER_TOOL_CATEGORY
+    }
+
+    fn enabled(&self) -> bool {
+        self.manifest.enabled
+    }
+
+    fn modifies_character(&self) -> bool {
+        self.manifest.modifies_character
+    }
+
+    fn input_schema(&self) -> Value {
+        self.manifest.input_schema.clone()
+    }
+
+    fn apply_to_card(&self, card: &mut TavernCardV2, request: &ToolCallRequest) -> Option<Result<(), String>> {
+        Some(self.run(card, &request.parameters).map(|_| ()))
+    }
+
+    async fn execute(&self, app_handle: &AppHandle, request: &ToolCallRequest) -> ToolResult {
+        let start_time = std::time::Instant::now();
+        let result = self.execute_on_character(app_handle, request);
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        match result {
+            Ok(data) => ToolResult {
+                success: true,
+                data: Some(data),
+                error: None,
+                execution_time_ms,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                data: None,
+                error: Some(e),
+                execution_time_ms,
+            },
+        }
+    }
+}
+
+impl UserTool {
+    fn execute_on_character(&self, app_handle: &AppHandle, request: &ToolCallRequest) -> Result<Value, String> {
+        let character_uuid = request
+            .character_uuid
+            .as_deref()
+            .ok_or_else(|| "缺少角色UUID".to_string())?;
+        let character_data = CharacterStorage::get_character_by_uuid(app_handle, character_uuid)?
+            .ok_or_else(|| "角色不存在".to_string())?;
+
+        let mut card = character_data.card;
+        let result = self.run(&mut card, &request.parameters)?;
+        if !self.manifest.modifies_character {
+            return Ok(result);
+        }
+
+        CharacterStorage::update_character(app_handle, character_uuid, &card)
+            .map_err(|e| format!("保存角色数据失败: {}", e))?;
+        if let Some(updated) = CharacterStorage::get_character_by_uuid(app_handle, character_uuid)? {
+            if let Err(e) =
+                EventBus::character_updated(app_handle, character_uuid, &updated, CharacterUpdateType::FullData)
+            {
+                eprintln!("发送角色更新事件失败: {}", e);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character_storage::test_support;
+
+    fn card() -> TavernCardV2 {
+        let mut card = test_support::card("", None);
+        card.data.tags = vec!["a".to_string()];
+        card
+    }
+
+    fn manifest(action: Value) -> UserToolManifest {
+        serde_json::from_value(serde_json::json!({ "name": "t", "description": "d", "action": action })).unwrap()
+    }
+
+    #[test]
+    fn test_template_and_script_actions() {
+        let arguments: HashMap<String, Value> =
+            serde_json::from_value(serde_json::json!({ "mood": "grim", "tags": ["x", "y"] })).unwrap();
+
+        let tool = UserTool::new(
+            manifest(serde_json::json!({
+                "type": "template",
+                "fields": { "description": "{{card.name}} is {{mood}}.", "tags": "{{tags}}" }
+            })),
+            None,
+        )
+        .unwrap();
+        let mut template_card = card();
+        tool.run(&mut template_card, &arguments).unwrap();
+        assert_eq!(template_card.data.description, "Alice is grim.");
+        assert_eq!(template_card.data.tags, vec!["x", "y"]);
+
+        let source = r#"card.tags.push(args.mood); card.tags.len()"#;
+        let tool = UserTool::new(manifest(serde_json::json!({ "type": "script" })), Some(source)).unwrap();
+        let mut script_card = card();
+        assert_eq!(tool.run(&mut script_card, &arguments).unwrap(), Value::from(2));
+        assert_eq!(script_card.data.tags, vec!["a", "grim"]);
+
+        let endless = UserTool::new(manifest(serde_json::json!({ "type": "script" })), Some("loop {}")).unwrap();
+        assert!(endless.run(&mut card(), &arguments).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::command_system::registry::COMMAND_REGISTRY;
+use crate::command_system::user_command::{UserCommand, UserCommandDefinition};
+use crate::file_utils::FileUtils;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 用户命令配置（user_commands.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCommandsConfig {
+    /// 模板中 `{{user}}` 的替换值
+    #[serde(default = "default_user_name")]
+    pub user_name: String,
+    #[serde(default)]
+    pub commands: Vec<UserCommandDefinition>,
+}
+
+fn default_user_name() -> String {
+    "User".to_string()
+}
+
+impl Default for UserCommandsConfig {
+    fn default() -> Self {
+        Self {
+            user_name: default_user_name(),
+            commands: Vec::new(),
+        }
+    }
+}
+
+/// 用户命令的加载状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCommandStatus {
+    pub id: String,
+    /// 是否已注册到命令注册表
+    pub loaded: bool,
+    pub enabled: bool,
+    pub error: Option<String>,
+}
+
+/// 最近一次加载的结果
+static USER_COMMANDS: Lazy<RwLock<Vec<UserCommandStatus>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// 用户命令服务：从配置文件加载用户定义的命令并注册到命令注册表
+///
+/// 用户命令通过配置中的 `enabled` 启停，不受 CCC_DISABLED_COMMANDS 影响
+pub struct UserCommandService;
+
+impl UserCommandService {
+    fn get_config_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let app_data_dir = FileUtils::get_app_data_dir(app_handle)?;
+        Ok(app_data_dir.join("user_commands.json"))
+    }
+
+    /// 读取用户命令配置
+    pub fn get_config(app_handle: &tauri::AppHandle) -> Result<UserCommandsConfig, String> {
+        let config_file = Self::get_config_file(app_handle)?;
+        if !config_file.exists() {
+            return Ok(UserCommandsConfig::default());
+        }
+        FileUtils::read_json_file(&config_file)
+    }
+
+    /// 保存用户命令配置并重新加载
+    pub async fn save_config(
+        app_handle: &tauri::AppHandle,
+        config: &UserCommandsConfig,
+    ) -> Result<Vec<UserCommandStatus>, String> {
+        for (index, command) in config.commands.iter().enumerate() {
+            Self::validate_id(&command.id)?;
+            if config.commands[..index].iter().any(|c| c.id == command.id) {
+                return Err(format!("用户命令 {} 重复", command.id));
+            }
+        }
+
+        let config_file = Self::get_config_file(app_handle)?;
+        FileUtils::write_json_file(&config_file, config)?;
+        Self::reload(app_handle).await
+    }
+
+    /// 重新加载用户命令（注销上次加载的命令后按配置重新注册）
+    pub async fn reload(app_handle: &tauri::AppHandle) -> Result<Vec<UserCommandStatus>, String> {
+        let config = Self::get_config(app_handle)?;
+
+        let mut statuses = USER_COMMANDS.write().await;
+        for status in statuses.iter().filter(|status| status.loaded) {
+            COMMAND_REGISTRY.unregister(&status.id).await;
+        }
+
+        let mut loaded = Vec::with_capacity(config.commands.len());
+        for definition in config.commands {
+            let mut status = UserCommandStatus {
+                id: definition.id.clone(),
+                loaded: false,
+                enabled: definition.enabled,
+                error: None,
+            };
+
+            if let Err(e) = Self::validate_id(&definition.id) {
+                status.error = Some(e);
+            } else if COMMAND_REGISTRY.contains(&definition.id).await {
+                status.error = Some("与已注册的命令重名".to_string());
+            } else if definition.enabled {
+                COMMAND_REGISTRY
+                    .register(Arc::new(UserCommand::new(definition, config.user_name.clone())))
+                    .await;
+                status.loaded = true;
+            }
+            loaded.push(status);
+        }
+
+        *statuses = loaded;
+        Ok(statuses.clone())
+    }
+
+    /// 最近一次加载的状态
+    pub async fn list_status() -> Vec<UserCommandStatus> {
+        USER_COMMANDS.read().await.clone()
+    }
+
+    fn validate_id(id: &str) -> Result<(), String> {
+        if id.is_empty()
+            || id.len() > 64
+            || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!(
+                "命令标识 {} 无效：只能包含字母、数字、下划线和连字符，且不超过64个字符",
+                id
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::file_utils::FileUtils;
+use crate::tools::{ToolRegistry, UserTool, UserToolAction, UserToolManifest};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// 检查工具目录变化的间隔
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 用户工具清单的加载状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserToolStatus {
+    /// 清单文件名
+    pub file: String,
+    pub name: Option<String>,
+    /// 是否已注册到工具中心
+    pub loaded: bool,
+    pub enabled: bool,
+    pub error: Option<String>,
+}
+
+/// 最近一次加载的结果
+static USER_TOOLS: Lazy<RwLock<Vec<UserToolStatus>>> = Lazy::new(|| RwLock::new(Vec::new()));
+/// 目录监视任务是否已启动
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// 用户工具服务：从工具目录加载 JSON 清单并注册到工具中心
+pub struct UserToolService;
+
+impl UserToolService {
+    /// 用户工具目录（应用数据目录下的 user-tools）
+    pub fn tools_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+        let tools_dir = FileUtils::get_app_data_dir(app_handle)?.join("user-tools");
+        FileUtils::ensure_dir_exists(&tools_dir)?;
+        Ok(tools_dir)
+    }
+
+    /// 重新加载所有用户工具（注销上次加载的工具后按目录内容重新注册）
+    pub fn reload(app_handle: &tauri::AppHandle) -> Result<Vec<UserToolStatus>, String> {
+        let tools_dir = Self::tools_dir(app_handle)?;
+
+        let mut statuses = USER_TOOLS.write().unwrap();
+        for status in statuses.iter().filter(|status| status.loaded) {
+            if let Some(name) = &status.name {
+                ToolRegistry::unregister_tool_global(name);
+            }
+        }
+
+        *statuses = Self::manifest_files(&tools_dir)?
+            .into_iter()
+            .map(|path| Self::load_manifest(&tools_dir, &path))
+            .collect();
+        Ok(statuses.clone())
+    }
+
+    /// 最近一次加载的状态
+    pub fn list_status() -> Vec<UserToolStatus> {
+        USER_TOOLS.read().unwrap().clone()
+    }
+
+    /// 加载用户工具并在目录变化时自动重新加载（应用启动时调用）
+    pub async fn watch(app_handle: tauri::AppHandle) {
+        if WATCHING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut fingerprint = None;
+        loop {
+            let current = Self::tools_dir(&app_handle).map(|dir| Self::fingerprint(&dir));
+            match current {
+                Ok(current) if fingerprint.as_ref() != Some(&current) => {
+                    match Self::reload(&app_handle) {
+                        Ok(statuses) => {
+                            for status in statuses.iter().filter(|status| status.error.is_some()) {
+                                eprintln!(
+                                    "加载用户工具 {} 失败: {}",
+                                    status.file,
+                                    status.error.as_deref().unwrap_or_default()
+                                );
+                            }
+                        }
+                        Err(e) => eprintln!("加载用户工具失败: {}", e),
+                    }
+                    fingerprint = Some(current);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("读取用户工具目录失败: {}", e),
+            }
+            tokio::time::sleep(RELOAD_POLL_INTERVAL).await;
+        }
+    }
+
+    fn manifest_files(tools_dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(tools_dir)
+            .map_err(|e| format!("读取用户工具目录失败: {}", e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// 目录内所有文件的路径、大小与修改时间，用于检测变化
+    fn fingerprint(tools_dir: &Path) -> Vec<(PathBuf, u64, Option<SystemTime>)> {
+        let mut fingerprint: Vec<_> = std::fs::read_dir(tools_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                Some((entry.path(), metadata.len(), metadata.modified().ok()))
+            })
+            .collect();
+        fingerprint.sort();
+        fingerprint
+    }
+
+    fn load_manifest(tools_dir: &Path, path: &Path) -> UserToolStatus {
+        let mut status = UserToolStatus {
+            file: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            name: None,
+            loaded: false,
+            enabled: false,
+            error: None,
+        };
+
+        let manifest: UserToolManifest = match FileUtils::read_json_file(path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                status.error = Some(e);
+                return status;
+            }
+        };
+        status.name = Some(manifest.name.clone());
+        status.enabled = manifest.enabled;
+
+        match Self::build_tool(tools_dir, manifest) {
+            Ok(tool) => {
+                if ToolRegistry::register_dynamic_tool_global(Arc::new(tool)) {
+                    status.loaded = true;
+                } else {
+                    status.error = Some("与已注册的工具重名".to_string());
+                }
+            }
+            Err(e) => status.error = Some(e),
+        }
+        status
+    }
+
+    fn build_tool(tools_dir: &Path, manifest: UserToolManifest) -> Result<UserTool, String> {
+        let name = manifest.name.as_str();
+        if name.is_empty()
+            || name.len() > 64
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err("工具名称只能包含字母、数字、下划线和连字符，且不超过64个字符".to_string());
+        }
+        if !manifest.input_schema.is_object() {
+            return Err("input_schema 必须是 JSON 对象".to_string());
+        }
+
+        let script_source = match &manifest.action {
+            UserToolAction::Template { .. } => None,
+            UserToolAction::Script { source: Some(source), .. } => Some(source.clone()),
+            UserToolAction::Script { file: Some(file), .. } => Some(Self::read_script(tools_dir, file)?),
+            UserToolAction::Script { .. } => return Err("脚本动作需要 source 或 file".to_string()),
+        };
+        UserTool::new(manifest, script_source.as_deref())
+    }
+
+    /// 读取脚本文件（只允许工具目录内的文件）
+    fn read_script(tools_dir: &Path, file: &str) -> Result<String, String> {
+        let tools_dir = tools_dir.canonicalize().map_err(|e| e.to_string())?;
+        let script_path = tools_dir
+            .join(file)
+            .canonicalize()
+            .map_err(|e| format!("脚本文件 {} 不存在: {}", file, e))?;
+        if !script_path.starts_with(&tools_dir) {
+            return Err(format!("脚本文件 {} 不在用户工具目录内", file));
+        }
+        std::fs::read_to_string(&script_path).map_err(|e| format!("读取脚本文件 {} 失败: {}", file, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_file_must_stay_in_tools_dir() {
+        let root = std::env::temp_dir().join(format!("ccc-user-tools-{}", std::process::id()));
+        let tools_dir = root.join("user-tools");
+        std::fs::create_dir_all(&tools_dir).unwrap();
+        std::fs::write(tools_dir.join("ok.rhai"), "1 + 1").unwrap();
+        std::fs::write(root.join("outside.rhai"), "1").unwrap();
+
+        assert_eq!(UserToolService::read_script(&tools_dir, "ok.rhai").unwrap(), "1 + 1");
+        assert!(UserToolService::read_script(&tools_dir, "../outside.rhai").is_err());
+
+        let manifest: UserToolManifest = serde_json::from_value(serde_json::json!({
+            "name": "bad name", "description": "d", "action": { "type": "template" }
+        }))
+        .unwrap();
+        assert!(UserToolService::build_tool(&tools_dir, manifest).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
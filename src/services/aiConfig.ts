@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { ToolSelection } from '@/types/events';
 
 export interface AIRole {
   name: string;
@@ -7,6 +8,10 @@ export interface AIRole {
   temperature: number;
   max_tokens: number;
   tools_enabled: boolean;
+  /** 单次回复的工具调用轮数上限（默认 5） */
+  max_tool_iterations?: number;
+  /** 角色级工具启用规则 */
+  tools?: ToolSelection;
 }
 
 export interface AIConfig {
@@ -55,6 +55,12 @@ export interface ToolCallRequest {
   parameters: Record<string, any>;
   character_uuid?: string; // 角色UUID
   context?: any; // CharacterData or other context
+  origin?: ToolCallOrigin | null; // 调用来源（写入审计日志）
+}
+
+export interface ToolCallOrigin {
+  session?: string | null;
+  model?: string | null;
 }
 
 export interface ToolResult {
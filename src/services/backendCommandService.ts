@@ -1,5 +1,13 @@
 import { invoke } from '@tauri-apps/api/core'
-import type { CommandMetadata, CommandResult } from '@/types/commands'
+import type {
+  BatchCommandReport,
+  BatchCommandRequest,
+  CommandCompletions,
+  CommandMetadata,
+  CommandResult,
+  UserCommandsConfig,
+  UserCommandStatus,
+} from '@/types/commands'
 
 /**
  * 后端命令服务类 - 后端代理层
@@ -60,6 +68,18 @@ export class BackendCommandService {
     }
   }
 
+  /**
+   * 补全命令行（命令名、参数名或参数值）
+   */
+  async completeCommand(input: string): Promise<CommandCompletions> {
+    try {
+      return await invoke<CommandCompletions>('complete_command', { input })
+    } catch (error) {
+      console.error('补全命令失败:', error)
+      return { offset: 0, items: [] }
+    }
+  }
+
   /**
    * 执行命令
    */
@@ -78,6 +98,59 @@ export class BackendCommandService {
     }
   }
 
+  /**
+   * 获取用户命令配置
+   */
+  async getUserCommandsConfig(): Promise<UserCommandsConfig> {
+    return await invoke<UserCommandsConfig>('get_user_commands_config')
+  }
+
+  /**
+   * 保存用户命令配置并重新加载
+   */
+  async saveUserCommandsConfig(config: UserCommandsConfig): Promise<UserCommandStatus[]> {
+    const statuses = await invoke<UserCommandStatus[]>('save_user_commands_config', { config })
+    this.clearCache()
+    return statuses
+  }
+
+  /**
+   * 重新加载用户命令
+   */
+  async reloadUserCommands(): Promise<UserCommandStatus[]> {
+    const statuses = await invoke<UserCommandStatus[]>('reload_user_commands')
+    this.clearCache()
+    return statuses
+  }
+
+  /**
+   * 获取用户命令的加载状态
+   */
+  async getUserCommandStatus(): Promise<UserCommandStatus[]> {
+    return await invoke<UserCommandStatus[]>('get_user_command_status')
+  }
+
+  /**
+   * 获取可批量执行的命令
+   */
+  async getBatchCommands(): Promise<CommandMetadata[]> {
+    return await invoke<CommandMetadata[]>('get_batch_commands')
+  }
+
+  /**
+   * 对多个角色批量执行命令或提示词（进度通过 batch-progress 事件推送）
+   */
+  async runBatchCommand(request: BatchCommandRequest): Promise<BatchCommandReport> {
+    return await invoke<BatchCommandReport>('run_batch_command', { request })
+  }
+
+  /**
+   * 取消正在运行的批量任务
+   */
+  async cancelBatchCommand(batchId: string): Promise<boolean> {
+    return await invoke<boolean>('cancel_batch_command', { batchId })
+  }
+
   /**
    * 清除缓存
    */
@@ -15,6 +15,81 @@ export interface ApiConfig {
   default: boolean;
   /** 是否启用 */
   enabled: boolean;
+  /** 提示词模式（chat 接口 / 文本补全 / KoboldCpp） */
+  prompt_mode?: PromptMode;
+  /** 文本补全模式使用的指令模板标识 */
+  instruct_template?: string | null;
+  /** 生成参数预设 */
+  generation_presets?: GenerationPreset[];
+  /** 默认使用的生成参数预设名称 */
+  default_preset?: string | null;
+  /** 默认配置失败时是否作为备用配置 */
+  failover?: boolean;
+  /** 网络选项（代理、附加请求头、自定义 CA 与超时） */
+  network?: NetworkOptions;
+  /** 接口方言（Azure 下 model 填写部署名称） */
+  dialect?: ApiDialect;
+  /** Azure OpenAI 的 api-version */
+  api_version?: string | null;
+}
+
+/**
+ * 接口方言
+ */
+export type ApiDialect = 'auto' | 'openai' | 'azure' | 'openrouter';
+
+/**
+ * API 配置的网络选项
+ */
+export interface NetworkOptions {
+  /** 代理地址（http:// 或 https://） */
+  proxy?: string | null;
+  /** 附加请求头 */
+  headers?: Record<string, string>;
+  /** 自定义 CA 证书文件路径（PEM） */
+  ca_bundle?: string | null;
+  /** 请求超时（秒） */
+  timeout_secs?: number | null;
+  /** 连接超时（秒） */
+  connect_timeout_secs?: number | null;
+}
+
+/**
+ * 生成参数预设
+ */
+export interface GenerationPreset {
+  name: string;
+  temperature?: number | null;
+  top_p?: number | null;
+  top_k?: number | null;
+  min_p?: number | null;
+  frequency_penalty?: number | null;
+  presence_penalty?: number | null;
+  repetition_penalty?: number | null;
+  seed?: number | null;
+  max_tokens?: number | null;
+  stop?: string[];
+}
+
+/**
+ * 提示词模式
+ */
+export type PromptMode = 'chat' | 'completion' | 'kobold';
+
+/**
+ * 文本补全指令模板
+ */
+export interface InstructTemplate {
+  id: string;
+  name: string;
+  bos: string;
+  system_prefix: string;
+  system_suffix: string;
+  user_prefix: string;
+  user_suffix: string;
+  assistant_prefix: string;
+  assistant_suffix: string;
+  system_as_user: boolean;
 }
 
 export interface ApiListResponse {
@@ -28,6 +103,14 @@ export interface CreateApiRequest {
   model?: string;
   default?: boolean;
   enabled?: boolean;
+  prompt_mode?: PromptMode;
+  instruct_template?: string;
+  generation_presets?: GenerationPreset[];
+  default_preset?: string;
+  failover?: boolean;
+  network?: NetworkOptions;
+  dialect?: ApiDialect;
+  api_version?: string;
 }
 
 export interface UpdateApiRequest extends Partial<ApiConfig> {
@@ -54,6 +137,33 @@ export interface ModelInfo {
   object: string;
   created?: number;
   owned_by?: string;
+  /** 上下文长度（服务端提供时） */
+  context_length?: number;
+  /** 价格（美元 / Token，服务端提供时） */
+  pricing?: ModelPricing;
+  /** 最大输出 Token 数（服务端提供时） */
+  max_output_tokens?: number;
+  /** 是否支持工具调用（服务端提供时） */
+  supports_tools?: boolean;
+  /** 是否支持图片输入（服务端提供时） */
+  supports_vision?: boolean;
+}
+
+export interface ModelPricing {
+  prompt?: number | null;
+  completion?: number | null;
+}
+
+/**
+ * 模型能力（内置推断、接口获取与用户覆盖合并后的结果）
+ */
+export interface ModelCapabilities {
+  model: string;
+  context_window?: number | null;
+  max_output_tokens?: number | null;
+  supports_tools?: boolean | null;
+  supports_vision?: boolean | null;
+  tokenizer?: string | null;
 }
 
 /**
@@ -81,6 +191,16 @@ export interface ChatMessage {
   tool_calls?: ToolCall[];
   tool_call_id?: string;
   timestamp?: number; // 消息时间戳（毫秒）
+  /** 实际生成该回复的 API 配置与模型 */
+  served_by?: ServedBy;
+}
+
+/**
+ * 生成回复的 API 配置与模型
+ */
+export interface ServedBy {
+  profile: string;
+  model: string;
 }
 
 /**
@@ -109,8 +229,12 @@ export interface ChatCompletionRequest {
   temperature?: number;
   max_tokens?: number;
   top_p?: number;
+  top_k?: number;
+  min_p?: number;
   frequency_penalty?: number;
   presence_penalty?: number;
+  repetition_penalty?: number;
+  seed?: number;
   stop?: string | string[];
   stream?: boolean;
   tools?: ChatTool[];
@@ -144,6 +268,51 @@ export interface ChatTool {
   function: ChatToolFunction;
 }
 
+/**
+ * MCP 服务器连接方式
+ */
+export type McpTransport =
+  | { type: 'stdio'; command: string; args?: string[]; env?: Record<string, string> }
+  | { type: 'http'; url: string; network?: NetworkOptions };
+
+/**
+ * MCP 服务器配置
+ */
+export interface McpServerConfig {
+  name: string;
+  enabled: boolean;
+  transport: McpTransport;
+}
+
+/**
+ * MCP 服务器连接状态
+ */
+export interface McpServerStatus {
+  name: string;
+  connected: boolean;
+  tools: string[];
+  error?: string | null;
+}
+
+/**
+ * 本地端口 MCP 服务器信息（外部客户端须在 initialize 的 params._meta.token 中提供令牌）
+ */
+export interface McpServerInfo {
+  address: string;
+  token: string;
+}
+
+/**
+ * 用户工具清单加载状态
+ */
+export interface UserToolStatus {
+  file: string;
+  name?: string | null;
+  loaded: boolean;
+  enabled: boolean;
+  error?: string | null;
+}
+
 /**
  * 使用统计信息
  */
@@ -194,6 +363,8 @@ export interface ChatCompletionResponse {
   usage: Usage;
   /** 中间消息（包括 assistant with tool_calls 和 tool results） */
   intermediate_messages?: ChatMessage[];
+  /** 实际生成回复的 API 配置与模型 */
+  served_by?: ServedBy;
 }
 
 /**
@@ -164,6 +164,23 @@ export interface CharacterData {
   card: TavernCardV2;
   backgroundPath: string; // card.png 路径（绝对路径）
   thumbnailPath: string; // thumbnail.png 路径（绝对路径）
+  tokenCounts?: CardTokenCounts; // 各字段 Token 计数缓存
+}
+
+/**
+ * 单个字段的 Token 计数
+ */
+export interface FieldTokenCount {
+  hash: string;
+  tokens: number;
+}
+
+/**
+ * 角色卡各字段的 Token 计数（字段路径如 description、alternate_greetings[0]、character_book.entries[2]）
+ */
+export interface CardTokenCounts {
+  tokenizer: string;
+  fields: Record<string, FieldTokenCount>;
 }
 
 /**
@@ -213,6 +230,34 @@ export interface TokenCountResult {
   text: string;
   token_count: number;
   char_count: number;
+  /** 计数使用的分词器 */
+  tokenizer: string;
+}
+
+/**
+ * 字段的 Token 类别：常驻 / 条件触发 / 不发送
+ */
+export type TokenCategory = 'permanent' | 'conditional' | 'excluded';
+
+/**
+ * 单个字段的 Token 统计
+ */
+export interface FieldTokenBreakdown {
+  path: string;
+  label?: string;
+  tokens: number;
+  category: TokenCategory;
+}
+
+/**
+ * 角色卡 Token 分布报告
+ */
+export interface CardTokenBreakdown {
+  tokenizer: string;
+  fields: FieldTokenBreakdown[];
+  permanent_tokens: number;
+  conditional_tokens: number;
+  total_tokens: number;
 }
 
 /**
@@ -3,6 +3,8 @@
  * 与 Rust 侧的命令结构体对应
  */
 
+import type { ToolSelection } from './events'
+
 /**
  * 命令元数据（与 Rust CommandMetadata 对应）
  */
@@ -23,6 +25,47 @@ export interface CommandMetadata {
   requires_confirmation: boolean
   /** 确认提示消息（可选） */
   confirmation_message?: string
+  /** 参数声明（按位置参数在前的顺序） */
+  arguments: CommandArgument[]
+}
+
+/**
+ * 命令参数类型（text 为剩余的整行文本）
+ */
+export type CommandArgumentType = 'string' | 'integer' | 'number' | 'boolean' | 'text'
+
+/**
+ * 命令参数声明（与 Rust CommandArgument 对应）
+ */
+export interface CommandArgument {
+  name: string
+  description: string
+  type: CommandArgumentType
+  /** 位置参数按顺序填写，命名参数以 --名称 值 或 --名称=值 填写 */
+  positional: boolean
+  required: boolean
+  /** 可选值 */
+  choices?: string[]
+  default?: any
+}
+
+/**
+ * 补全候选项
+ */
+export interface CommandCompletion {
+  value: string
+  description?: string
+}
+
+/**
+ * 补全结果（与 Rust CommandCompletions 对应）
+ */
+export interface CommandCompletions {
+  /** 被替换文本在输入中的起始位置（字符偏移） */
+  offset: number
+  /** 正在补全的参数（补全命令名时为空） */
+  argument?: string
+  items: CommandCompletion[]
 }
 
 /**
@@ -43,3 +86,96 @@ export interface CommandResult {
   /** 返回数据（可选） */
   data?: any
 }
+
+/**
+ * 用户定义的命令（展开为提示词后发送给 AI）
+ */
+export interface UserCommandDefinition {
+  /** 命令标识（输入 /标识 调用） */
+  id: string
+  description: string
+  icon?: string
+  category?: CommandCategory
+  priority?: number
+  enabled: boolean
+  requires_confirmation: boolean
+  /** 提示词模板：{{char}}、{{user}}、{{card.字段}}、{{参数名}} */
+  prompt: string
+  arguments: CommandArgument[]
+  /** 本次生成使用的 AI 角色 */
+  ai_role?: string
+  /** 本次生成使用的工具档案 */
+  tool_profile?: string
+  /** 本次生成的工具启用规则 */
+  tools?: ToolSelection
+}
+
+/**
+ * 用户命令配置（与 Rust UserCommandsConfig 对应）
+ */
+export interface UserCommandsConfig {
+  /** 模板中 {{user}} 的替换值 */
+  user_name: string
+  commands: UserCommandDefinition[]
+}
+
+/**
+ * 用户命令的加载状态
+ */
+export interface UserCommandStatus {
+  id: string
+  loaded: boolean
+  enabled: boolean
+  error?: string
+}
+
+/**
+ * 批量执行的目标角色
+ */
+export type BatchTarget =
+  | { type: 'uuids'; uuids: string[] }
+  | { type: 'tag'; tag: string }
+
+/**
+ * 批量执行请求（command 与 prompt 二选一）
+ */
+export interface BatchCommandRequest {
+  target: BatchTarget
+  /** 要执行的命令行（如 /snapshot 批量维护前） */
+  command?: string
+  /** 直接发送给 AI 的提示词（支持 {{char}}、{{user}}、{{card.字段}}） */
+  prompt?: string
+  /** 相邻两个角色之间的等待时间（毫秒） */
+  delay_ms?: number
+  /** 只预览，不实际执行 */
+  dry_run?: boolean
+  /** 遇到失败时停止处理剩余角色 */
+  stop_on_error?: boolean
+}
+
+/**
+ * 单个角色的执行结果
+ */
+export interface BatchItemResult {
+  uuid: string
+  name?: string
+  success: boolean
+  /** 未执行（预览、取消或因先前失败而停止） */
+  skipped: boolean
+  message?: string
+  error?: string
+  data?: any
+}
+
+/**
+ * 批量执行报告（与 Rust BatchCommandReport 对应）
+ */
+export interface BatchCommandReport {
+  batch_id: string
+  dry_run: boolean
+  cancelled: boolean
+  total: number
+  succeeded: number
+  failed: number
+  results: BatchItemResult[]
+}
@@ -1,5 +1,6 @@
 import type { CharacterData } from './character'
 import type { ChatMessage } from './api'
+import type { BatchItemResult } from './commands'
 
 // 角色加载事件载荷
 export interface CharacterLoadedPayload {
@@ -66,6 +67,112 @@ export interface ToolExecutedPayload {
   timestamp: number
 }
 
+// 角色卡字段级修改
+export interface FieldChange {
+  path: string
+  before?: any
+  after?: any
+}
+
+// 工具调用审计记录
+export interface ToolAuditRecord {
+  id: string
+  timestamp: number
+  tool_name: string
+  session?: string | null
+  model?: string | null
+  arguments: Record<string, any>
+  success: boolean
+  result?: any
+  error?: string | null
+  duration_ms: number
+  changes: FieldChange[]
+  reverts?: string
+}
+
+// 审计日志查询条件
+export interface ToolAuditQuery {
+  tool_name?: string | null
+  session?: string | null
+  success?: boolean | null
+  since?: number | null
+  until?: number | null
+  changes_only?: boolean
+  offset?: number
+  limit?: number | null
+}
+
+// 工具调用审批请求事件载荷
+export interface ToolApprovalRequestedPayload {
+  uuid: string
+  approval_id: string
+  tool_name: string
+  arguments: Record<string, any>
+  changes: FieldChange[]
+  preview_error?: string
+  timestamp: number
+}
+
+// 工具审批策略
+export type ApprovalPolicy = 'auto' | 'ask' | 'deny'
+
+// 工具调用审批配置
+export interface ToolApprovalConfig {
+  enabled: boolean
+  default_policy: ApprovalPolicy
+  auto_approve_read_only: boolean
+  tools: Record<string, ApprovalPolicy>
+  timeout_secs: number
+}
+
+// 一层工具启用规则（工具名称优先于分类）
+export interface ToolSelection {
+  read_only?: boolean
+  categories?: Record<string, boolean>
+  tools?: Record<string, boolean>
+}
+
+// 可切换的工具档案
+export interface ToolProfile extends ToolSelection {
+  name: string
+  description?: string
+}
+
+// 工具启用设置
+export interface ToolSettings {
+  active_profile?: string | null
+  profiles: ToolProfile[]
+}
+
+// 用户对审批请求的回复
+export type ApprovalDecision =
+  | { decision: 'approve' }
+  | { decision: 'reject'; reason?: string }
+  | { decision: 'edit'; arguments: Record<string, any> }
+
+// AI 提议的一次修改
+export interface ProposedChange {
+  id: string
+  tool_name: string
+  arguments: Record<string, any>
+  changes: FieldChange[]
+  conflict?: string
+  created_at: number
+}
+
+// 角色的待审阅修改集
+export interface ChangeSet {
+  uuid: string
+  proposals: ProposedChange[]
+}
+
+// 修改集更新事件载荷
+export interface ChangeSetUpdatedPayload {
+  uuid: string
+  change_set: ChangeSet
+  timestamp: number
+}
+
 // 会话卸载事件载荷
 export interface SessionUnloadedPayload {
   uuid: string
@@ -116,6 +223,15 @@ export interface ProgressPayload {
   timestamp: number
 }
 
+// 批量命令进度事件载荷（每处理完一个角色发送一次）
+export interface BatchProgressPayload {
+  batch_id: string
+  completed: number
+  total: number
+  result: BatchItemResult
+  timestamp: number
+}
+
 // 会话信息
 export interface SessionInfo {
   uuid: string
@@ -124,6 +240,16 @@ export interface SessionInfo {
   last_active: string
   status: SessionStatus
   last_context_tokens: number
+  settings: SessionSettings
+}
+
+// 会话级偏好设置
+export interface SessionSettings {
+  generation_preset?: string | null
+  ai_role?: string | null
+  proposal_mode?: boolean
+  tool_profile?: string | null
+  tools?: ToolSelection
 }
 
 // 会话状态